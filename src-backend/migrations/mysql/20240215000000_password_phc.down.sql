@@ -0,0 +1,4 @@
+-- 只能还原仍为 PBKDF2 的哈希, 已升级为 Argon2id 的用户需要重置密码
+ALTER TABLE users ADD COLUMN salt VARCHAR(255) NOT NULL DEFAULT '' AFTER password;
+
+UPDATE users SET salt = SUBSTRING_INDEX(SUBSTRING_INDEX(password, '$', 4), '$', -1), password = SUBSTRING_INDEX(password, '$', -1) WHERE password LIKE '$pbkdf2-sha256-hex$%';
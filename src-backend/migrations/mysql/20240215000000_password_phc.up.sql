@@ -0,0 +1,4 @@
+-- 旧的 PBKDF2 哈希与盐合并为自描述的哈希字符串, 之后不再需要 salt 列
+UPDATE users SET password = CONCAT('$pbkdf2-sha256-hex$i=101101$', salt, '$', password) WHERE salt <> '';
+
+ALTER TABLE users DROP COLUMN salt;
@@ -0,0 +1,13 @@
+-- 外部身份提供方 (OIDC) 的账户与本地用户的关联, 以 (issuer, subject) 唯一标识
+CREATE TABLE identities (
+  id INT NOT NULL AUTO_INCREMENT,
+  user_id INT NOT NULL,
+  issuer VARCHAR(255) NOT NULL,
+  subject VARCHAR(255) NOT NULL,
+  email VARCHAR(255) NULL DEFAULT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  last_login_at TIMESTAMP NULL DEFAULT NULL,
+  PRIMARY KEY (id),
+  UNIQUE KEY uk_identities_issuer_subject (issuer, subject),
+  KEY idx_identities_user_id (user_id)
+);
@@ -0,0 +1,21 @@
+-- TOTP 两步验证, enabled_at 为空表示已生成密钥但尚未确认
+CREATE TABLE totp_credentials (
+  user_id INT NOT NULL,
+  secret VARCHAR(64) NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  enabled_at TIMESTAMP NULL DEFAULT NULL,
+  -- 最近一次通过验证的时间步, 同一验证码不能重复使用
+  last_used_step BIGINT NULL DEFAULT NULL,
+  PRIMARY KEY (user_id)
+);
+
+-- 一次性恢复码, 只保存哈希值
+CREATE TABLE recovery_codes (
+  id INT NOT NULL AUTO_INCREMENT,
+  user_id INT NOT NULL,
+  code_hash VARCHAR(64) NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  used_at TIMESTAMP NULL DEFAULT NULL,
+  PRIMARY KEY (id),
+  KEY idx_recovery_codes_user_id (user_id)
+);
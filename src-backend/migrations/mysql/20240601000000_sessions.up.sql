@@ -0,0 +1,23 @@
+-- 每次登录对应一个会话, 与同一次登录轮换出的刷新令牌 family 一一对应
+CREATE TABLE sessions (
+  id INT NOT NULL AUTO_INCREMENT,
+  user_id INT NOT NULL,
+  family_id VARCHAR(64) NOT NULL,
+  device_name VARCHAR(100) NULL DEFAULT NULL,
+  user_agent VARCHAR(512) NULL DEFAULT NULL,
+  ip_address VARCHAR(45) NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  last_active_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  expires_at TIMESTAMP NOT NULL,
+  revoked_at TIMESTAMP NULL DEFAULT NULL,
+  PRIMARY KEY (id),
+  UNIQUE KEY uk_sessions_family_id (family_id),
+  KEY idx_sessions_user_id (user_id)
+);
+
+-- 为已有的刷新令牌 family 补建会话, 升级后仍可用刷新令牌换取带会话标识的 access token
+INSERT INTO sessions (user_id, family_id, ip_address, created_at, last_active_at, expires_at)
+SELECT user_id, family_id, '', MIN(created_at), MAX(created_at), MAX(expires_at)
+FROM refresh_tokens
+WHERE revoked_at IS NULL
+GROUP BY user_id, family_id;
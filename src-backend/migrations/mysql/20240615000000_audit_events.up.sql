@@ -0,0 +1,16 @@
+-- 安全审计事件, 只追加不修改
+CREATE TABLE audit_events (
+  id BIGINT NOT NULL AUTO_INCREMENT,
+  actor_id INT NULL DEFAULT NULL,
+  action VARCHAR(64) NOT NULL,
+  target VARCHAR(255) NULL DEFAULT NULL,
+  ip_address VARCHAR(45) NULL DEFAULT NULL,
+  user_agent VARCHAR(512) NULL DEFAULT NULL,
+  outcome SMALLINT NOT NULL,
+  detail VARCHAR(255) NULL DEFAULT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (id),
+  KEY idx_audit_events_actor_id (actor_id),
+  KEY idx_audit_events_target (target),
+  KEY idx_audit_events_created_at (created_at)
+);
@@ -0,0 +1,4 @@
+ALTER TABLE todos
+  DROP KEY idx_todos_parent_id,
+  DROP COLUMN auto_complete,
+  DROP COLUMN parent_id;
@@ -0,0 +1,4 @@
+ALTER TABLE todos
+  ADD COLUMN parent_id INT NULL DEFAULT NULL,
+  ADD COLUMN auto_complete BOOLEAN NOT NULL DEFAULT FALSE,
+  ADD KEY idx_todos_parent_id (user_id, parent_id);
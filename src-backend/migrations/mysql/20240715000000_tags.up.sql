@@ -0,0 +1,18 @@
+-- 用户自定义标签, 通过 todo_tags 与任务多对多关联
+CREATE TABLE tags (
+  id INT NOT NULL AUTO_INCREMENT,
+  user_id INT NOT NULL,
+  name VARCHAR(64) NOT NULL,
+  color VARCHAR(7) NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (id),
+  UNIQUE KEY uk_tags_user_id_name (user_id, name)
+);
+
+CREATE TABLE todo_tags (
+  todo_id INT NOT NULL,
+  tag_id INT NOT NULL,
+  PRIMARY KEY (todo_id, tag_id),
+  KEY idx_todo_tags_tag_id (tag_id)
+);
@@ -0,0 +1,15 @@
+ALTER TABLE users ADD COLUMN email_verified_at TIMESTAMPTZ;
+
+-- 邮箱验证和密码重置等一次性令牌, 只保存令牌的哈希值
+CREATE TABLE user_tokens (
+  id SERIAL PRIMARY KEY,
+  user_id INTEGER NOT NULL,
+  purpose VARCHAR(32) NOT NULL,
+  token_hash VARCHAR(64) NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  expires_at TIMESTAMPTZ NOT NULL,
+  used_at TIMESTAMPTZ,
+  CONSTRAINT uk_user_tokens_token_hash UNIQUE (token_hash)
+);
+
+CREATE INDEX idx_user_tokens_user_id ON user_tokens (user_id, purpose);
@@ -0,0 +1,16 @@
+-- 个人 API 令牌, 只保存令牌的哈希值, scopes 以空格分隔
+CREATE TABLE api_tokens (
+  id SERIAL PRIMARY KEY,
+  user_id INTEGER NOT NULL,
+  name VARCHAR(255) NOT NULL,
+  token_prefix VARCHAR(16) NOT NULL,
+  token_hash VARCHAR(64) NOT NULL,
+  scopes VARCHAR(255) NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  last_used_at TIMESTAMPTZ,
+  expires_at TIMESTAMPTZ,
+  revoked_at TIMESTAMPTZ,
+  CONSTRAINT uk_api_tokens_token_hash UNIQUE (token_hash)
+);
+
+CREATE INDEX idx_api_tokens_user_id ON api_tokens (user_id);
@@ -0,0 +1,13 @@
+-- 外部身份提供方 (OIDC) 的账户与本地用户的关联, 以 (issuer, subject) 唯一标识
+CREATE TABLE identities (
+  id SERIAL PRIMARY KEY,
+  user_id INTEGER NOT NULL,
+  issuer VARCHAR(255) NOT NULL,
+  subject VARCHAR(255) NOT NULL,
+  email VARCHAR(255),
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  last_login_at TIMESTAMPTZ,
+  CONSTRAINT uk_identities_issuer_subject UNIQUE (issuer, subject)
+);
+
+CREATE INDEX idx_identities_user_id ON identities (user_id);
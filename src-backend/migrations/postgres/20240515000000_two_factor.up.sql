@@ -0,0 +1,20 @@
+-- TOTP 两步验证, enabled_at 为空表示已生成密钥但尚未确认
+CREATE TABLE totp_credentials (
+  user_id INTEGER PRIMARY KEY,
+  secret VARCHAR(64) NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  enabled_at TIMESTAMPTZ,
+  -- 最近一次通过验证的时间步, 同一验证码不能重复使用
+  last_used_step BIGINT
+);
+
+-- 一次性恢复码, 只保存哈希值
+CREATE TABLE recovery_codes (
+  id SERIAL PRIMARY KEY,
+  user_id INTEGER NOT NULL,
+  code_hash VARCHAR(64) NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  used_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_recovery_codes_user_id ON recovery_codes (user_id);
@@ -0,0 +1,16 @@
+-- 安全审计事件, 只追加不修改
+CREATE TABLE audit_events (
+  id BIGSERIAL PRIMARY KEY,
+  actor_id INTEGER,
+  action VARCHAR(64) NOT NULL,
+  target VARCHAR(255),
+  ip_address VARCHAR(45),
+  user_agent VARCHAR(512),
+  outcome SMALLINT NOT NULL,
+  detail VARCHAR(255),
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_events_actor_id ON audit_events (actor_id);
+CREATE INDEX idx_audit_events_target ON audit_events (target);
+CREATE INDEX idx_audit_events_created_at ON audit_events (created_at);
@@ -0,0 +1,3 @@
+DROP INDEX idx_todos_parent_id;
+ALTER TABLE todos DROP COLUMN auto_complete;
+ALTER TABLE todos DROP COLUMN parent_id;
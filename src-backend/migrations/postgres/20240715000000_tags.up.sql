@@ -0,0 +1,18 @@
+-- 用户自定义标签, 通过 todo_tags 与任务多对多关联
+CREATE TABLE tags (
+  id SERIAL PRIMARY KEY,
+  user_id INTEGER NOT NULL,
+  name VARCHAR(64) NOT NULL,
+  color VARCHAR(7) NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  CONSTRAINT uk_tags_user_id_name UNIQUE (user_id, name)
+);
+
+CREATE TABLE todo_tags (
+  todo_id INTEGER NOT NULL,
+  tag_id INTEGER NOT NULL,
+  PRIMARY KEY (todo_id, tag_id)
+);
+
+CREATE INDEX idx_todo_tags_tag_id ON todo_tags (tag_id);
@@ -0,0 +1,4 @@
+-- 只能还原仍为 PBKDF2 的哈希, 已升级为 Argon2id 的用户需要重置密码
+ALTER TABLE users ADD COLUMN salt TEXT NOT NULL DEFAULT '';
+
+UPDATE users SET salt = substr(password, 29, 32), password = substr(password, 62) WHERE password LIKE '$pbkdf2-sha256-hex$%';
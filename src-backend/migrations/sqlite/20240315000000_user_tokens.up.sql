@@ -0,0 +1,14 @@
+ALTER TABLE users ADD COLUMN email_verified_at TEXT;
+
+-- 邮箱验证和密码重置等一次性令牌, 只保存令牌的哈希值
+CREATE TABLE user_tokens (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  user_id INTEGER NOT NULL,
+  purpose TEXT NOT NULL,
+  token_hash TEXT NOT NULL UNIQUE,
+  created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  expires_at TEXT NOT NULL,
+  used_at TEXT
+);
+
+CREATE INDEX idx_user_tokens_user_id ON user_tokens (user_id, purpose);
@@ -0,0 +1,2 @@
+-- status 沿用初始表结构中的列: 0 正常, 1 停用
+ALTER TABLE users ADD COLUMN role INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,13 @@
+-- 外部身份提供方 (OIDC) 的账户与本地用户的关联, 以 (issuer, subject) 唯一标识
+CREATE TABLE identities (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  user_id INTEGER NOT NULL,
+  issuer TEXT NOT NULL,
+  subject TEXT NOT NULL,
+  email TEXT,
+  created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  last_login_at TEXT,
+  UNIQUE (issuer, subject)
+);
+
+CREATE INDEX idx_identities_user_id ON identities (user_id);
@@ -0,0 +1,20 @@
+-- TOTP 两步验证, enabled_at 为空表示已生成密钥但尚未确认
+CREATE TABLE totp_credentials (
+  user_id INTEGER PRIMARY KEY,
+  secret TEXT NOT NULL,
+  created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  enabled_at TEXT,
+  -- 最近一次通过验证的时间步, 同一验证码不能重复使用
+  last_used_step INTEGER
+);
+
+-- 一次性恢复码, 只保存哈希值
+CREATE TABLE recovery_codes (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  user_id INTEGER NOT NULL,
+  code_hash TEXT NOT NULL,
+  created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  used_at TEXT
+);
+
+CREATE INDEX idx_recovery_codes_user_id ON recovery_codes (user_id);
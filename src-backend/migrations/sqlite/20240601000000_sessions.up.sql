@@ -0,0 +1,22 @@
+-- 每次登录对应一个会话, 与同一次登录轮换出的刷新令牌 family 一一对应
+CREATE TABLE sessions (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  user_id INTEGER NOT NULL,
+  family_id TEXT NOT NULL UNIQUE,
+  device_name TEXT,
+  user_agent TEXT,
+  ip_address TEXT NOT NULL,
+  created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  last_active_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  expires_at TEXT NOT NULL,
+  revoked_at TEXT
+);
+
+CREATE INDEX idx_sessions_user_id ON sessions (user_id);
+
+-- 为已有的刷新令牌 family 补建会话, 升级后仍可用刷新令牌换取带会话标识的 access token
+INSERT INTO sessions (user_id, family_id, ip_address, created_at, last_active_at, expires_at)
+SELECT user_id, family_id, '', MIN(created_at), MAX(created_at), MAX(expires_at)
+FROM refresh_tokens
+WHERE revoked_at IS NULL
+GROUP BY user_id, family_id;
@@ -0,0 +1,16 @@
+-- 安全审计事件, 只追加不修改
+CREATE TABLE audit_events (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  actor_id INTEGER,
+  action TEXT NOT NULL,
+  target TEXT,
+  ip_address TEXT,
+  user_agent TEXT,
+  outcome INTEGER NOT NULL,
+  detail TEXT,
+  created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_audit_events_actor_id ON audit_events (actor_id);
+CREATE INDEX idx_audit_events_target ON audit_events (target);
+CREATE INDEX idx_audit_events_created_at ON audit_events (created_at);
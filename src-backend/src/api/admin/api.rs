@@ -0,0 +1,470 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{path, Query, State},
+    Json,
+};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::{
+        middleware::{auth::AuthUser, client::ClientInfo},
+        request::{default_pagination, success_response, Response},
+        user::api::UserResponse,
+    },
+    application::{
+        account::service::AccountService, audit::service::AuditContext, auth::service::AuthService,
+        user::service::UserService,
+    },
+    domain::{
+        entities::user::{User, UserRole, UserStatus},
+        error::{AppError, AppResult},
+        repository::{
+            user::{UserFilter, UserQuery},
+            Page,
+        },
+    },
+};
+
+const MAX_PAGE_SIZE: u32 = 100;
+
+// 管理后台看到的用户信息, 在 UserResponse 基础上包含账户状态
+#[derive(Deserialize, Serialize, Clone)]
+pub struct AdminUserResponse {
+    #[serde(flatten)]
+    pub user: UserResponse,
+    pub status: UserStatus,
+    pub failed_login_attempts: i32,
+    pub locked_until: Option<DateTime<Local>>,
+}
+
+impl From<User> for AdminUserResponse {
+    fn from(user: User) -> Self {
+        Self {
+            status: user.status,
+            failed_login_attempts: user.failed_login_attempts,
+            locked_until: user.locked_until,
+            user: UserResponse::from(user),
+        }
+    }
+}
+
+// 校验分页参数并填充默认值, 返回 (page, page_size)
+pub(super) fn validate_pagination(
+    page: Option<u32>,
+    page_size: Option<u32>,
+) -> AppResult<(u32, u32)> {
+    let default = default_pagination();
+    let page = page.unwrap_or(default.page);
+    let page_size = page_size.unwrap_or(default.page_size);
+    if page == 0 {
+        return Err(AppError::Validation("page must be at least 1".to_string()));
+    }
+    if page_size == 0 || page_size > MAX_PAGE_SIZE {
+        return Err(AppError::Validation(format!(
+            "page_size must be between 1 and {MAX_PAGE_SIZE}"
+        )));
+    }
+    Ok((page, page_size))
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct ListUsersQuery {
+    page: Option<u32>,
+    page_size: Option<u32>,
+    q: Option<String>,
+    role: Option<UserRole>,
+    status: Option<UserStatus>,
+}
+
+impl TryFrom<ListUsersQuery> for UserQuery {
+    type Error = AppError;
+
+    fn try_from(req: ListUsersQuery) -> Result<Self, Self::Error> {
+        let (page, page_size) = validate_pagination(req.page, req.page_size)?;
+        Ok(UserQuery {
+            filter: UserFilter {
+                search: req
+                    .q
+                    .map(|q| q.trim().to_string())
+                    .filter(|q| !q.is_empty()),
+                role: req.role,
+                status: req.status,
+            },
+            page,
+            page_size,
+        })
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct UpdateRoleRequest {
+    pub role: UserRole,
+}
+
+// 管理员不能停用自己或修改自己的角色, 避免系统中失去最后一个管理员
+fn ensure_not_self(auth_user: &AuthUser, id: i32) -> AppResult<()> {
+    if auth_user.user_id == id {
+        return Err(AppError::Validation(
+            "Administrators cannot change their own role or status".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn user_response(user: User) -> Response {
+    success_response(serde_json::to_value(AdminUserResponse::from(user)).unwrap())
+}
+
+pub async fn list_users(
+    State(user_service): State<Arc<dyn UserService>>,
+    Query(query): Query<ListUsersQuery>,
+) -> AppResult<Response> {
+    let page = user_service.search_users(query.try_into()?).await?;
+    let page = Page {
+        items: page
+            .items
+            .into_iter()
+            .map(AdminUserResponse::from)
+            .collect::<Vec<_>>(),
+        total: page.total,
+        page: page.page,
+        page_size: page.page_size,
+    };
+    Ok(success_response(serde_json::to_value(page).unwrap()))
+}
+
+pub async fn get_user(
+    State(user_service): State<Arc<dyn UserService>>,
+    path::Path(id): path::Path<i32>,
+) -> AppResult<Response> {
+    let user = user_service.get_user_by_id(id).await?;
+    Ok(user_response(user))
+}
+
+pub async fn update_role(
+    State(user_service): State<Arc<dyn UserService>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    path::Path(id): path::Path<i32>,
+    playload: Json<UpdateRoleRequest>,
+) -> AppResult<Response> {
+    ensure_not_self(&auth_user, id)?;
+    let ctx = AuditContext::new(&client, Some(auth_user.user_id));
+    let user = user_service.set_role(id, playload.0.role, &ctx).await?;
+    Ok(user_response(user))
+}
+
+// 停用后立即吊销该用户所有的刷新令牌
+pub async fn suspend_user(
+    State(user_service): State<Arc<dyn UserService>>,
+    State(auth_service): State<Arc<dyn AuthService>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    path::Path(id): path::Path<i32>,
+) -> AppResult<Response> {
+    ensure_not_self(&auth_user, id)?;
+    let ctx = AuditContext::new(&client, Some(auth_user.user_id));
+    let user = user_service
+        .set_status(id, UserStatus::Suspended, &ctx)
+        .await?;
+    auth_service.logout_all(id, &ctx).await?;
+    Ok(user_response(user))
+}
+
+pub async fn reactivate_user(
+    State(user_service): State<Arc<dyn UserService>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    path::Path(id): path::Path<i32>,
+) -> AppResult<Response> {
+    let ctx = AuditContext::new(&client, Some(auth_user.user_id));
+    let user = user_service
+        .set_status(id, UserStatus::Active, &ctx)
+        .await?;
+    Ok(user_response(user))
+}
+
+pub async fn force_password_reset(
+    State(user_service): State<Arc<dyn UserService>>,
+    State(account_service): State<Arc<dyn AccountService>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    path::Path(id): path::Path<i32>,
+) -> AppResult<Response> {
+    let user = user_service.get_user_by_id(id).await?;
+    let ctx = AuditContext::new(&client, Some(auth_user.user_id));
+    account_service.force_password_reset(&user, &ctx).await?;
+    Ok(success_response(serde_json::Value::Null))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        http::{Method, StatusCode},
+        Router,
+    };
+    use serde_json::json;
+
+    use crate::api::{
+        router::build_router,
+        testing::{
+            promote_admin, register_and_login, send, test_config, test_state_with_mailer,
+            token_from_mail, TEST_PASSWORD,
+        },
+    };
+    use crate::infastructure::mail::log::LogMailer;
+
+    // 返回路由, 管理员 access token 和 mailer
+    async fn setup() -> (Router, String, std::sync::Arc<LogMailer>) {
+        let (state, mailer) = test_state_with_mailer(test_config());
+        let router = build_router(state.clone());
+        register_and_login(&router, "admin@example.com").await;
+        promote_admin(&state, "admin@example.com").await;
+        let (_, body) = send(
+            &router,
+            Method::POST,
+            "/api/auth/login",
+            None,
+            Some(json!({ "email": "admin@example.com", "password": TEST_PASSWORD })),
+        )
+        .await;
+        let token = body["data"]["token"].as_str().unwrap().to_string();
+        (router, token, mailer)
+    }
+
+    async fn login(
+        router: &Router,
+        email: &str,
+        password: &str,
+    ) -> (StatusCode, serde_json::Value) {
+        send(
+            router,
+            Method::POST,
+            "/api/auth/login",
+            None,
+            Some(json!({ "email": email, "password": password })),
+        )
+        .await
+    }
+
+    async fn user_id(router: &Router, token: &str) -> i64 {
+        let (_, body) = send(router, Method::GET, "/api/me", Some(token), None).await;
+        body["data"]["id"].as_i64().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_admin_routes_require_admin_role() {
+        let (router, admin_token, _) = setup().await;
+        let token = register_and_login(&router, "user@example.com").await;
+
+        let (status, _) = send(&router, Method::GET, "/api/admin/users", None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, body) =
+            send(&router, Method::GET, "/api/admin/users", Some(&token), None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"], "FORBIDDEN");
+
+        let (status, body) = send(&router, Method::GET, "/api/me", Some(&admin_token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["role"], "admin");
+
+        // 角色变更在下一次请求时立即生效
+        let id = user_id(&router, &token).await;
+        let (status, body) = send(
+            &router,
+            Method::PUT,
+            &format!("/api/admin/users/{id}/role"),
+            Some(&admin_token),
+            Some(json!({ "role": "admin" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["role"], "admin");
+        let (status, _) = send(&router, Method::GET, "/api/admin/users", Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let admin_id = user_id(&router, &admin_token).await;
+        let (status, _) = send(
+            &router,
+            Method::PUT,
+            &format!("/api/admin/users/{admin_id}/role"),
+            Some(&admin_token),
+            Some(json!({ "role": "user" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_list_and_search_users() {
+        let (router, token, _) = setup().await;
+        for email in ["alice@example.com", "bob@example.com", "alina@test.org"] {
+            register_and_login(&router, email).await;
+        }
+
+        let (status, body) = send(
+            &router,
+            Method::GET,
+            "/api/admin/users?page_size=2",
+            Some(&token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["total"], 4);
+        assert_eq!(body["data"]["items"].as_array().unwrap().len(), 2);
+        assert_eq!(body["data"]["items"][0]["status"], "active");
+        assert!(body["data"]["items"][0].get("password").is_none());
+
+        let (_, body) = send(
+            &router,
+            Method::GET,
+            "/api/admin/users?q=ALI",
+            Some(&token),
+            None,
+        )
+        .await;
+        assert_eq!(body["data"]["total"], 2);
+
+        let (_, body) = send(
+            &router,
+            Method::GET,
+            "/api/admin/users?role=admin",
+            Some(&token),
+            None,
+        )
+        .await;
+        assert_eq!(body["data"]["total"], 1);
+        assert_eq!(body["data"]["items"][0]["email"], "admin@example.com");
+
+        let (status, _) = send(
+            &router,
+            Method::GET,
+            "/api/admin/users?page_size=1000",
+            Some(&token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_suspend_and_reactivate_user() {
+        let (router, admin_token, _) = setup().await;
+        let token = register_and_login(&router, "user@example.com").await;
+        let (_, body) = login(&router, "user@example.com", TEST_PASSWORD).await;
+        let refresh_token = body["data"]["refresh_token"].as_str().unwrap().to_string();
+        let id = user_id(&router, &token).await;
+
+        let (status, body) = send(
+            &router,
+            Method::POST,
+            &format!("/api/admin/users/{id}/suspend"),
+            Some(&admin_token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["status"], "suspended");
+
+        // 已签发的令牌和新的登录都会被拒绝
+        let (status, body) = send(&router, Method::GET, "/api/todo", Some(&token), None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"], "ACCOUNT_SUSPENDED");
+        let (status, body) = login(&router, "user@example.com", TEST_PASSWORD).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"], "ACCOUNT_SUSPENDED");
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/auth/refresh",
+            None,
+            Some(json!({ "refresh_token": refresh_token })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, body) = send(
+            &router,
+            Method::GET,
+            "/api/admin/users?status=suspended",
+            Some(&admin_token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["total"], 1);
+
+        let (status, body) = send(
+            &router,
+            Method::POST,
+            &format!("/api/admin/users/{id}/reactivate"),
+            Some(&admin_token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["status"], "active");
+        let (status, _) = login(&router, "user@example.com", TEST_PASSWORD).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let admin_id = user_id(&router, &admin_token).await;
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            &format!("/api/admin/users/{admin_id}/suspend"),
+            Some(&admin_token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/admin/users/9999/suspend",
+            Some(&admin_token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_force_password_reset() {
+        let (router, admin_token, mailer) = setup().await;
+        let token = register_and_login(&router, "user@example.com").await;
+        let id = user_id(&router, &token).await;
+
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            &format!("/api/admin/users/{id}/password-reset"),
+            Some(&admin_token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, _) = login(&router, "user@example.com", TEST_PASSWORD).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let reset_token = token_from_mail(&mailer, "user@example.com");
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/auth/reset-password",
+            None,
+            Some(json!({
+                "token": reset_token,
+                "password": "a-brand-new-password",
+                "password_confirmation": "a-brand-new-password",
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = login(&router, "user@example.com", "a-brand-new-password").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
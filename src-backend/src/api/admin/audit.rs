@@ -0,0 +1,437 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Query, State},
+    http::header,
+    response::IntoResponse,
+};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::{
+        admin::api::validate_pagination,
+        request::{success_response, Response},
+    },
+    application::audit::service::AuditService,
+    domain::{
+        entities::audit_event::AuditOutcome,
+        error::{AppError, AppResult},
+        repository::audit::{AuditFilter, AuditQuery},
+    },
+};
+
+// 导出时每次从数据库读取的事件数量
+const EXPORT_BATCH_SIZE: u32 = 500;
+
+// 时间参数为 RFC 3339 格式, since 包含在内, until 不包含
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct AuditEventsQuery {
+    page: Option<u32>,
+    page_size: Option<u32>,
+    actor_id: Option<i32>,
+    action: Option<String>,
+    target: Option<String>,
+    outcome: Option<AuditOutcome>,
+    since: Option<DateTime<Local>>,
+    until: Option<DateTime<Local>>,
+}
+
+impl TryFrom<&AuditEventsQuery> for AuditFilter {
+    type Error = AppError;
+
+    fn try_from(req: &AuditEventsQuery) -> Result<Self, Self::Error> {
+        if let (Some(since), Some(until)) = (req.since, req.until) {
+            if since >= until {
+                return Err(AppError::Validation(
+                    "since must be earlier than until".to_string(),
+                ));
+            }
+        }
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        Ok(AuditFilter {
+            actor_id: req.actor_id,
+            action: non_empty(&req.action),
+            target: non_empty(&req.target),
+            outcome: req.outcome,
+            since: req.since,
+            until: req.until,
+        })
+    }
+}
+
+impl TryFrom<AuditEventsQuery> for AuditQuery {
+    type Error = AppError;
+
+    fn try_from(req: AuditEventsQuery) -> Result<Self, Self::Error> {
+        let (page, page_size) = validate_pagination(req.page, req.page_size)?;
+        Ok(AuditQuery {
+            filter: AuditFilter::try_from(&req)?,
+            page,
+            page_size,
+        })
+    }
+}
+
+// 按时间倒序分页查询审计事件
+pub async fn list_audit_events(
+    State(audit_service): State<Arc<dyn AuditService>>,
+    Query(query): Query<AuditEventsQuery>,
+) -> AppResult<Response> {
+    let page = audit_service.search(query.try_into()?).await?;
+    Ok(success_response(serde_json::to_value(page).unwrap()))
+}
+
+// 以 JSON Lines 格式导出所有符合条件的事件, 按 id 升序分批读取, 不受分页参数限制
+pub async fn export_audit_events(
+    State(audit_service): State<Arc<dyn AuditService>>,
+    Query(query): Query<AuditEventsQuery>,
+) -> AppResult<axum::response::Response> {
+    let filter = AuditFilter::try_from(&query)?;
+    // 状态为下一批的起始 id, None 表示已经读完
+    let stream = futures_util::stream::try_unfold(Some(0), move |after_id| {
+        let audit_service = audit_service.clone();
+        let filter = filter.clone();
+        async move {
+            let Some(after_id) = after_id else {
+                return Ok(None);
+            };
+            let events = audit_service
+                .list_after(&filter, after_id, EXPORT_BATCH_SIZE)
+                .await
+                .inspect_err(|err| log::error!("failed to export audit events: {err}"))?;
+            let Some(last) = events.last() else {
+                return Ok(None);
+            };
+            let next = (events.len() == EXPORT_BATCH_SIZE as usize).then_some(last.id);
+            let mut lines = Vec::new();
+            for event in &events {
+                serde_json::to_writer(&mut lines, event).unwrap();
+                lines.push(b'\n');
+            }
+            Ok::<_, AppError>(Some((Bytes::from(lines), next)))
+        }
+    });
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"audit-events.jsonl\"",
+            ),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        Router,
+    };
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    use crate::api::{
+        router::build_router,
+        testing::{
+            promote_admin, register_and_login, send, test_config, test_state_with_mailer,
+            token_from_mail, TEST_PASSWORD,
+        },
+    };
+
+    // 返回路由和管理员 access token
+    async fn setup() -> (Router, String) {
+        let (state, _) = test_state_with_mailer(test_config());
+        let router = build_router(state.clone());
+        register_and_login(&router, "admin@example.com").await;
+        promote_admin(&state, "admin@example.com").await;
+        let (_, body) = send(
+            &router,
+            Method::POST,
+            "/api/auth/login",
+            None,
+            Some(json!({ "email": "admin@example.com", "password": TEST_PASSWORD })),
+        )
+        .await;
+        (router, body["data"]["token"].as_str().unwrap().to_string())
+    }
+
+    async fn login(router: &Router, email: &str, password: &str) -> StatusCode {
+        send(
+            router,
+            Method::POST,
+            "/api/auth/login",
+            None,
+            Some(json!({ "email": email, "password": password })),
+        )
+        .await
+        .0
+    }
+
+    async fn events(router: &Router, token: &str, query: &str) -> Value {
+        let (status, body) = send(
+            router,
+            Method::GET,
+            &format!("/api/admin/audit-events?{query}"),
+            Some(token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        body["data"].clone()
+    }
+
+    #[tokio::test]
+    async fn test_records_login_attempts() {
+        let (router, admin_token) = setup().await;
+        register_and_login(&router, "user@example.com").await;
+        assert_eq!(
+            login(&router, "user@example.com", "wrong-password").await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            login(&router, "nobody@example.com", TEST_PASSWORD).await,
+            StatusCode::UNAUTHORIZED
+        );
+
+        let failures = events(&router, &admin_token, "action=auth.login&outcome=failure").await;
+        assert_eq!(failures["total"], 2);
+        let items = failures["items"].as_array().unwrap();
+        // 最新的事件在前
+        assert_eq!(items[0]["detail"], "unknown email");
+        assert!(items[0]["actor_id"].is_null());
+        assert_eq!(items[1]["detail"], "invalid credentials");
+        let target = items[1]["target"].as_str().unwrap().to_string();
+        assert!(target.starts_with("user:"));
+
+        // 注册和成功登录同样被记录
+        let user_events = events(&router, &admin_token, &format!("target={target}")).await;
+        assert_eq!(user_events["total"], 2);
+        let actor_id = target.trim_start_matches("user:");
+        let successes = events(
+            &router,
+            &admin_token,
+            &format!("actor_id={actor_id}&outcome=success"),
+        )
+        .await;
+        let actions: Vec<&str> = successes["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|event| event["action"].as_str().unwrap())
+            .collect();
+        assert_eq!(actions, ["auth.login", "user.register"]);
+    }
+
+    #[tokio::test]
+    async fn test_admin_actions_are_recorded() {
+        let (router, admin_token) = setup().await;
+        let token = register_and_login(&router, "user@example.com").await;
+        let (_, me) = send(&router, Method::GET, "/api/me", Some(&token), None).await;
+        let id = me["data"]["id"].as_i64().unwrap();
+
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            &format!("/api/admin/users/{id}/suspend"),
+            Some(&admin_token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let changes = events(&router, &admin_token, "action=admin.status_change").await;
+        assert_eq!(changes["total"], 1);
+        let event = &changes["items"][0];
+        assert_eq!(event["target"], format!("user:{id}"));
+        assert_eq!(event["detail"], "active -> suspended");
+        assert!(event["actor_id"].as_i64().unwrap() != id);
+
+        // 普通用户不能查看审计日志
+        let token = register_and_login(&router, "other@example.com").await;
+        let (status, _) = send(
+            &router,
+            Method::GET,
+            "/api/admin/audit-events",
+            Some(&token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_account_changes_are_recorded() {
+        let (state, mailer) = test_state_with_mailer(test_config());
+        let router = build_router(state.clone());
+        register_and_login(&router, "admin@example.com").await;
+        promote_admin(&state, "admin@example.com").await;
+        let login_body = |email: &str| {
+            send(
+                &router,
+                Method::POST,
+                "/api/auth/login",
+                None,
+                Some(json!({ "email": email, "password": TEST_PASSWORD })),
+            )
+        };
+        let (_, body) = login_body("admin@example.com").await;
+        let admin_token = body["data"]["token"].as_str().unwrap().to_string();
+        register_and_login(&router, "user@example.com").await;
+        let (_, body) = login_body("user@example.com").await;
+        let token = body["data"]["token"].as_str().unwrap().to_string();
+        let refresh_token = body["data"]["refresh_token"].clone();
+        let (_, me) = send(&router, Method::GET, "/api/me", Some(&token), None).await;
+        let id = me["data"]["id"].as_i64().unwrap();
+
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/auth/refresh",
+            None,
+            Some(json!({ "refresh_token": refresh_token })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, body) = send(
+            &router,
+            Method::POST,
+            "/api/me/tokens",
+            Some(&token),
+            Some(json!({ "name": "ci", "scopes": ["todo:read"] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let token_id = body["data"]["id"].as_i64().unwrap();
+        let (status, _) = send(
+            &router,
+            Method::DELETE,
+            &format!("/api/me/tokens/{token_id}"),
+            Some(&token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, _) = send(
+            &router,
+            Method::PATCH,
+            "/api/me",
+            Some(&token),
+            Some(json!({ "email": "changed@example.com" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let verification = token_from_mail(&mailer, "changed@example.com");
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/auth/verify-email",
+            None,
+            Some(json!({ "token": verification })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        for (action, target, detail) in [
+            ("auth.refresh", None, None),
+            (
+                "user.api_token_create",
+                Some(format!("api_token:{token_id}")),
+                Some("todo:read"),
+            ),
+            (
+                "user.api_token_revoke",
+                Some(format!("api_token:{token_id}")),
+                None,
+            ),
+            (
+                "user.email_change",
+                Some(format!("user:{id}")),
+                Some("user@example.com -> changed@example.com"),
+            ),
+            ("user.email_verify", Some(format!("user:{id}")), None),
+        ] {
+            let found = events(
+                &router,
+                &admin_token,
+                &format!("action={action}&actor_id={id}&outcome=success"),
+            )
+            .await;
+            assert_eq!(found["total"], 1, "{action}");
+            let event = &found["items"][0];
+            if let Some(target) = target {
+                assert_eq!(event["target"], target, "{action}");
+            }
+            if let Some(detail) = detail {
+                assert_eq!(event["detail"], detail, "{action}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_query() {
+        let (router, admin_token) = setup().await;
+        for query in [
+            "page=0",
+            "page_size=101",
+            "outcome=unknown",
+            "since=2024-01-02T00:00:00Z&until=2024-01-01T00:00:00Z",
+        ] {
+            let (status, _) = send(
+                &router,
+                Method::GET,
+                &format!("/api/admin/audit-events?{query}"),
+                Some(&admin_token),
+                None,
+            )
+            .await;
+            assert!(status.is_client_error(), "{query}: {status}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_json_lines() {
+        let (router, admin_token) = setup().await;
+        for _ in 0..3 {
+            login(&router, "admin@example.com", "wrong-password").await;
+        }
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri("/api/admin/audit-events/export?outcome=failure")
+            .header(header::AUTHORIZATION, format!("Bearer {admin_token}"))
+            .body(Body::empty())
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/x-ndjson"
+        );
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let lines: Vec<Value> = std::str::from_utf8(&bytes)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        // 导出按时间顺序排列
+        assert!(lines
+            .windows(2)
+            .all(|pair| pair[0]["id"].as_i64() < pair[1]["id"].as_i64()));
+        assert!(lines.iter().all(|event| event["outcome"] == "failure"));
+    }
+}
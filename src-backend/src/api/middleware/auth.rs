@@ -0,0 +1,174 @@
+use std::sync::Arc;
+
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request, State},
+    http::{header::AUTHORIZATION, request::Parts, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    application::{
+        api_token::service::{is_api_token, ApiTokenService},
+        auth::service::AuthService,
+        user::service::UserService,
+    },
+    config::AppConfig,
+    domain::{
+        entities::{api_token::ApiScope, user::UserRole},
+        error::{AppError, AppResult},
+    },
+    utils::jwt::JwtKeys,
+};
+
+use super::client::ClientInfo;
+
+// 通过认证的用户上下文, 由 auth 中间件写入请求扩展
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub user_id: i32,
+    pub role: UserRole,
+    // 通过 API 令牌认证时为令牌的 scope, 登录会话为 None 且不受 scope 限制
+    pub scopes: Option<Vec<ApiScope>>,
+    // 登录会话的 id, 通过 API 令牌认证时为 None
+    pub session_id: Option<i32>,
+}
+
+impl AuthUser {
+    // 管理员拥有所有角色的权限
+    pub fn require_role(&self, role: UserRole) -> AppResult<()> {
+        if self.role == role || self.role == UserRole::Admin {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden("Insufficient permissions".to_string()))
+        }
+    }
+
+    pub fn require_scope(&self, scope: ApiScope) -> AppResult<()> {
+        match &self.scopes {
+            Some(scopes) if !scopes.iter().any(|granted| granted.grants(scope)) => Err(
+                AppError::Forbidden(format!("Token is missing the `{}` scope", scope.as_str())),
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn unauthorized(message: &str) -> Response {
+    AppError::Unauthorized(message.to_string()).into_response()
+}
+
+fn bearer_token(parts: &Parts) -> Option<&str> {
+    parts
+        .headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+// 校验 Authorization 头中的 JWT 或 API 令牌, 失败时返回 401
+pub async fn auth(
+    State(config): State<Arc<AppConfig>>,
+    State(jwt): State<Arc<JwtKeys>>,
+    State(user_service): State<Arc<dyn UserService>>,
+    State(auth_service): State<Arc<dyn AuthService>>,
+    State(api_token_service): State<Arc<dyn ApiTokenService>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let (mut parts, body) = req.into_parts();
+    let token = match bearer_token(&parts) {
+        Some(token) => token,
+        None => return unauthorized("Missing token"),
+    };
+    let (user_id, scopes, session_id) = if is_api_token(token) {
+        match api_token_service.authenticate(token).await {
+            Ok(api_token) => (api_token.user_id, Some(api_token.scopes()), None),
+            Err(err) => return err.into_response(),
+        }
+    } else {
+        let claims = match jwt.verify_token(token) {
+            Ok(claims) => claims,
+            Err(_) => return unauthorized("Invalid token"),
+        };
+        match claims.sub.parse::<i32>() {
+            Ok(user_id) => (user_id, None, Some(claims.sid)),
+            Err(_) => return unauthorized("Invalid token"),
+        }
+    };
+
+    // 每次请求都读取用户, 删除或停用后已签发的令牌立即失效
+    let user = match user_service.get_user_by_id(user_id).await {
+        Ok(user) => user,
+        Err(AppError::NotFound(_)) => return unauthorized("Invalid token"),
+        Err(err) => return err.into_response(),
+    };
+    if user.is_suspended() {
+        return AppError::AccountSuspended.into_response();
+    }
+    // 会话被吊销后, 尚未过期的 access token 也立即失效
+    if let Some(session_id) = session_id {
+        let client = ClientInfo::from_parts(&parts, &config.server.trusted_proxies);
+        if let Err(err) = auth_service
+            .authenticate_session(user_id, session_id, &client)
+            .await
+        {
+            return err.into_response();
+        }
+    }
+
+    parts.extensions.insert(AuthUser {
+        user_id,
+        role: user.role,
+        scopes,
+        session_id,
+    });
+    next.run(Request::from_parts(parts, body)).await
+}
+
+// todo 路由: 读请求需要 todo:read, 其余请求需要 todo:write
+pub async fn require_todo_scope(auth_user: AuthUser, req: Request, next: Next) -> Response {
+    let scope = match *req.method() {
+        Method::GET | Method::HEAD => ApiScope::TodoRead,
+        _ => ApiScope::TodoWrite,
+    };
+    match auth_user.require_scope(scope) {
+        Ok(()) => next.run(req).await,
+        Err(err) => err.into_response(),
+    }
+}
+
+// 账户管理等敏感路由只接受登录会话, 拒绝 API 令牌
+pub async fn require_session(auth_user: AuthUser, req: Request, next: Next) -> Response {
+    if auth_user.scopes.is_some() {
+        return AppError::Forbidden("API tokens cannot access this endpoint".to_string())
+            .into_response();
+    }
+    next.run(req).await
+}
+
+// 仅允许管理员访问, 作为 route_layer 使用时需要放在 auth 之内
+pub async fn require_admin(auth_user: AuthUser, req: Request, next: Next) -> Response {
+    match auth_user.require_role(UserRole::Admin) {
+        Ok(()) => next.run(req).await,
+        Err(err) => err.into_response(),
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthUser
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<AuthUser>()
+            .cloned()
+            .ok_or_else(|| unauthorized("Missing token"))
+    }
+}
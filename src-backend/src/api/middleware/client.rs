@@ -0,0 +1,122 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
+};
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRef, FromRequestParts},
+    http::{header::USER_AGENT, request::Parts, HeaderMap},
+};
+use ipnet::IpNet;
+
+use crate::config::AppConfig;
+
+// 发起请求的客户端信息, 仅当直连地址是受信任的代理时才读取 X-Forwarded-For/X-Real-IP
+#[derive(Debug, Clone)]
+pub struct ClientInfo {
+    pub ip: IpAddr,
+    pub user_agent: Option<String>,
+}
+
+fn is_trusted(ip: &IpAddr, trusted_proxies: &[IpNet]) -> bool {
+    trusted_proxies.iter().any(|proxy| proxy.contains(ip))
+}
+
+fn forwarded_ip(headers: &HeaderMap, trusted_proxies: &[IpNet]) -> Option<IpAddr> {
+    // 左侧的地址由客户端任意填写, 从右往左跳过受信任的代理, 第一个地址才是可信的客户端
+    if let Some(forwarded_for) = headers
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+    {
+        for entry in forwarded_for.rsplit(',') {
+            let ip: IpAddr = entry.trim().parse().ok()?;
+            if !is_trusted(&ip, trusted_proxies) {
+                return Some(ip);
+            }
+        }
+    }
+    headers
+        .get("x-real-ip")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|ip| ip.trim().parse().ok())
+}
+
+impl ClientInfo {
+    pub fn from_parts(parts: &Parts, trusted_proxies: &[IpNet]) -> Self {
+        let connected = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        let proxied = connected
+            .filter(|ip| is_trusted(ip, trusted_proxies))
+            .and_then(|_| forwarded_ip(&parts.headers, trusted_proxies));
+        Self {
+            ip: proxied
+                .or(connected)
+                .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            user_agent: parts
+                .headers
+                .get(USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    Arc<AppConfig>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let config = Arc::<AppConfig>::from_ref(state);
+        Ok(Self::from_parts(parts, &config.server.trusted_proxies))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Request;
+
+    use super::*;
+
+    fn client_ip(peer: &str, forwarded_for: Option<&str>, trusted: &[&str]) -> IpAddr {
+        let mut builder = Request::builder();
+        if let Some(forwarded_for) = forwarded_for {
+            builder = builder.header("x-forwarded-for", forwarded_for);
+        }
+        let mut request = builder.body(()).unwrap();
+        let peer: SocketAddr = format!("{peer}:4000").parse().unwrap();
+        request.extensions_mut().insert(ConnectInfo(peer));
+        let trusted: Vec<IpNet> = trusted.iter().map(|net| net.parse().unwrap()).collect();
+        ClientInfo::from_parts(&request.into_parts().0, &trusted).ip
+    }
+
+    #[test]
+    fn test_ignores_spoofed_leftmost_entry() {
+        let ip = client_ip(
+            "10.0.0.2",
+            Some("1.2.3.4, 203.0.113.7, 10.0.0.1"),
+            &["10.0.0.0/8"],
+        );
+        assert_eq!(ip, "203.0.113.7".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_ignores_headers_from_untrusted_peer() {
+        let ip = client_ip("198.51.100.9", Some("203.0.113.7"), &["10.0.0.0/8"]);
+        assert_eq!(ip, "198.51.100.9".parse::<IpAddr>().unwrap());
+        let ip = client_ip("10.0.0.2", Some("203.0.113.7"), &[]);
+        assert_eq!(ip, "10.0.0.2".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_falls_back_to_peer_on_malformed_header() {
+        let ip = client_ip("10.0.0.2", Some("203.0.113.7, not-an-ip"), &["10.0.0.0/8"]);
+        assert_eq!(ip, "10.0.0.2".parse::<IpAddr>().unwrap());
+    }
+}
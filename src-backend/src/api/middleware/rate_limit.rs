@@ -0,0 +1,121 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::domain::error::AppError;
+
+use super::client::ClientInfo;
+
+// 超过该数量的客户端记录时清理已回满的令牌桶
+const PRUNE_THRESHOLD: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+// 按客户端 IP 的令牌桶限流, 每分钟最多 per_minute 次请求, 为 0 时不限流
+pub struct RateLimiter {
+    per_minute: u32,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(per_minute: u32) -> Self {
+        Self {
+            per_minute,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn refill_rate(&self) -> f64 {
+        self.per_minute as f64 / 60.0
+    }
+
+    // 消耗一个令牌, 令牌不足时返回需要等待的秒数
+    pub fn check(&self, ip: IpAddr) -> Result<(), u64> {
+        self.check_at(ip, Instant::now())
+    }
+
+    fn check_at(&self, ip: IpAddr, now: Instant) -> Result<(), u64> {
+        if self.per_minute == 0 {
+            return Ok(());
+        }
+        let capacity = self.per_minute as f64;
+        let rate = self.refill_rate();
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= PRUNE_THRESHOLD {
+            let full_after = Duration::from_secs(60);
+            buckets.retain(|_, bucket| now.duration_since(bucket.updated_at) < full_after);
+        }
+
+        let bucket = buckets.entry(ip).or_insert(Bucket {
+            tokens: capacity,
+            updated_at: now,
+        });
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.updated_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - bucket.tokens) / rate).ceil().max(1.0) as u64)
+        }
+    }
+}
+
+pub async fn rate_limit(
+    State(limiter): State<Arc<RateLimiter>>,
+    client: ClientInfo,
+    req: Request,
+    next: Next,
+) -> Response {
+    match limiter.check(client.ip) {
+        Ok(()) => next.run(req).await,
+        Err(retry_after) => AppError::RateLimited { retry_after }.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let limiter = RateLimiter::new(2);
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        let other: IpAddr = "10.0.0.2".parse().unwrap();
+        let start = Instant::now();
+
+        assert!(limiter.check_at(ip, start).is_ok());
+        assert!(limiter.check_at(ip, start).is_ok());
+        assert_eq!(limiter.check_at(ip, start), Err(30));
+        assert!(limiter.check_at(other, start).is_ok());
+
+        assert!(limiter
+            .check_at(ip, start + Duration::from_secs(30))
+            .is_ok());
+        assert!(limiter
+            .check_at(ip, start + Duration::from_secs(30))
+            .is_err());
+    }
+
+    #[test]
+    fn test_zero_disables_limit() {
+        let limiter = RateLimiter::new(0);
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        for _ in 0..100 {
+            assert!(limiter.check(ip).is_ok());
+        }
+    }
+}
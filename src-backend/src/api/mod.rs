@@ -1,7 +1,12 @@
 use axum::{response::IntoResponse, Json};
 
+pub mod admin;
+pub mod middleware;
 pub mod request;
 pub mod router;
+pub mod state;
+#[cfg(test)]
+pub(crate) mod testing;
 pub mod todo;
 pub mod user;
 
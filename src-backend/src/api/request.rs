@@ -7,8 +7,13 @@
  * @FilePath: /src-backend/src/api/request.rs
  */
 
-use axum::{extract::FromRequestParts, response::IntoResponse};
-use serde::{Deserialize, Serialize};
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::IntoResponse,
+};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::domain::error::AppError;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Pagination {
@@ -29,6 +34,15 @@ pub struct Sort {
     pub order: String,
 }
 
+// 用于可清空的可选字段: 缺失时为 None, 显式的 null 为 Some(None)
+pub fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
 pub fn default_sort(key: String) -> Sort {
     Sort {
         field: key,
@@ -45,12 +59,17 @@ pub struct Response {
 
 impl IntoResponse for Response {
     fn into_response(self) -> axum::response::Response {
+        // code 为合法的 HTTP 状态码时同时作为响应状态
+        let status = u16::try_from(self.code)
+            .ok()
+            .and_then(|code| StatusCode::from_u16(code).ok())
+            .unwrap_or(StatusCode::OK);
         let json_response = serde_json::json!({
             "code":self.code,
             "message":self.message,
             "data":Some(self.data)
         });
-        axum::Json(json_response).into_response()
+        (status, axum::Json(json_response)).into_response()
     }
 }
 
@@ -69,3 +88,74 @@ pub fn error_response(code: i32, message: String) -> Response {
         data: None,
     }
 }
+
+impl AppError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::WeakPassword(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::EmailNotVerified => StatusCode::FORBIDDEN,
+            AppError::AccountSuspended => StatusCode::FORBIDDEN,
+            AppError::AccountLocked { .. } => StatusCode::LOCKED,
+            AppError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    // 稳定的机器可读错误码, 客户端应依据它而不是 message 做判断
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::Validation(_) => "VALIDATION_FAILED",
+            AppError::Conflict(_) => "CONFLICT",
+            AppError::Unauthorized(_) => "UNAUTHORIZED",
+            AppError::Forbidden(_) => "FORBIDDEN",
+            AppError::WeakPassword(_) => "WEAK_PASSWORD",
+            AppError::EmailNotVerified => "EMAIL_NOT_VERIFIED",
+            AppError::AccountSuspended => "ACCOUNT_SUSPENDED",
+            AppError::AccountLocked { .. } => "ACCOUNT_LOCKED",
+            AppError::RateLimited { .. } => "RATE_LIMITED",
+            AppError::Internal(_) => "INTERNAL_ERROR",
+        }
+    }
+
+    // 需要客户端等待后重试的错误, 对应 Retry-After 响应头
+    pub fn retry_after(&self) -> Option<u64> {
+        match self {
+            AppError::AccountLocked { retry_after } | AppError::RateLimited { retry_after } => {
+                Some(*retry_after)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> axum::response::Response {
+        let status = self.status();
+        let message = match &self {
+            AppError::Internal(err) => {
+                log::error!("internal error: {err}");
+                "Internal server error".to_string()
+            }
+            err => err.to_string(),
+        };
+        let json_response = serde_json::json!({
+            "code":status.as_u16(),
+            "error":self.error_code(),
+            "message":message,
+            "data":null
+        });
+        let mut response = (status, axum::Json(json_response)).into_response();
+        if let Some(retry_after) = self.retry_after() {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+        response
+    }
+}
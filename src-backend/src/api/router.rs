@@ -1,49 +1,474 @@
 use std::sync::Arc;
 
 use axum::{
-    routing::{get, post},
-    Extension, Router,
+    http::{
+        header::{AUTHORIZATION, CONTENT_TYPE},
+        HeaderValue,
+    },
+    middleware,
+    routing::{delete, get, patch, post, put},
+    Router,
 };
+use tower_http::cors::{Any, CorsLayer};
 
 use crate::{
-    application::todo::service::{TodoAppService, TodoAppServiceImpl},
-    domain::repository::todo::TodoRepository,
+    api::state::AppState,
+    application::{
+        account::service::{AccountServiceImpl, AccountSettings},
+        api_token::service::ApiTokenServiceImpl,
+        audit::service::{AuditService, AuditServiceImpl},
+        auth::service::AuthServiceImpl,
+        oidc::service::OidcServiceImpl,
+        tag::service::TagServiceImpl,
+        todo::service::{TodoAppService, TodoAppServiceImpl},
+        trash::spawn_trash_purger,
+        two_factor::service::TwoFactorServiceImpl,
+        user::service::{LockoutPolicy, UserService, UserServiceImpl},
+    },
+    config::{AppConfig, AuthConfig},
+    domain::repository::{
+        api_token::ApiTokenRepository, audit::AuditRepository, identity::IdentityRepository,
+        refresh_token::RefreshTokenRepository, session::SessionRepository, tag::TagRepository,
+        todo::TodoRepository, two_factor::TwoFactorRepository, user::UserRepository,
+        user_token::UserTokenRepository,
+    },
     infastructure::db::{
+        api_token::{
+            mysql::MySqlApiTokenRepository, postgresql::PgApiTokenRepository,
+            sqlite::SqliteApiTokenRepository,
+        },
+        audit::{
+            mysql::MySqlAuditRepository, postgresql::PgAuditRepository,
+            sqlite::SqliteAuditRepository,
+        },
+        identity::{
+            mysql::MySqlIdentityRepository, postgresql::PgIdentityRepository,
+            sqlite::SqliteIdentityRepository,
+        },
         init_db,
-        todo::{mysql::MySqlTodoRepository, postgresql::PgSqlTodoRepository},
-        Database, DB,
+        refresh_token::{
+            mysql::MySqlRefreshTokenRepository, postgresql::PgRefreshTokenRepository,
+            sqlite::SqliteRefreshTokenRepository,
+        },
+        session::{
+            mysql::MySqlSessionRepository, postgresql::PgSessionRepository,
+            sqlite::SqliteSessionRepository,
+        },
+        tag::{
+            mysql::MySqlTagRepository, postgresql::PgTagRepository, sqlite::SqliteTagRepository,
+        },
+        todo::{
+            mysql::MySqlTodoRepository, postgresql::PgSqlTodoRepository,
+            sqlite::SqliteTodoRepository,
+        },
+        two_factor::{
+            mysql::MySqlTwoFactorRepository, postgresql::PgTwoFactorRepository,
+            sqlite::SqliteTwoFactorRepository,
+        },
+        user::{
+            mysql::MySqlUserRepository, postgresql::PgUserRepository, sqlite::SqliteUserRepository,
+        },
+        user_token::{
+            mysql::MySqlUserTokenRepository, postgresql::PgUserTokenRepository,
+            sqlite::SqliteUserTokenRepository,
+        },
+        Database,
     },
+    infastructure::mail::{create_mailer, Mailer},
+    infastructure::oidc::OidcClient,
+    utils::jwt::JwtKeys,
 };
 
-use super::todo::api::{create_todo, get_todo};
+use super::{
+    admin::api::{
+        force_password_reset, get_user, list_users, reactivate_user, suspend_user, update_role,
+    },
+    admin::audit::{export_audit_events, list_audit_events},
+    middleware::{
+        auth::{auth, require_admin, require_session, require_todo_scope},
+        rate_limit::{rate_limit, RateLimiter},
+    },
+    todo::api::{
+        create_todo, delete_todo, get_todo, get_todo_list, get_todo_tree, get_trash, move_todo,
+        restore_todo, update_todo,
+    },
+    todo::tag::{
+        attach_tag, create_tag, delete_tag, detach_tag, list_tags, list_todo_tags, update_tag,
+    },
+    user::api::{
+        forgot_password, login, logout, logout_all, refresh, register, resend_verification,
+        reset_password, verify_email,
+    },
+    user::me::{change_password, delete_me, get_me, update_me},
+    user::oidc::{authorize, callback, link_identity, list_identities, unlink_identity},
+    user::session::{list_sessions, revoke_other_sessions, revoke_session},
+    user::tokens::{create_token, list_tokens, revoke_token},
+    user::two_factor::{
+        confirm_totp, disable_totp, enroll_totp, get_two_factor, login_two_factor,
+        regenerate_recovery_codes,
+    },
+};
 
-fn create_todo_service<T>(todo_repository: T) -> Arc<dyn TodoAppService>
+pub fn create_todo_service<T>(todo_repository: T) -> Arc<dyn TodoAppService>
 where
     T: TodoRepository + 'static,
 {
     Arc::new(TodoAppServiceImpl::new(todo_repository))
 }
 
-pub async fn create_router() -> Router {
-    init_db().await;
-    let db = DB.lock().unwrap();
-    if let Some(database) = &*db {
-        let todo_service: Arc<dyn TodoAppService> = match database {
-            Database::MySQL(pool) => {
-                let todo_repository = MySqlTodoRepository::new(pool.clone()).unwrap();
-                create_todo_service(todo_repository)
-            }
-            Database::PgSQL(pool) => {
-                let todo_repository = PgSqlTodoRepository::new(pool.clone()).unwrap();
-                create_todo_service(todo_repository)
-            }
-        };
-
-        return Router::new()
-            .route("/api/todo", post(create_todo))
-            .route("/api/todo/:id", get(get_todo))
-            .layer(Extension(todo_service));
-    } else {
-        panic!("Database not initialized");
+pub fn create_audit_service<T>(audit_repository: T) -> Arc<dyn AuditService>
+where
+    T: AuditRepository + 'static,
+{
+    Arc::new(AuditServiceImpl::new(audit_repository))
+}
+
+pub fn create_user_service<T>(
+    user_repository: T,
+    config: &AuthConfig,
+    audit_service: Arc<dyn AuditService>,
+) -> Arc<dyn UserService>
+where
+    T: UserRepository + 'static,
+{
+    Arc::new(
+        UserServiceImpl::new(user_repository)
+            .with_audit(audit_service)
+            .with_password_policy(config.password_policy.clone())
+            .with_lockout(LockoutPolicy {
+                max_attempts: config.max_failed_logins,
+                duration: config.lockout_duration,
+            })
+            .with_require_verified_email(config.require_verified_email),
+    )
+}
+
+// 命令行管理工具只需要用户服务, 不启动后台任务
+pub fn create_user_service_for(
+    database: Database,
+    config: &AuthConfig,
+) -> anyhow::Result<Arc<dyn UserService>> {
+    Ok(match database {
+        Database::MySQL(pool) => create_user_service(
+            MySqlUserRepository::new(pool.clone())?,
+            config,
+            create_audit_service(MySqlAuditRepository::new(pool)?),
+        ),
+        Database::PgSQL(pool) => create_user_service(
+            PgUserRepository::new(pool.clone())?,
+            config,
+            create_audit_service(PgAuditRepository::new(pool)?),
+        ),
+        Database::Sqlite(pool) => create_user_service(
+            SqliteUserRepository::new(pool.clone())?,
+            config,
+            create_audit_service(SqliteAuditRepository::new(pool)?),
+        ),
+    })
+}
+
+// 由各个仓储实现组装出路由共享状态
+#[allow(clippy::too_many_arguments)]
+pub fn create_state<T, G, U, R, V, W, I, F, S, A>(
+    config: AppConfig,
+    todo_repository: T,
+    tag_repository: G,
+    user_repository: U,
+    refresh_token_repository: R,
+    user_token_repository: V,
+    api_token_repository: W,
+    identity_repository: I,
+    two_factor_repository: F,
+    session_repository: S,
+    audit_repository: A,
+    mailer: Arc<dyn Mailer>,
+) -> anyhow::Result<AppState>
+where
+    T: TodoRepository + 'static,
+    G: TagRepository + 'static,
+    U: UserRepository + 'static,
+    R: RefreshTokenRepository + 'static,
+    V: UserTokenRepository + 'static,
+    W: ApiTokenRepository + 'static,
+    I: IdentityRepository + 'static,
+    F: TwoFactorRepository + 'static,
+    S: SessionRepository + 'static,
+    A: AuditRepository + 'static,
+{
+    let jwt = Arc::new(JwtKeys::new(&config.jwt)?);
+    let audit_service = create_audit_service(audit_repository);
+    let user_service = create_user_service(user_repository, &config.auth, audit_service.clone());
+    let auth_service = Arc::new(AuthServiceImpl::new(
+        refresh_token_repository,
+        session_repository,
+        user_service.clone(),
+        audit_service.clone(),
+        jwt.clone(),
+        config.auth.refresh_token_ttl,
+    ));
+    let account_service = Arc::new(AccountServiceImpl::new(
+        user_token_repository,
+        user_service.clone(),
+        auth_service.clone(),
+        audit_service.clone(),
+        mailer,
+        AccountSettings {
+            email_verification_ttl: config.auth.email_verification_ttl,
+            password_reset_ttl: config.auth.password_reset_ttl,
+            login_challenge_ttl: config.auth.two_factor_challenge_ttl,
+            link_base_url: config.mail.link_base_url.clone(),
+        },
+    ));
+    let two_factor_service = Arc::new(TwoFactorServiceImpl::new(
+        two_factor_repository,
+        user_service.clone(),
+        account_service.clone(),
+        audit_service.clone(),
+        config.auth.totp_issuer.clone(),
+    ));
+    let oidc_client = config.oidc.clone().map(OidcClient::new).transpose()?;
+    let oidc_service = Arc::new(OidcServiceImpl::new(
+        identity_repository,
+        oidc_client,
+        user_service.clone(),
+        audit_service.clone(),
+    ));
+    let auth_rate_limiter = Arc::new(RateLimiter::new(config.auth.rate_limit_per_minute));
+    let todo_service = create_todo_service(todo_repository);
+    let tag_service = Arc::new(TagServiceImpl::new(tag_repository, todo_service.clone()));
+    Ok(AppState {
+        config: Arc::new(config),
+        jwt,
+        todo_service,
+        tag_service,
+        user_service,
+        auth_service,
+        account_service,
+        api_token_service: Arc::new(ApiTokenServiceImpl::new(
+            api_token_repository,
+            audit_service.clone(),
+        )),
+        oidc_service,
+        two_factor_service,
+        audit_service,
+        auth_rate_limiter,
+    })
+}
+
+pub async fn create_router(config: AppConfig) -> anyhow::Result<Router> {
+    let database = init_db(&config.database).await?;
+    let mailer = create_mailer(&config.mail)?;
+    let state = match database {
+        Database::MySQL(pool) => create_state(
+            config,
+            MySqlTodoRepository::new(pool.clone())?,
+            MySqlTagRepository::new(pool.clone())?,
+            MySqlUserRepository::new(pool.clone())?,
+            MySqlRefreshTokenRepository::new(pool.clone())?,
+            MySqlUserTokenRepository::new(pool.clone())?,
+            MySqlApiTokenRepository::new(pool.clone())?,
+            MySqlIdentityRepository::new(pool.clone())?,
+            MySqlTwoFactorRepository::new(pool.clone())?,
+            MySqlSessionRepository::new(pool.clone())?,
+            MySqlAuditRepository::new(pool)?,
+            mailer,
+        )?,
+        Database::PgSQL(pool) => create_state(
+            config,
+            PgSqlTodoRepository::new(pool.clone())?,
+            PgTagRepository::new(pool.clone())?,
+            PgUserRepository::new(pool.clone())?,
+            PgRefreshTokenRepository::new(pool.clone())?,
+            PgUserTokenRepository::new(pool.clone())?,
+            PgApiTokenRepository::new(pool.clone())?,
+            PgIdentityRepository::new(pool.clone())?,
+            PgTwoFactorRepository::new(pool.clone())?,
+            PgSessionRepository::new(pool.clone())?,
+            PgAuditRepository::new(pool)?,
+            mailer,
+        )?,
+        Database::Sqlite(pool) => create_state(
+            config,
+            SqliteTodoRepository::new(pool.clone())?,
+            SqliteTagRepository::new(pool.clone())?,
+            SqliteUserRepository::new(pool.clone())?,
+            SqliteRefreshTokenRepository::new(pool.clone())?,
+            SqliteUserTokenRepository::new(pool.clone())?,
+            SqliteApiTokenRepository::new(pool.clone())?,
+            SqliteIdentityRepository::new(pool.clone())?,
+            SqliteTwoFactorRepository::new(pool.clone())?,
+            SqliteSessionRepository::new(pool.clone())?,
+            SqliteAuditRepository::new(pool)?,
+            mailer,
+        )?,
+    };
+    spawn_trash_purger(&state);
+
+    Ok(build_router(state))
+}
+
+fn cors_layer(origins: &[String]) -> Option<CorsLayer> {
+    if origins.is_empty() {
+        return None;
+    }
+    let layer = CorsLayer::new()
+        .allow_methods(Any)
+        .allow_headers([AUTHORIZATION, CONTENT_TYPE]);
+    if origins.iter().any(|origin| origin == "*") {
+        return Some(layer.allow_origin(Any));
+    }
+    let origins: Vec<HeaderValue> = origins
+        .iter()
+        .filter_map(|origin| HeaderValue::from_str(origin).ok())
+        .collect();
+    Some(layer.allow_origin(origins))
+}
+
+// 根据已构建好的服务组装路由, 不依赖具体的数据库
+pub fn build_router(state: AppState) -> Router {
+    let admin_routes = Router::new()
+        .route("/api/admin/users", get(list_users))
+        .route("/api/admin/users/:id", get(get_user))
+        .route("/api/admin/users/:id/role", put(update_role))
+        .route("/api/admin/users/:id/suspend", post(suspend_user))
+        .route("/api/admin/users/:id/reactivate", post(reactivate_user))
+        .route(
+            "/api/admin/users/:id/password-reset",
+            post(force_password_reset),
+        )
+        .route("/api/admin/audit-events", get(list_audit_events))
+        .route("/api/admin/audit-events/export", get(export_audit_events))
+        .route_layer(middleware::from_fn(require_admin));
+
+    // API 令牌只能访问 todo 路由, 并受令牌的 scope 限制
+    let todo_routes = Router::new()
+        .route("/api/todo", get(get_todo_list).post(create_todo))
+        .route("/api/todo/trash", get(get_trash))
+        .route("/api/todo/:id/restore", post(restore_todo))
+        .route("/api/todo/:id/tree", get(get_todo_tree))
+        .route("/api/todo/:id/move", post(move_todo))
+        .route("/api/todo/:id/tags", get(list_todo_tags))
+        .route(
+            "/api/todo/:id/tags/:tag_id",
+            put(attach_tag).delete(detach_tag),
+        )
+        .route("/api/tags", get(list_tags).post(create_tag))
+        .route("/api/tags/:id", patch(update_tag).delete(delete_tag))
+        .route(
+            "/api/todo/:id",
+            get(get_todo)
+                .put(update_todo)
+                .patch(update_todo)
+                .delete(delete_todo),
+        )
+        .route_layer(middleware::from_fn(require_todo_scope));
+
+    let account_routes = Router::new()
+        .route("/api/auth/logout-all", post(logout_all))
+        .route("/api/me", get(get_me).patch(update_me).delete(delete_me))
+        .route("/api/me/password", post(change_password))
+        .route("/api/me/sessions", get(list_sessions))
+        .route(
+            "/api/me/sessions/revoke-others",
+            post(revoke_other_sessions),
+        )
+        .route("/api/me/sessions/:id", delete(revoke_session))
+        .route("/api/me/tokens", get(list_tokens).post(create_token))
+        .route("/api/me/tokens/:id", delete(revoke_token))
+        .route("/api/me/identities", get(list_identities))
+        .route("/api/me/identities/oidc", post(link_identity))
+        .route("/api/me/identities/:id", delete(unlink_identity))
+        .route("/api/me/2fa", get(get_two_factor))
+        .route("/api/me/2fa/totp", post(enroll_totp).delete(disable_totp))
+        .route("/api/me/2fa/totp/confirm", post(confirm_totp))
+        .route(
+            "/api/me/2fa/recovery-codes",
+            post(regenerate_recovery_codes),
+        )
+        .merge(admin_routes)
+        .route_layer(middleware::from_fn(require_session));
+
+    let protected_routes = todo_routes
+        .merge(account_routes)
+        .route_layer(middleware::from_fn_with_state(state.clone(), auth));
+
+    // 未登录即可访问的认证接口按客户端 IP 限流
+    let auth_routes = Router::new()
+        .route("/api/auth/register", post(register))
+        .route("/api/auth/login", post(login))
+        .route("/api/auth/login/2fa", post(login_two_factor))
+        .route("/api/auth/refresh", post(refresh))
+        .route("/api/auth/logout", post(logout))
+        .route("/api/auth/verify-email", post(verify_email))
+        .route("/api/auth/verify-email/resend", post(resend_verification))
+        .route("/api/auth/forgot-password", post(forgot_password))
+        .route("/api/auth/reset-password", post(reset_password))
+        .route("/api/auth/oidc/authorize", post(authorize))
+        .route("/api/auth/oidc/callback", post(callback))
+        .route_layer(middleware::from_fn_with_state(state.clone(), rate_limit));
+
+    let cors = cors_layer(&state.config.server.cors_origins);
+    let router = Router::new()
+        .merge(auth_routes)
+        .merge(protected_routes)
+        .with_state(state);
+    match cors {
+        Some(cors) => router.layer(cors),
+        None => router,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    use crate::api::testing::{
+        register_and_login, send, test_config, test_router, test_router_with, TEST_PASSWORD,
+    };
+
+    #[tokio::test]
+    async fn test_routers_do_not_share_state() {
+        let first = test_router();
+        let mut config = test_config();
+        config.jwt.keys[0].secret = Some("another-secret-another-secret-another".to_string());
+        let second = test_router_with(config);
+
+        let token = register_and_login(&first, "isolated@example.com").await;
+
+        let (status, _) = send(
+            &second,
+            Method::POST,
+            "/api/auth/login",
+            None,
+            Some(json!({ "email": "isolated@example.com", "password": TEST_PASSWORD })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _) = send(&second, Method::GET, "/api/todo", Some(&token), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(&first, Method::GET, "/api/todo", Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_cors_origins_from_config() {
+        let mut config = test_config();
+        config.server.cors_origins = vec!["http://localhost:1420".to_string()];
+        let router = test_router_with(config);
+
+        let request = axum::http::Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/api/auth/login")
+            .header("origin", "http://localhost:1420")
+            .header("access-control-request-method", "POST")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = tower::ServiceExt::oneshot(router, request).await.unwrap();
+        assert_eq!(
+            response.headers()["access-control-allow-origin"],
+            "http://localhost:1420"
+        );
     }
 }
@@ -0,0 +1,32 @@
+use std::sync::Arc;
+
+use axum::extract::FromRef;
+
+use crate::{
+    api::middleware::rate_limit::RateLimiter,
+    application::{
+        account::service::AccountService, api_token::service::ApiTokenService,
+        audit::service::AuditService, auth::service::AuthService, oidc::service::OidcService,
+        tag::service::TagService, todo::service::TodoAppService,
+        two_factor::service::TwoFactorService, user::service::UserService,
+    },
+    config::AppConfig,
+    utils::jwt::JwtKeys,
+};
+
+// 路由共享状态, 处理函数通过 State 提取其中的字段
+#[derive(Clone, FromRef)]
+pub struct AppState {
+    pub config: Arc<AppConfig>,
+    pub jwt: Arc<JwtKeys>,
+    pub todo_service: Arc<dyn TodoAppService>,
+    pub tag_service: Arc<dyn TagService>,
+    pub user_service: Arc<dyn UserService>,
+    pub auth_service: Arc<dyn AuthService>,
+    pub account_service: Arc<dyn AccountService>,
+    pub api_token_service: Arc<dyn ApiTokenService>,
+    pub oidc_service: Arc<dyn OidcService>,
+    pub two_factor_service: Arc<dyn TwoFactorService>,
+    pub audit_service: Arc<dyn AuditService>,
+    pub auth_rate_limiter: Arc<RateLimiter>,
+}
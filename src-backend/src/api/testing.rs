@@ -0,0 +1,196 @@
+// 接口测试辅助: 基于内存仓储构建完整路由, 通过 oneshot 发送请求
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use serde_json::Value;
+use tower::ServiceExt;
+
+use crate::{
+    config::{AppConfig, FileConfig},
+    infastructure::db::{
+        api_token::memory::InMemoryApiTokenRepository, audit::memory::InMemoryAuditRepository,
+        identity::memory::InMemoryIdentityRepository,
+        refresh_token::memory::InMemoryRefreshTokenRepository,
+        session::memory::InMemorySessionRepository, tag::memory::InMemoryTagRepository,
+        todo::memory::InMemoryTodoRepository, two_factor::memory::InMemoryTwoFactorRepository,
+        user::memory::InMemoryUserRepository, user_token::memory::InMemoryUserTokenRepository,
+    },
+    infastructure::mail::{log::LogMailer, Mailer},
+};
+
+use super::{
+    router::{build_router, create_state},
+    state::AppState,
+};
+
+// 满足默认密码策略的测试密码
+pub(crate) const TEST_PASSWORD: &str = "correct-horse-battery";
+
+pub(crate) fn test_config() -> AppConfig {
+    AppConfig::from_sources(FileConfig::default(), |key| match key {
+        "DATABASE_URL" => Some("sqlite::memory:".to_string()),
+        "DATABASE_TYPE" => Some("sqlite".to_string()),
+        "JWT_SECRET" => Some("test-secret-test-secret-test-secret".to_string()),
+        _ => None,
+    })
+    .unwrap()
+}
+
+// 每次调用都会得到一个独立的路由和存储, 测试之间互不影响
+pub(crate) fn test_router() -> Router {
+    test_router_with(test_config())
+}
+
+pub(crate) fn test_router_with(config: AppConfig) -> Router {
+    test_router_with_mailer(config).0
+}
+
+// 同时返回捕获邮件的 mailer, 用于从邮件中读取验证和重置令牌
+pub(crate) fn test_router_with_mailer(config: AppConfig) -> (Router, Arc<LogMailer>) {
+    let (state, mailer) = test_state_with_mailer(config);
+    (build_router(state), mailer)
+}
+
+// 返回共享状态, 测试可以绕过接口直接调整数据, 如提升管理员
+pub(crate) fn test_state_with_mailer(config: AppConfig) -> (AppState, Arc<LogMailer>) {
+    let mailer = Arc::new(LogMailer::new());
+    (test_state_with(config, mailer.clone()), mailer)
+}
+
+pub(crate) fn test_state_with(config: AppConfig, mailer: Arc<dyn Mailer>) -> AppState {
+    let tags = InMemoryTagRepository::new();
+    let todos = InMemoryTodoRepository::new().with_tags(tags.clone());
+    let refresh_tokens = InMemoryRefreshTokenRepository::new();
+    let user_tokens = InMemoryUserTokenRepository::new();
+    let api_tokens = InMemoryApiTokenRepository::new();
+    let identities = InMemoryIdentityRepository::new();
+    let two_factor = InMemoryTwoFactorRepository::new();
+    let sessions = InMemorySessionRepository::new();
+    // 用户仓储持有其他仓储的克隆, 清理用户时级联删除其数据
+    let users = InMemoryUserRepository::new()
+        .with_dependent(todos.clone())
+        .with_dependent(tags.clone())
+        .with_dependent(refresh_tokens.clone())
+        .with_dependent(user_tokens.clone())
+        .with_dependent(api_tokens.clone())
+        .with_dependent(identities.clone())
+        .with_dependent(two_factor.clone())
+        .with_dependent(sessions.clone());
+    create_state(
+        config,
+        todos,
+        tags,
+        users,
+        refresh_tokens,
+        user_tokens,
+        api_tokens,
+        identities,
+        two_factor,
+        sessions,
+        InMemoryAuditRepository::new(),
+        mailer,
+    )
+    .unwrap()
+}
+
+pub(crate) async fn send(
+    router: &Router,
+    method: Method,
+    uri: &str,
+    token: Option<&str>,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut builder = Request::builder().method(method).uri(uri);
+    if let Some(token) = token {
+        builder = builder.header(header::AUTHORIZATION, format!("Bearer {token}"));
+    }
+    let request = match body {
+        Some(body) => builder
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap(),
+        None => builder.body(Body::empty()).unwrap(),
+    };
+
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (status, json)
+}
+
+// 注册并登录一个用户, 返回 access token
+pub(crate) async fn register_and_login(router: &Router, email: &str) -> String {
+    let password = TEST_PASSWORD;
+    let (status, _) = send(
+        router,
+        Method::POST,
+        "/api/auth/register",
+        None,
+        Some(serde_json::json!({
+            "email": email,
+            "username": "tester",
+            "password": password,
+            "password_confirmation": password,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = send(
+        router,
+        Method::POST,
+        "/api/auth/login",
+        None,
+        Some(serde_json::json!({ "email": email, "password": password })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    body["data"]["token"].as_str().unwrap().to_string()
+}
+
+// 等待后台任务发出第 count 封邮件, 超时说明邮件没有发送
+pub(crate) async fn wait_for_mail(mailer: &LogMailer, count: usize) {
+    tokio::time::timeout(std::time::Duration::from_secs(5), mailer.wait_for(count))
+        .await
+        .expect("no mail sent in time");
+}
+
+// 取出最后一封发给 email 的邮件中链接携带的令牌
+pub(crate) fn token_from_mail(mailer: &LogMailer, email: &str) -> String {
+    let mail = mailer
+        .sent()
+        .into_iter()
+        .rev()
+        .find(|mail| mail.to == email)
+        .expect("no mail sent");
+    let start = mail.body.find("token=").unwrap() + "token=".len();
+    mail.body[start..]
+        .chars()
+        .take_while(char::is_ascii_hexdigit)
+        .collect()
+}
+
+// 直接通过用户服务提升为管理员, 对应命令行的 admin grant
+pub(crate) async fn promote_admin(state: &AppState, email: &str) {
+    let user = state
+        .user_service
+        .get_user_by_email(email.to_string())
+        .await
+        .unwrap();
+    state
+        .user_service
+        .set_role(
+            user.id,
+            crate::domain::entities::user::UserRole::Admin,
+            &crate::application::audit::service::AuditContext::system(),
+        )
+        .await
+        .unwrap();
+}
@@ -1,33 +1,193 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{self, path},
-    response::IntoResponse,
-    Extension, Json,
+    extract::{path, Query, State},
+    Json,
 };
+use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    api::request::{error_response, success_response},
-    application::todo::service::TodoAppService,
-    domain::entities::todo::{Priority, Status, Todo},
+    api::{
+        middleware::auth::AuthUser,
+        request::{
+            default_pagination, default_sort, double_option, success_response, Pagination, Response,
+        },
+    },
+    application::todo::service::{TodoAppService, UpdateTodo},
+    domain::{
+        entities::todo::{Priority, Status, Todo},
+        error::{AppError, AppResult},
+        repository::{
+            tag::{TagFilter, TagMatch},
+            todo::{TodoFilter, TodoQuery, TodoSortField},
+            SortOrder,
+        },
+    },
 };
 
 #[derive(Deserialize, Serialize, Clone)]
 pub struct CreateTodoRequest {
     title: String,
     description: String,
+    // 作为该任务的子任务创建
+    #[serde(default)]
+    parent_id: Option<i32>,
+    #[serde(default)]
+    auto_complete: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct UpdateTodoRequest {
+    title: Option<String>,
+    description: Option<String>,
+    status: Option<Status>,
+    priority: Option<Priority>,
+    // null 清除截止时间
+    #[serde(default, deserialize_with = "double_option")]
+    deadline: Option<Option<DateTime<Local>>>,
+    done: Option<bool>,
+    auto_complete: Option<bool>,
+}
+
+impl From<UpdateTodoRequest> for UpdateTodo {
+    fn from(req: UpdateTodoRequest) -> Self {
+        Self {
+            title: req.title,
+            description: req.description,
+            status: req.status,
+            priority: req.priority,
+            deadline: req.deadline,
+            done: req.done,
+            auto_complete: req.auto_complete,
+        }
+    }
+}
+
+// parent_id 为空或省略时移动到顶层
+#[derive(Deserialize, Serialize, Clone)]
+pub struct MoveTodoRequest {
+    #[serde(default)]
+    parent_id: Option<i32>,
+}
+
+const MAX_PAGE_SIZE: u32 = 100;
+const MAX_FILTER_TAGS: usize = 20;
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct ListTodoQuery {
+    page: Option<u32>,
+    page_size: Option<u32>,
+    sort: Option<String>,
+    order: Option<String>,
+    status: Option<Status>,
+    priority: Option<Priority>,
+    done: Option<bool>,
+    deadline_from: Option<DateTime<Local>>,
+    deadline_to: Option<DateTime<Local>>,
+    // 以逗号分隔的标签 id
+    tags: Option<String>,
+    // any 或 all, 默认 any
+    tag_match: Option<String>,
+}
+
+impl ListTodoQuery {
+    fn tag_filter(&self) -> AppResult<Option<TagFilter>> {
+        let mode = match self.tag_match.as_deref() {
+            Some(mode) => TagMatch::parse(mode).ok_or_else(|| {
+                AppError::Validation("tag_match must be `any` or `all`".to_string())
+            })?,
+            None => TagMatch::default(),
+        };
+        let Some(tags) = self.tags.as_deref() else {
+            return Ok(None);
+        };
+        let mut tag_ids = tags
+            .split(',')
+            .map(|id| id.trim().parse::<i32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| {
+                AppError::Validation("tags must be a comma separated list of tag ids".to_string())
+            })?;
+        if tag_ids.len() > MAX_FILTER_TAGS {
+            return Err(AppError::Validation(format!(
+                "Cannot filter by more than {MAX_FILTER_TAGS} tags"
+            )));
+        }
+        // 重复的标签会让 all 模式的计数永远无法满足
+        tag_ids.sort_unstable();
+        tag_ids.dedup();
+        Ok(Some(TagFilter { tag_ids, mode }))
+    }
+}
+
+impl TryFrom<ListTodoQuery> for TodoQuery {
+    type Error = AppError;
+
+    fn try_from(req: ListTodoQuery) -> Result<Self, Self::Error> {
+        let default = default_pagination();
+        let pagination = Pagination {
+            page: req.page.unwrap_or(default.page),
+            page_size: req.page_size.unwrap_or(default.page_size),
+        };
+        if pagination.page == 0 {
+            return Err(AppError::Validation("page must be at least 1".to_string()));
+        }
+        if pagination.page_size == 0 || pagination.page_size > MAX_PAGE_SIZE {
+            return Err(AppError::Validation(format!(
+                "page_size must be between 1 and {MAX_PAGE_SIZE}"
+            )));
+        }
+
+        let tags = req.tag_filter()?;
+
+        let mut sort = default_sort("created_at".to_string());
+        if let Some(field) = req.sort {
+            sort.field = field;
+        }
+        if let Some(order) = req.order {
+            sort.order = order;
+        }
+        let field = TodoSortField::parse(&sort.field).ok_or_else(|| {
+            AppError::Validation(format!("Cannot sort by field `{}`", sort.field))
+        })?;
+        let order = SortOrder::parse(&sort.order)
+            .ok_or_else(|| AppError::Validation("order must be `asc` or `desc`".to_string()))?;
+
+        if let (Some(from), Some(to)) = (req.deadline_from, req.deadline_to) {
+            if from > to {
+                return Err(AppError::Validation(
+                    "deadline_from must not be after deadline_to".to_string(),
+                ));
+            }
+        }
+
+        Ok(TodoQuery {
+            filter: TodoFilter {
+                status: req.status,
+                priority: req.priority,
+                done: req.done,
+                deadline_from: req.deadline_from,
+                deadline_to: req.deadline_to,
+                tags,
+            },
+            sort: field,
+            order,
+            page: pagination.page,
+            page_size: pagination.page_size,
+        })
+    }
 }
 
 #[axum::debug_handler]
 pub async fn create_todo(
-    todo_service: extract::Extension<Arc<dyn TodoAppService>>,
-    Extension(user_id): Extension<i32>,
+    State(todo_service): State<Arc<dyn TodoAppService>>,
+    auth_user: AuthUser,
     playload: Json<CreateTodoRequest>,
-) -> impl IntoResponse {
+) -> AppResult<Response> {
     let todo = Todo {
         id: 0,
-        user_id,
+        user_id: auth_user.user_id,
         title: playload.title.clone(),
         description: playload.description.clone(),
         status: Status::Open,
@@ -37,33 +197,537 @@ pub async fn create_todo(
         deleted_at: None,
         deadline: None,
         done: false,
+        parent_id: playload.parent_id,
+        auto_complete: playload.auto_complete,
     };
 
-    let todo = todo_service.create(todo).await;
-    if let Ok(todo) = todo {
-        success_response(serde_json::to_value(todo).unwrap())
-    } else {
-        error_response(500, "Failed to create todo".to_string())
-    }
+    let todo = todo_service.create(todo).await?;
+    Ok(success_response(serde_json::to_value(todo).unwrap()))
 }
 
 pub async fn get_todo(
-    todo_service: extract::Extension<Arc<dyn TodoAppService>>,
+    State(todo_service): State<Arc<dyn TodoAppService>>,
+    auth_user: AuthUser,
     path::Path(id): path::Path<i32>,
-) -> impl IntoResponse {
-    let todo = todo_service.get_by_id(id).await;
-    print!("{id}");
-    if let Some(todo) = todo {
-        success_response(serde_json::to_value(todo).unwrap())
-    } else {
-        error_response(500, "Failed to get todo".to_string())
-    }
+) -> AppResult<Response> {
+    let todo = todo_service.get_by_id(auth_user.user_id, id).await?;
+    Ok(success_response(serde_json::to_value(todo).unwrap()))
+}
+
+// 返回任务及其所有层级的子任务
+pub async fn get_todo_tree(
+    State(todo_service): State<Arc<dyn TodoAppService>>,
+    auth_user: AuthUser,
+    path::Path(id): path::Path<i32>,
+) -> AppResult<Response> {
+    let tree = todo_service.get_tree(auth_user.user_id, id).await?;
+    Ok(success_response(serde_json::to_value(tree).unwrap()))
+}
+
+pub async fn move_todo(
+    State(todo_service): State<Arc<dyn TodoAppService>>,
+    auth_user: AuthUser,
+    path::Path(id): path::Path<i32>,
+    playload: Json<MoveTodoRequest>,
+) -> AppResult<Response> {
+    let todo = todo_service
+        .move_to(auth_user.user_id, id, playload.parent_id)
+        .await?;
+    Ok(success_response(serde_json::to_value(todo).unwrap()))
 }
 
 pub async fn get_todo_list(
-    todo_service: extract::Extension<Arc<dyn TodoAppService>>,
-    Extension(user_id): Extension<i32>,
-) -> impl IntoResponse {
-    let todo_list = todo_service.get_all_by_user_id(user_id).await;
-    success_response(serde_json::to_value(todo_list).unwrap())
+    State(todo_service): State<Arc<dyn TodoAppService>>,
+    auth_user: AuthUser,
+    Query(query): Query<ListTodoQuery>,
+) -> AppResult<Response> {
+    let query = TodoQuery::try_from(query)?;
+    let todo_list = todo_service.list(auth_user.user_id, query).await?;
+    Ok(success_response(serde_json::to_value(todo_list).unwrap()))
+}
+
+pub async fn update_todo(
+    State(todo_service): State<Arc<dyn TodoAppService>>,
+    auth_user: AuthUser,
+    path::Path(id): path::Path<i32>,
+    playload: Json<UpdateTodoRequest>,
+) -> AppResult<Response> {
+    let todo = todo_service
+        .update(auth_user.user_id, id, playload.0.into())
+        .await?;
+    Ok(success_response(serde_json::to_value(todo).unwrap()))
+}
+
+pub async fn delete_todo(
+    State(todo_service): State<Arc<dyn TodoAppService>>,
+    auth_user: AuthUser,
+    path::Path(id): path::Path<i32>,
+) -> AppResult<Response> {
+    todo_service.delete(auth_user.user_id, id).await?;
+    Ok(success_response(serde_json::Value::Null))
+}
+
+pub async fn get_trash(
+    State(todo_service): State<Arc<dyn TodoAppService>>,
+    auth_user: AuthUser,
+) -> AppResult<Response> {
+    let todo_list = todo_service.list_trash(auth_user.user_id).await?;
+    Ok(success_response(serde_json::to_value(todo_list).unwrap()))
+}
+
+pub async fn restore_todo(
+    State(todo_service): State<Arc<dyn TodoAppService>>,
+    auth_user: AuthUser,
+    path::Path(id): path::Path<i32>,
+) -> AppResult<Response> {
+    let todo = todo_service.restore(auth_user.user_id, id).await?;
+    Ok(success_response(serde_json::to_value(todo).unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::testing::{register_and_login, send, test_router};
+
+    #[test]
+    fn test_list_query_defaults() {
+        let query = TodoQuery::try_from(ListTodoQuery::default()).unwrap();
+        assert_eq!(query.page, 1);
+        assert_eq!(query.page_size, 10);
+        assert_eq!(query.sort, TodoSortField::CreatedAt);
+        assert_eq!(query.order, SortOrder::Desc);
+    }
+
+    #[test]
+    fn test_list_query_rejects_unknown_sort_field() {
+        let req = ListTodoQuery {
+            sort: Some("user_id; DROP TABLE todos".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            TodoQuery::try_from(req),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_list_query_rejects_oversized_page() {
+        let req = ListTodoQuery {
+            page_size: Some(MAX_PAGE_SIZE + 1),
+            ..Default::default()
+        };
+        assert!(matches!(
+            TodoQuery::try_from(req),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_todo_routes_require_token() {
+        let router = test_router();
+        let (status, body) = send(&router, Method::GET, "/api/todo", None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"], "UNAUTHORIZED");
+
+        let (status, _) = send(&router, Method::GET, "/api/todo", Some("garbage"), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_todo_crud() {
+        let router = test_router();
+        let token = register_and_login(&router, "crud@example.com").await;
+
+        let (status, body) = send(
+            &router,
+            Method::POST,
+            "/api/todo",
+            Some(&token),
+            Some(json!({ "title": "Write tests", "description": "" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let id = body["data"]["id"].as_i64().unwrap();
+
+        let (status, body) = send(
+            &router,
+            Method::PATCH,
+            &format!("/api/todo/{id}"),
+            Some(&token),
+            Some(json!({ "priority": "High", "done": true })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["priority"], "High");
+        assert_eq!(body["data"]["title"], "Write tests");
+
+        let (status, body) = send(
+            &router,
+            Method::GET,
+            "/api/todo?done=true",
+            Some(&token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["total"], 1);
+
+        let uri = format!("/api/todo/{id}");
+        let (status, _) = send(&router, Method::DELETE, &uri, Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = send(&router, Method::GET, &uri, Some(&token), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "NOT_FOUND");
+
+        let (status, body) =
+            send(&router, Method::GET, "/api/todo/trash", Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"].as_array().unwrap().len(), 1);
+
+        let restore = format!("/api/todo/{id}/restore");
+        let (status, _) = send(&router, Method::POST, &restore, Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&router, Method::GET, &uri, Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_update_sets_and_clears_deadline() {
+        let router = test_router();
+        let token = register_and_login(&router, "deadline@example.com").await;
+        let (_, body) = send(
+            &router,
+            Method::POST,
+            "/api/todo",
+            Some(&token),
+            Some(json!({ "title": "Todo", "description": "" })),
+        )
+        .await;
+        let uri = format!("/api/todo/{}", body["data"]["id"]);
+
+        // 未提供 deadline 时保持不变, 显式的 null 清除截止时间
+        for (update, has_deadline) in [
+            (json!({ "deadline": "2030-01-01T00:00:00Z" }), true),
+            (json!({ "title": "Renamed" }), true),
+            (json!({ "deadline": null }), false),
+        ] {
+            let (status, body) =
+                send(&router, Method::PATCH, &uri, Some(&token), Some(update)).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(!body["data"]["deadline"].is_null(), has_deadline);
+        }
+        let (_, body) = send(&router, Method::GET, &uri, Some(&token), None).await;
+        assert!(body["data"]["deadline"].is_null());
+        assert_eq!(body["data"]["title"], "Renamed");
+    }
+
+    #[tokio::test]
+    async fn test_todo_is_invisible_to_other_users() {
+        let router = test_router();
+        let owner = register_and_login(&router, "owner@example.com").await;
+        let other = register_and_login(&router, "other@example.com").await;
+
+        let (_, body) = send(
+            &router,
+            Method::POST,
+            "/api/todo",
+            Some(&owner),
+            Some(json!({ "title": "Private", "description": "" })),
+        )
+        .await;
+        let uri = format!("/api/todo/{}", body["data"]["id"]);
+
+        let (status, _) = send(&router, Method::GET, &uri, Some(&other), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(&router, Method::DELETE, &uri, Some(&other), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(&router, Method::GET, &uri, Some(&owner), None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    async fn create(router: &axum::Router, token: &str, body: serde_json::Value) -> i64 {
+        let (status, body) = send(router, Method::POST, "/api/todo", Some(token), Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        body["data"]["id"].as_i64().unwrap()
+    }
+
+    async fn set_done(router: &axum::Router, token: &str, id: i64) {
+        let (status, _) = send(
+            router,
+            Method::PATCH,
+            &format!("/api/todo/{id}"),
+            Some(token),
+            Some(json!({ "done": true })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    async fn tree(router: &axum::Router, token: &str, id: i64) -> serde_json::Value {
+        let uri = format!("/api/todo/{id}/tree");
+        let (status, body) = send(router, Method::GET, &uri, Some(token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        body["data"].clone()
+    }
+
+    #[tokio::test]
+    async fn test_subtask_tree_and_progress() {
+        let router = test_router();
+        let token = register_and_login(&router, "tree@example.com").await;
+        let root = create(
+            &router,
+            &token,
+            json!({ "title": "Root", "description": "" }),
+        )
+        .await;
+        let child = create(
+            &router,
+            &token,
+            json!({ "title": "Child", "description": "", "parent_id": root }),
+        )
+        .await;
+        let grandchild = create(
+            &router,
+            &token,
+            json!({ "title": "Grandchild", "description": "", "parent_id": child }),
+        )
+        .await;
+        create(
+            &router,
+            &token,
+            json!({ "title": "Sibling", "description": "", "parent_id": root }),
+        )
+        .await;
+        set_done(&router, &token, grandchild).await;
+
+        let root_tree = tree(&router, &token, root).await;
+        assert_eq!(root_tree["title"], "Root");
+        assert_eq!(root_tree["progress"], 33);
+        let children = root_tree["children"].as_array().unwrap();
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0]["progress"], 100);
+        assert_eq!(children[0]["children"][0]["id"], grandchild);
+        assert_eq!(children[1]["progress"], 0);
+
+        // 父任务必须存在且属于当前用户
+        let other = register_and_login(&router, "other@example.com").await;
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/todo",
+            Some(&other),
+            Some(json!({ "title": "Sneaky", "description": "", "parent_id": root })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let uri = format!("/api/todo/{root}/tree");
+        let (status, _) = send(&router, Method::GET, &uri, Some(&other), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_move_prevents_cycles() {
+        let router = test_router();
+        let token = register_and_login(&router, "move@example.com").await;
+        let root = create(
+            &router,
+            &token,
+            json!({ "title": "Root", "description": "" }),
+        )
+        .await;
+        let child = create(
+            &router,
+            &token,
+            json!({ "title": "Child", "description": "", "parent_id": root }),
+        )
+        .await;
+        let other = create(
+            &router,
+            &token,
+            json!({ "title": "Other", "description": "" }),
+        )
+        .await;
+
+        for parent_id in [root, child] {
+            let (status, _) = send(
+                &router,
+                Method::POST,
+                &format!("/api/todo/{root}/move"),
+                Some(&token),
+                Some(json!({ "parent_id": parent_id })),
+            )
+            .await;
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        }
+
+        // 整个子树随之移动
+        let (status, body) = send(
+            &router,
+            Method::POST,
+            &format!("/api/todo/{root}/move"),
+            Some(&token),
+            Some(json!({ "parent_id": other })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["parent_id"], other);
+        let other_tree = tree(&router, &token, other).await;
+        assert_eq!(other_tree["children"][0]["children"][0]["id"], child);
+
+        let (status, body) = send(
+            &router,
+            Method::POST,
+            &format!("/api/todo/{root}/move"),
+            Some(&token),
+            Some(json!({ "parent_id": null })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["data"]["parent_id"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_auto_complete_parent() {
+        let router = test_router();
+        let token = register_and_login(&router, "auto@example.com").await;
+        let root = create(
+            &router,
+            &token,
+            json!({ "title": "Root", "description": "", "auto_complete": true }),
+        )
+        .await;
+        let parent = create(
+            &router,
+            &token,
+            json!({ "title": "Parent", "description": "", "parent_id": root, "auto_complete": true }),
+        )
+        .await;
+        let first = create(
+            &router,
+            &token,
+            json!({ "title": "First", "description": "", "parent_id": parent }),
+        )
+        .await;
+        let second = create(
+            &router,
+            &token,
+            json!({ "title": "Second", "description": "", "parent_id": parent }),
+        )
+        .await;
+
+        set_done(&router, &token, first).await;
+        assert_eq!(tree(&router, &token, parent).await["done"], false);
+        // 完成最后一个子任务后逐级向上完成
+        set_done(&router, &token, second).await;
+        let root_tree = tree(&router, &token, root).await;
+        assert_eq!(root_tree["done"], true);
+        assert_eq!(root_tree["status"], "Done");
+        assert_eq!(root_tree["children"][0]["done"], true);
+        assert_eq!(root_tree["progress"], 100);
+    }
+
+    #[tokio::test]
+    async fn test_delete_removes_subtree() {
+        let router = test_router();
+        let token = register_and_login(&router, "subtree@example.com").await;
+        let root = create(
+            &router,
+            &token,
+            json!({ "title": "Root", "description": "" }),
+        )
+        .await;
+        let child = create(
+            &router,
+            &token,
+            json!({ "title": "Child", "description": "", "parent_id": root }),
+        )
+        .await;
+
+        let (status, _) = send(
+            &router,
+            Method::DELETE,
+            &format!("/api/todo/{root}"),
+            Some(&token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, body) = send(&router, Method::GET, "/api/todo", Some(&token), None).await;
+        assert_eq!(body["data"]["total"], 0);
+
+        // 父任务仍在回收站中时恢复为顶层任务
+        let restore = format!("/api/todo/{child}/restore");
+        let (status, body) = send(&router, Method::POST, &restore, Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["data"]["parent_id"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_restore_brings_back_subtree() {
+        let router = test_router();
+        let token = register_and_login(&router, "restore@example.com").await;
+        let root = create(
+            &router,
+            &token,
+            json!({ "title": "Root", "description": "" }),
+        )
+        .await;
+        let child = create(
+            &router,
+            &token,
+            json!({ "title": "Child", "description": "", "parent_id": root }),
+        )
+        .await;
+        create(
+            &router,
+            &token,
+            json!({ "title": "Grandchild", "description": "", "parent_id": child }),
+        )
+        .await;
+        let earlier = create(
+            &router,
+            &token,
+            json!({ "title": "Earlier", "description": "", "parent_id": root }),
+        )
+        .await;
+
+        for id in [earlier, root] {
+            let uri = format!("/api/todo/{id}");
+            let (status, _) = send(&router, Method::DELETE, &uri, Some(&token), None).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let restore = format!("/api/todo/{root}/restore");
+        let (status, _) = send(&router, Method::POST, &restore, Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // 之前单独删除的子任务不随父任务恢复
+        let root_tree = tree(&router, &token, root).await;
+        let children = root_tree["children"].as_array().unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0]["id"], child);
+        assert_eq!(children[0]["children"][0]["title"], "Grandchild");
+        let (_, body) = send(&router, Method::GET, "/api/todo/trash", Some(&token), None).await;
+        let trash = body["data"].as_array().unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0]["id"], earlier);
+    }
+
+    #[tokio::test]
+    async fn test_create_todo_rejects_empty_title() {
+        let router = test_router();
+        let token = register_and_login(&router, "empty@example.com").await;
+        let (status, body) = send(
+            &router,
+            Method::POST,
+            "/api/todo",
+            Some(&token),
+            Some(json!({ "title": "  ", "description": "" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"], "VALIDATION_FAILED");
+    }
 }
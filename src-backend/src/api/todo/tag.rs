@@ -0,0 +1,313 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{path, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::{
+        middleware::auth::AuthUser,
+        request::{success_response, Response},
+    },
+    application::tag::service::{TagService, UpdateTag},
+    domain::error::AppResult,
+};
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct CreateTagRequest {
+    name: String,
+    // #rrggbb, 省略时使用默认颜色
+    color: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct UpdateTagRequest {
+    name: Option<String>,
+    color: Option<String>,
+}
+
+pub async fn list_tags(
+    State(tag_service): State<Arc<dyn TagService>>,
+    auth_user: AuthUser,
+) -> AppResult<Response> {
+    let tags = tag_service.list(auth_user.user_id).await?;
+    Ok(success_response(serde_json::to_value(tags).unwrap()))
+}
+
+pub async fn create_tag(
+    State(tag_service): State<Arc<dyn TagService>>,
+    auth_user: AuthUser,
+    playload: Json<CreateTagRequest>,
+) -> AppResult<Response> {
+    let playload = playload.0;
+    let tag = tag_service
+        .create(auth_user.user_id, playload.name, playload.color)
+        .await?;
+    Ok(success_response(serde_json::to_value(tag).unwrap()))
+}
+
+pub async fn update_tag(
+    State(tag_service): State<Arc<dyn TagService>>,
+    auth_user: AuthUser,
+    path::Path(id): path::Path<i32>,
+    playload: Json<UpdateTagRequest>,
+) -> AppResult<Response> {
+    let playload = playload.0;
+    let update = UpdateTag {
+        name: playload.name,
+        color: playload.color,
+    };
+    let tag = tag_service.update(auth_user.user_id, id, update).await?;
+    Ok(success_response(serde_json::to_value(tag).unwrap()))
+}
+
+pub async fn delete_tag(
+    State(tag_service): State<Arc<dyn TagService>>,
+    auth_user: AuthUser,
+    path::Path(id): path::Path<i32>,
+) -> AppResult<Response> {
+    tag_service.delete(auth_user.user_id, id).await?;
+    Ok(success_response(serde_json::Value::Null))
+}
+
+pub async fn list_todo_tags(
+    State(tag_service): State<Arc<dyn TagService>>,
+    auth_user: AuthUser,
+    path::Path(id): path::Path<i32>,
+) -> AppResult<Response> {
+    let tags = tag_service.list_for_todo(auth_user.user_id, id).await?;
+    Ok(success_response(serde_json::to_value(tags).unwrap()))
+}
+
+pub async fn attach_tag(
+    State(tag_service): State<Arc<dyn TagService>>,
+    auth_user: AuthUser,
+    path::Path((id, tag_id)): path::Path<(i32, i32)>,
+) -> AppResult<Response> {
+    let tags = tag_service.attach(auth_user.user_id, id, tag_id).await?;
+    Ok(success_response(serde_json::to_value(tags).unwrap()))
+}
+
+pub async fn detach_tag(
+    State(tag_service): State<Arc<dyn TagService>>,
+    auth_user: AuthUser,
+    path::Path((id, tag_id)): path::Path<(i32, i32)>,
+) -> AppResult<Response> {
+    let tags = tag_service.detach(auth_user.user_id, id, tag_id).await?;
+    Ok(success_response(serde_json::to_value(tags).unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    use crate::api::testing::{register_and_login, send, test_router};
+
+    async fn create_tag(router: &axum::Router, token: &str, name: &str) -> i64 {
+        let (status, body) = send(
+            router,
+            Method::POST,
+            "/api/tags",
+            Some(token),
+            Some(json!({ "name": name })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        body["data"]["id"].as_i64().unwrap()
+    }
+
+    async fn create_todo(router: &axum::Router, token: &str, title: &str) -> i64 {
+        let (status, body) = send(
+            router,
+            Method::POST,
+            "/api/todo",
+            Some(token),
+            Some(json!({ "title": title, "description": "" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        body["data"]["id"].as_i64().unwrap()
+    }
+
+    async fn attach(router: &axum::Router, token: &str, todo: i64, tag: i64) -> StatusCode {
+        let uri = format!("/api/todo/{todo}/tags/{tag}");
+        send(router, Method::PUT, &uri, Some(token), None).await.0
+    }
+
+    async fn titles(router: &axum::Router, token: &str, query: &str) -> Vec<String> {
+        let uri = format!("/api/todo?sort=title&order=asc&{query}");
+        let (status, body) = send(router, Method::GET, &uri, Some(token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        body["data"]["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|todo| todo["title"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_tag_crud() {
+        let router = test_router();
+        let token = register_and_login(&router, "tags@example.com").await;
+
+        let (status, body) = send(
+            &router,
+            Method::POST,
+            "/api/tags",
+            Some(&token),
+            Some(json!({ "name": " work ", "color": "#FF8800" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["name"], "work");
+        assert_eq!(body["data"]["color"], "#ff8800");
+        let id = body["data"]["id"].as_i64().unwrap();
+
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/tags",
+            Some(&token),
+            Some(json!({ "name": "work" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/tags",
+            Some(&token),
+            Some(json!({ "name": "home", "color": "red" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (status, body) = send(
+            &router,
+            Method::PATCH,
+            &format!("/api/tags/{id}"),
+            Some(&token),
+            Some(json!({ "name": "office" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["name"], "office");
+        assert_eq!(body["data"]["color"], "#ff8800");
+
+        // 其他用户看不到也不能修改
+        let other = register_and_login(&router, "other@example.com").await;
+        let (_, body) = send(&router, Method::GET, "/api/tags", Some(&other), None).await;
+        assert_eq!(body["data"], json!([]));
+        let (status, _) = send(
+            &router,
+            Method::DELETE,
+            &format!("/api/tags/{id}"),
+            Some(&other),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = send(
+            &router,
+            Method::DELETE,
+            &format!("/api/tags/{id}"),
+            Some(&token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, body) = send(&router, Method::GET, "/api/tags", Some(&token), None).await;
+        assert_eq!(body["data"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_attach_detach_and_filter() {
+        let router = test_router();
+        let token = register_and_login(&router, "filter@example.com").await;
+        let work = create_tag(&router, &token, "work").await;
+        let urgent = create_tag(&router, &token, "urgent").await;
+        let report = create_todo(&router, &token, "Report").await;
+        let review = create_todo(&router, &token, "Review").await;
+        create_todo(&router, &token, "Groceries").await;
+
+        assert_eq!(attach(&router, &token, report, work).await, StatusCode::OK);
+        assert_eq!(
+            attach(&router, &token, report, urgent).await,
+            StatusCode::OK
+        );
+        // 重复关联不报错
+        assert_eq!(
+            attach(&router, &token, report, urgent).await,
+            StatusCode::OK
+        );
+        assert_eq!(attach(&router, &token, review, work).await, StatusCode::OK);
+
+        let uri = format!("/api/todo/{report}/tags");
+        let (_, body) = send(&router, Method::GET, &uri, Some(&token), None).await;
+        assert_eq!(body["data"][0]["name"], "urgent");
+        assert_eq!(body["data"][1]["name"], "work");
+
+        let any = format!("tags={work},{urgent}");
+        assert_eq!(titles(&router, &token, &any).await, ["Report", "Review"]);
+        let all = format!("tags={work},{urgent}&tag_match=all");
+        assert_eq!(titles(&router, &token, &all).await, ["Report"]);
+        let duplicated = format!("tags={urgent},{urgent}&tag_match=all");
+        assert_eq!(titles(&router, &token, &duplicated).await, ["Report"]);
+
+        let uri = format!("/api/todo/{report}/tags/{urgent}");
+        let (status, body) = send(&router, Method::DELETE, &uri, Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"].as_array().unwrap().len(), 1);
+        assert!(titles(&router, &token, &all).await.is_empty());
+
+        // 删除标签后不再参与过滤
+        let uri = format!("/api/tags/{work}");
+        send(&router, Method::DELETE, &uri, Some(&token), None).await;
+        assert!(titles(&router, &token, &format!("tags={work}"))
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tags_are_scoped_to_owner() {
+        let router = test_router();
+        let token = register_and_login(&router, "owner@example.com").await;
+        let other = register_and_login(&router, "intruder@example.com").await;
+        let tag = create_tag(&router, &token, "private").await;
+        let todo = create_todo(&router, &token, "Mine").await;
+        let other_tag = create_tag(&router, &other, "theirs").await;
+        let other_todo = create_todo(&router, &other, "Theirs").await;
+
+        assert_eq!(
+            attach(&router, &token, todo, other_tag).await,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            attach(&router, &token, other_todo, tag).await,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            attach(&router, &other, other_todo, other_tag).await,
+            StatusCode::OK
+        );
+        // 用其他用户的标签过滤得不到任何结果
+        let query = format!("tags={other_tag}");
+        assert!(titles(&router, &token, &query).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_rejects_invalid_tag_filter() {
+        let router = test_router();
+        let token = register_and_login(&router, "invalid@example.com").await;
+        for query in ["tags=1,abc", "tags=1&tag_match=some"] {
+            let uri = format!("/api/todo?{query}");
+            let (status, _) = send(&router, Method::GET, &uri, Some(&token), None).await;
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    }
+}
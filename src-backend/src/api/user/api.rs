@@ -1,11 +1,21 @@
 use std::sync::Arc;
 
-use axum::response::IntoResponse;
-use axum::{extract, Json};
+use axum::{extract::State, Json};
+use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
 
-use crate::api::request::{error_response, success_response};
+use crate::api::middleware::{auth::AuthUser, client::ClientInfo};
+use crate::api::request::{success_response, Response};
+use crate::api::user::two_factor::finish_login;
+use crate::application::account::service::AccountService;
+use crate::application::audit::service::AuditContext;
+use crate::application::auth::service::{AuthService, SessionInfo, TokenPair};
+use crate::application::two_factor::service::TwoFactorService;
 use crate::application::user::service::UserService;
+use crate::domain::{
+    entities::user::{User, UserRole},
+    error::AppResult,
+};
 
 #[derive(Deserialize, Serialize, Clone)]
 pub struct CreateUserRequest {
@@ -19,32 +29,647 @@ pub struct CreateUserRequest {
 pub struct UserLoginRequest {
     pub email: String,
     pub password: String,
+    // 显示在会话列表中的设备名称, 可选
+    #[serde(default)]
+    pub device_name: Option<String>,
+}
+
+// 对外返回的用户信息, 不包含密码和盐
+#[derive(Deserialize, Serialize, Clone)]
+pub struct UserResponse {
+    pub id: i32,
+    pub username: String,
+    pub email: String,
+    pub email_verified_at: Option<DateTime<Local>>,
+    pub role: UserRole,
+    pub created_at: DateTime<Local>,
+    pub updated_at: DateTime<Local>,
+}
+
+impl From<User> for UserResponse {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            email_verified_at: user.email_verified_at,
+            role: user.role,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct EmailRequest {
+    pub email: String,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub password: String,
+    pub password_confirmation: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct LoginResponse {
+    #[serde(flatten)]
+    pub tokens: TokenPair,
+    pub user: UserResponse,
 }
 
 pub async fn register(
-    user_service: extract::Extension<Arc<dyn UserService>>,
+    State(user_service): State<Arc<dyn UserService>>,
+    State(account_service): State<Arc<dyn AccountService>>,
+    client: ClientInfo,
     playload: Json<CreateUserRequest>,
-) -> impl IntoResponse {
+) -> AppResult<Response> {
     // get request body from playload
     let req = playload.0.clone();
-    let res = user_service.register(req).await;
-    if let Ok(user) = res {
-        success_response(serde_json::to_value(user).unwrap())
-    } else {
-        error_response(500, "Failed to register user".to_string())
+    let user = user_service
+        .register(req, &AuditContext::new(&client, None))
+        .await?;
+    // 验证邮件发送失败不影响注册, 用户可以稍后重新请求
+    if let Err(err) = account_service.send_email_verification(&user).await {
+        log::warn!(
+            "failed to send verification email to user {}: {err}",
+            user.id
+        );
     }
+    Ok(success_response(
+        serde_json::to_value(UserResponse::from(user)).unwrap(),
+    ))
 }
 
 pub async fn login(
-    user_service: extract::Extension<Arc<dyn UserService>>,
+    State(user_service): State<Arc<dyn UserService>>,
+    State(auth_service): State<Arc<dyn AuthService>>,
+    State(account_service): State<Arc<dyn AccountService>>,
+    State(two_factor_service): State<Arc<dyn TwoFactorService>>,
+    client: ClientInfo,
     playload: Json<UserLoginRequest>,
-) -> impl IntoResponse {
+) -> AppResult<Response> {
     // get request body from playload
     let req = playload.0.clone();
-    let res = user_service.login(req).await;
-    if let Ok(user) = res {
-        success_response(serde_json::to_value(user).unwrap())
-    } else {
-        error_response(500, "Failed to login user".to_string())
+    let info = SessionInfo::new(&client, req.device_name.clone());
+    let user = user_service
+        .login(req, &AuditContext::new(&client, None))
+        .await?;
+    finish_login(
+        auth_service.as_ref(),
+        account_service.as_ref(),
+        two_factor_service.as_ref(),
+        user,
+        info,
+    )
+    .await
+}
+
+// 用刷新令牌换取新的 access token, 旧的刷新令牌随即失效
+pub async fn refresh(
+    State(auth_service): State<Arc<dyn AuthService>>,
+    client: ClientInfo,
+    playload: Json<RefreshTokenRequest>,
+) -> AppResult<Response> {
+    let tokens = auth_service
+        .refresh(&playload.refresh_token, &client)
+        .await?;
+    Ok(success_response(serde_json::to_value(tokens).unwrap()))
+}
+
+pub async fn logout(
+    State(auth_service): State<Arc<dyn AuthService>>,
+    client: ClientInfo,
+    playload: Json<RefreshTokenRequest>,
+) -> AppResult<Response> {
+    auth_service
+        .logout(&playload.refresh_token, &AuditContext::new(&client, None))
+        .await?;
+    Ok(success_response(serde_json::Value::Null))
+}
+
+// 吊销当前用户的所有会话和刷新令牌
+pub async fn logout_all(
+    State(auth_service): State<Arc<dyn AuthService>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+) -> AppResult<Response> {
+    let ctx = AuditContext::new(&client, Some(auth_user.user_id));
+    auth_service.logout_all(auth_user.user_id, &ctx).await?;
+    Ok(success_response(serde_json::Value::Null))
+}
+
+pub async fn verify_email(
+    State(account_service): State<Arc<dyn AccountService>>,
+    client: ClientInfo,
+    playload: Json<VerifyEmailRequest>,
+) -> AppResult<Response> {
+    let ctx = AuditContext::new(&client, None);
+    let user = account_service.verify_email(&playload.token, &ctx).await?;
+    Ok(success_response(
+        serde_json::to_value(UserResponse::from(user)).unwrap(),
+    ))
+}
+
+// 无论邮箱是否存在都返回成功, 避免被用来探测已注册的邮箱
+pub async fn resend_verification(
+    State(account_service): State<Arc<dyn AccountService>>,
+    playload: Json<EmailRequest>,
+) -> AppResult<Response> {
+    account_service
+        .request_email_verification(&playload.email)
+        .await?;
+    Ok(success_response(serde_json::Value::Null))
+}
+
+pub async fn forgot_password(
+    State(account_service): State<Arc<dyn AccountService>>,
+    playload: Json<EmailRequest>,
+) -> AppResult<Response> {
+    account_service
+        .request_password_reset(&playload.email)
+        .await?;
+    Ok(success_response(serde_json::Value::Null))
+}
+
+pub async fn reset_password(
+    State(account_service): State<Arc<dyn AccountService>>,
+    client: ClientInfo,
+    playload: Json<ResetPasswordRequest>,
+) -> AppResult<Response> {
+    account_service
+        .reset_password(playload.0, &AuditContext::new(&client, None))
+        .await?;
+    Ok(success_response(serde_json::Value::Null))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    use crate::api::testing::{
+        register_and_login, send, test_config, test_router, test_router_with,
+        test_router_with_mailer, test_state_with, token_from_mail, wait_for_mail, TEST_PASSWORD,
+    };
+
+    async fn post(
+        router: &axum::Router,
+        uri: &str,
+        body: serde_json::Value,
+    ) -> (StatusCode, serde_json::Value) {
+        send(router, Method::POST, uri, None, Some(body)).await
+    }
+
+    async fn login(router: &axum::Router, email: &str) -> serde_json::Value {
+        let (status, body) = send(
+            router,
+            Method::POST,
+            "/api/auth/login",
+            None,
+            Some(json!({ "email": email, "password": TEST_PASSWORD })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        body["data"].clone()
+    }
+
+    async fn refresh(
+        router: &axum::Router,
+        refresh_token: &str,
+    ) -> (StatusCode, serde_json::Value) {
+        send(
+            router,
+            Method::POST,
+            "/api/auth/refresh",
+            None,
+            Some(json!({ "refresh_token": refresh_token })),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_login_returns_token_and_sanitized_user() {
+        let router = test_router();
+        register_and_login(&router, "login@example.com").await;
+
+        let (status, body) = send(
+            &router,
+            Method::POST,
+            "/api/auth/login",
+            None,
+            Some(json!({ "email": "login@example.com", "password": TEST_PASSWORD })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["data"]["token"].is_string());
+        assert_eq!(body["data"]["user"]["email"], "login@example.com");
+        assert!(body["data"]["user"].get("password").is_none());
+        assert!(body["data"]["user"].get("salt").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_register_rejects_duplicate_email() {
+        let router = test_router();
+        register_and_login(&router, "dup@example.com").await;
+
+        let (status, body) = send(
+            &router,
+            Method::POST,
+            "/api/auth/register",
+            None,
+            Some(json!({
+                "email": "dup@example.com",
+                "username": "dup",
+                "password": TEST_PASSWORD,
+                "password_confirmation": TEST_PASSWORD,
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"], "CONFLICT");
+    }
+
+    #[tokio::test]
+    async fn test_login_rejects_wrong_password() {
+        let router = test_router();
+        register_and_login(&router, "wrong@example.com").await;
+
+        let (status, body) = send(
+            &router,
+            Method::POST,
+            "/api/auth/login",
+            None,
+            Some(json!({ "email": "wrong@example.com", "password": "nope" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"], "UNAUTHORIZED");
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rotates_token() {
+        let router = test_router();
+        register_and_login(&router, "refresh@example.com").await;
+        let tokens = login(&router, "refresh@example.com").await;
+        assert!(tokens["expires_in"].as_i64().unwrap() > 0);
+
+        let first = tokens["refresh_token"].as_str().unwrap();
+        let (status, body) = refresh(&router, first).await;
+        assert_eq!(status, StatusCode::OK);
+        let second = body["data"]["refresh_token"].as_str().unwrap().to_string();
+        assert_ne!(first, second);
+
+        let access = body["data"]["token"].as_str().unwrap();
+        let (status, _) = send(&router, Method::GET, "/api/todo", Some(access), None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, _) = refresh(&router, &second).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_reuse_revokes_family() {
+        let router = test_router();
+        register_and_login(&router, "reuse@example.com").await;
+        let stolen = login(&router, "reuse@example.com").await["refresh_token"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let other_session = login(&router, "reuse@example.com").await["refresh_token"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let (_, body) = refresh(&router, &stolen).await;
+        let rotated = body["data"]["refresh_token"].as_str().unwrap().to_string();
+
+        // 重放已轮换的令牌, 同一 family 中新签发的令牌也一并失效
+        let (status, body) = refresh(&router, &stolen).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"], "UNAUTHORIZED");
+        let (status, _) = refresh(&router, &rotated).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _) = refresh(&router, &other_session).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_logout_and_logout_all() {
+        let router = test_router();
+        register_and_login(&router, "logout@example.com").await;
+        let first = login(&router, "logout@example.com").await;
+        let second = login(&router, "logout@example.com").await;
+        let third = login(&router, "logout@example.com").await;
+
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/auth/logout",
+            None,
+            Some(json!({ "refresh_token": first["refresh_token"] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = refresh(&router, first["refresh_token"].as_str().unwrap()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _) = send(&router, Method::POST, "/api/auth/logout-all", None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/auth/logout-all",
+            second["token"].as_str(),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        for tokens in [&second, &third] {
+            let (status, _) = refresh(&router, tokens["refresh_token"].as_str().unwrap()).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rejects_unknown_token() {
+        let router = test_router();
+        let (status, _) = refresh(&router, "not-a-token").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_register_rejects_weak_password() {
+        let router = test_router();
+        for password in ["short", "password123"] {
+            let (status, body) = send(
+                &router,
+                Method::POST,
+                "/api/auth/register",
+                None,
+                Some(json!({
+                    "email": "weak@example.com",
+                    "username": "weak",
+                    "password": password,
+                    "password_confirmation": password,
+                })),
+            )
+            .await;
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+            assert_eq!(body["error"], "WEAK_PASSWORD");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_locks_account_after_failures() {
+        let router = test_router();
+        register_and_login(&router, "locked@example.com").await;
+        let attempt = |password: &'static str| {
+            send(
+                &router,
+                Method::POST,
+                "/api/auth/login",
+                None,
+                Some(json!({ "email": "locked@example.com", "password": password })),
+            )
+        };
+
+        for _ in 0..test_config().auth.max_failed_logins {
+            let (status, _) = attempt("wrong-password").await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+        }
+        let (status, body) = attempt(TEST_PASSWORD).await;
+        assert_eq!(status, StatusCode::LOCKED);
+        assert_eq!(body["error"], "ACCOUNT_LOCKED");
+    }
+
+    #[tokio::test]
+    async fn test_auth_endpoints_are_rate_limited_per_ip() {
+        let mut config = test_config();
+        config.auth.rate_limit_per_minute = 2;
+        config.server.trusted_proxies = vec!["10.0.0.0/8".parse().unwrap()];
+        let router = test_router_with(config);
+
+        // 请求经由 10.0.0.2 的反向代理转发
+        let request = |forwarded_for: &str| {
+            let mut request = axum::http::Request::builder()
+                .method(Method::POST)
+                .uri("/api/auth/refresh")
+                .header("content-type", "application/json")
+                .header("x-forwarded-for", forwarded_for)
+                .body(axum::body::Body::from(
+                    json!({ "refresh_token": "not-a-token" }).to_string(),
+                ))
+                .unwrap();
+            let proxy: std::net::SocketAddr = "10.0.0.2:40000".parse().unwrap();
+            request
+                .extensions_mut()
+                .insert(axum::extract::ConnectInfo(proxy));
+            request
+        };
+        let oneshot = |ip: &str| tower::ServiceExt::oneshot(router.clone(), request(ip));
+
+        for _ in 0..2 {
+            let response = oneshot("203.0.113.7, 10.0.0.1").await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+        // 客户端伪造的最左侧地址不能绕过限流
+        let response = oneshot("192.0.2.44, 203.0.113.7, 10.0.0.1").await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = response.headers()["retry-after"]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(retry_after >= 1);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"], "RATE_LIMITED");
+
+        let response = oneshot("198.51.100.1").await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_verify_email_with_mailed_token() {
+        let mut config = test_config();
+        config.auth.require_verified_email = true;
+        let (router, mailer) = test_router_with_mailer(config);
+
+        let (status, _) = post(
+            &router,
+            "/api/auth/register",
+            json!({
+                "email": "verify@example.com",
+                "username": "verify",
+                "password": TEST_PASSWORD,
+                "password_confirmation": TEST_PASSWORD,
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let credentials = json!({ "email": "verify@example.com", "password": TEST_PASSWORD });
+        let (status, body) = post(&router, "/api/auth/login", credentials.clone()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"], "EMAIL_NOT_VERIFIED");
+
+        // 重新发送后只有最新的令牌有效
+        let first = token_from_mail(&mailer, "verify@example.com");
+        let sent = mailer.total();
+        let (status, _) = post(
+            &router,
+            "/api/auth/verify-email/resend",
+            json!({ "email": "verify@example.com" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        wait_for_mail(&mailer, sent + 1).await;
+        let second = token_from_mail(&mailer, "verify@example.com");
+        assert_ne!(first, second);
+        let (status, _) = post(&router, "/api/auth/verify-email", json!({ "token": first })).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (status, body) = post(
+            &router,
+            "/api/auth/verify-email",
+            json!({ "token": second }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["data"]["email_verified_at"].is_string());
+        let (status, _) = post(
+            &router,
+            "/api/auth/verify-email",
+            json!({ "token": second }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (status, _) = post(&router, "/api/auth/login", credentials).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // 已验证的邮箱不会再收到验证邮件, 也不会启动后台任务
+        let sent = mailer.total();
+        post(
+            &router,
+            "/api/auth/verify-email/resend",
+            json!({ "email": "verify@example.com" }),
+        )
+        .await;
+        assert_eq!(mailer.total(), sent);
+    }
+
+    struct FailingMailer;
+
+    #[async_trait::async_trait]
+    impl crate::infastructure::mail::Mailer for FailingMailer {
+        async fn send(&self, _email: crate::infastructure::mail::Email) -> anyhow::Result<()> {
+            anyhow::bail!("smtp server unavailable")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_forgot_password_does_not_reveal_accounts() {
+        let state = test_state_with(test_config(), std::sync::Arc::new(FailingMailer));
+        let router = crate::api::router::build_router(state);
+        register_and_login(&router, "exists@example.com").await;
+
+        // 邮件发送失败时, 已注册和未注册的邮箱得到相同的响应
+        for email in ["exists@example.com", "missing@example.com"] {
+            let (status, body) = post(
+                &router,
+                "/api/auth/forgot-password",
+                json!({ "email": email }),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+            assert!(body["data"].is_null());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reset_password_with_mailed_token() {
+        let (router, mailer) = test_router_with_mailer(test_config());
+        register_and_login(&router, "reset@example.com").await;
+        let session = login(&router, "reset@example.com").await;
+
+        // 未注册的邮箱同样返回成功, 但不发送邮件, 也不会启动后台任务
+        let sent = mailer.total();
+        let (status, _) = post(
+            &router,
+            "/api/auth/forgot-password",
+            json!({ "email": "nobody@example.com" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(mailer.total(), sent);
+
+        let (status, _) = post(
+            &router,
+            "/api/auth/forgot-password",
+            json!({ "email": "reset@example.com" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        wait_for_mail(&mailer, sent + 1).await;
+        let token = token_from_mail(&mailer, "reset@example.com");
+
+        let reset = |password: &str| {
+            json!({
+                "token": token,
+                "password": password,
+                "password_confirmation": password,
+            })
+        };
+        let (status, body) = post(&router, "/api/auth/reset-password", reset("password")).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"], "WEAK_PASSWORD");
+        let (status, _) = post(
+            &router,
+            "/api/auth/reset-password",
+            reset("a-brand-new-passphrase"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = post(
+            &router,
+            "/api/auth/reset-password",
+            reset("another-new-passphrase"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (status, _) = post(
+            &router,
+            "/api/auth/login",
+            json!({ "email": "reset@example.com", "password": TEST_PASSWORD }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = post(
+            &router,
+            "/api/auth/login",
+            json!({ "email": "reset@example.com", "password": "a-brand-new-passphrase" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        // 重置密码后之前的会话全部失效
+        let (status, _) = refresh(&router, session["refresh_token"].as_str().unwrap()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
     }
 }
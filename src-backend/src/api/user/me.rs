@@ -0,0 +1,346 @@
+use std::sync::Arc;
+
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::{
+        middleware::{auth::AuthUser, client::ClientInfo},
+        request::{success_response, Response},
+        user::api::UserResponse,
+    },
+    application::{
+        account::service::AccountService,
+        audit::service::AuditContext,
+        auth::service::{AuthService, SessionInfo},
+        user::service::UserService,
+    },
+    domain::error::AppResult,
+};
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct UpdateProfileRequest {
+    pub username: Option<String>,
+    pub email: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub password: String,
+    pub password_confirmation: String,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct DeleteAccountRequest {
+    pub password: String,
+}
+
+pub async fn get_me(
+    State(user_service): State<Arc<dyn UserService>>,
+    auth_user: AuthUser,
+) -> AppResult<Response> {
+    let user = user_service.get_user_by_id(auth_user.user_id).await?;
+    Ok(success_response(
+        serde_json::to_value(UserResponse::from(user)).unwrap(),
+    ))
+}
+
+pub async fn update_me(
+    State(user_service): State<Arc<dyn UserService>>,
+    State(account_service): State<Arc<dyn AccountService>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    playload: Json<UpdateProfileRequest>,
+) -> AppResult<Response> {
+    let ctx = AuditContext::new(&client, Some(auth_user.user_id));
+    let before = user_service.get_user_by_id(auth_user.user_id).await?;
+    let user = user_service
+        .update_profile(auth_user.user_id, playload.0, &ctx)
+        .await?;
+    // 新邮箱需要重新验证, 邮件发送失败时用户可以稍后重新请求
+    if user.email != before.email {
+        if let Err(err) = account_service.send_email_verification(&user).await {
+            log::warn!(
+                "failed to send verification email to user {}: {err}",
+                user.id
+            );
+        }
+    }
+    Ok(success_response(
+        serde_json::to_value(UserResponse::from(user)).unwrap(),
+    ))
+}
+
+// 修改密码后吊销所有会话, 并为当前客户端开启新的会话
+pub async fn change_password(
+    State(user_service): State<Arc<dyn UserService>>,
+    State(auth_service): State<Arc<dyn AuthService>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    playload: Json<ChangePasswordRequest>,
+) -> AppResult<Response> {
+    let ctx = AuditContext::new(&client, Some(auth_user.user_id));
+    user_service
+        .change_password(auth_user.user_id, playload.0, &ctx)
+        .await?;
+    // 沿用当前会话的设备名称
+    let device_name = match auth_user.session_id {
+        Some(session_id) => auth_service
+            .list_sessions(auth_user.user_id)
+            .await?
+            .into_iter()
+            .find(|session| session.id == session_id)
+            .and_then(|session| session.device_name),
+        None => None,
+    };
+    auth_service.logout_all(auth_user.user_id, &ctx).await?;
+    let tokens = auth_service
+        .issue(auth_user.user_id, SessionInfo::new(&client, device_name))
+        .await?;
+    Ok(success_response(serde_json::to_value(tokens).unwrap()))
+}
+
+// 软删除账户及其所有 todo 并吊销全部会话, 回收站保留期过后由后台任务物理删除
+pub async fn delete_me(
+    State(user_service): State<Arc<dyn UserService>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    playload: Json<DeleteAccountRequest>,
+) -> AppResult<Response> {
+    let ctx = AuditContext::new(&client, Some(auth_user.user_id));
+    user_service
+        .delete_account(auth_user.user_id, &playload.password, &ctx)
+        .await?;
+    Ok(success_response(serde_json::Value::Null))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use chrono::{Duration, Local};
+    use serde_json::json;
+
+    use crate::api::{
+        router::build_router,
+        testing::{
+            register_and_login, send, test_config, test_router, test_router_with_mailer,
+            test_state_with_mailer, TEST_PASSWORD,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_get_and_update_profile() {
+        let (router, mailer) = test_router_with_mailer(test_config());
+        let token = register_and_login(&router, "me@example.com").await;
+        register_and_login(&router, "taken@example.com").await;
+
+        let (status, body) = send(&router, Method::GET, "/api/me", Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["email"], "me@example.com");
+        assert!(body["data"].get("password").is_none());
+
+        let (status, body) = send(
+            &router,
+            Method::PATCH,
+            "/api/me",
+            Some(&token),
+            Some(json!({ "username": "  renamed  " })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["username"], "renamed");
+
+        for (email, expected) in [
+            ("taken@example.com", StatusCode::CONFLICT),
+            ("not-an-email", StatusCode::UNPROCESSABLE_ENTITY),
+        ] {
+            let (status, _) = send(
+                &router,
+                Method::PATCH,
+                "/api/me",
+                Some(&token),
+                Some(json!({ "email": email })),
+            )
+            .await;
+            assert_eq!(status, expected);
+        }
+
+        let (status, body) = send(
+            &router,
+            Method::PATCH,
+            "/api/me",
+            Some(&token),
+            Some(json!({ "email": "new@example.com" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["email"], "new@example.com");
+        assert_eq!(body["data"]["username"], "renamed");
+        assert!(body["data"]["email_verified_at"].is_null());
+        assert_eq!(mailer.sent().last().unwrap().to, "new@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_change_password_requires_current_password() {
+        let router = test_router();
+        let token = register_and_login(&router, "change@example.com").await;
+
+        let change = |current: &str| {
+            json!({
+                "current_password": current,
+                "password": "a-brand-new-passphrase",
+                "password_confirmation": "a-brand-new-passphrase",
+            })
+        };
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/me/password",
+            Some(&token),
+            Some(change("wrong-password")),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (status, body) = send(
+            &router,
+            Method::POST,
+            "/api/me/password",
+            Some(&token),
+            Some(change(TEST_PASSWORD)),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["data"]["refresh_token"].is_string());
+
+        for (password, expected) in [
+            (TEST_PASSWORD, StatusCode::UNAUTHORIZED),
+            ("a-brand-new-passphrase", StatusCode::OK),
+        ] {
+            let (status, _) = send(
+                &router,
+                Method::POST,
+                "/api/auth/login",
+                None,
+                Some(json!({ "email": "change@example.com", "password": password })),
+            )
+            .await;
+            assert_eq!(status, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_account_soft_deletes_todos() {
+        let (state, _) = test_state_with_mailer(test_config());
+        let router = build_router(state.clone());
+        let token = register_and_login(&router, "delete@example.com").await;
+        let user_id = state
+            .user_service
+            .get_user_by_email("delete@example.com".to_string())
+            .await
+            .unwrap()
+            .id;
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/todo",
+            Some(&token),
+            Some(json!({ "title": "Todo", "description": "" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, _) = send(
+            &router,
+            Method::DELETE,
+            "/api/me",
+            Some(&token),
+            Some(json!({ "password": "wrong-password" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (status, _) = send(
+            &router,
+            Method::DELETE,
+            "/api/me",
+            Some(&token),
+            Some(json!({ "password": TEST_PASSWORD })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        // 已签发的 access token 随账户删除立即失效
+        let (status, _) = send(&router, Method::GET, "/api/me", Some(&token), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let trash = state.todo_service.list_trash(user_id).await.unwrap();
+        assert_eq!(trash.len(), 1);
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/auth/login",
+            None,
+            Some(json!({ "email": "delete@example.com", "password": TEST_PASSWORD })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_deleted_email_is_reusable_after_purge() {
+        let (state, _) = test_state_with_mailer(test_config());
+        let router = build_router(state.clone());
+        let token = register_and_login(&router, "again@example.com").await;
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/todo",
+            Some(&token),
+            Some(json!({ "title": "Todo", "description": "" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(
+            &router,
+            Method::DELETE,
+            "/api/me",
+            Some(&token),
+            Some(json!({ "password": TEST_PASSWORD })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let register = json!({
+            "email": "again@example.com",
+            "username": "again",
+            "password": TEST_PASSWORD,
+            "password_confirmation": TEST_PASSWORD,
+        });
+        let (status, body) = send(
+            &router,
+            Method::POST,
+            "/api/auth/register",
+            None,
+            Some(register),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(body["message"]
+            .as_str()
+            .unwrap()
+            .contains("deleted account"));
+
+        // 清理后邮箱释放, 旧账户的数据不会留给新账户
+        let purged = state
+            .user_service
+            .purge_deleted_before(Local::now() + Duration::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(purged, 1);
+        let token = register_and_login(&router, "again@example.com").await;
+        let (status, body) = send(&router, Method::GET, "/api/todo", Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["total"], 0);
+    }
+}
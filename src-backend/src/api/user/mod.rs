@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
 
 pub mod api;
+pub mod me;
+pub mod oidc;
+pub mod session;
+pub mod tokens;
+pub mod two_factor;
 
 #[derive(Deserialize, Serialize, Clone)]
 pub struct CreateUserRequest {
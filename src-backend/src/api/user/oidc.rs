@@ -0,0 +1,495 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{path, State},
+    Json,
+};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::{
+        middleware::{auth::AuthUser, client::ClientInfo},
+        request::{success_response, Response},
+        user::two_factor::finish_login,
+    },
+    application::{
+        account::service::AccountService,
+        audit::service::AuditContext,
+        auth::service::{AuthService, SessionInfo},
+        oidc::service::{OidcAuthorization, OidcService},
+        two_factor::service::TwoFactorService,
+    },
+    domain::{entities::identity::Identity, error::AppResult},
+};
+
+// 身份提供方重定向到前端回调地址时携带的参数, 由前端转发
+#[derive(Deserialize, Serialize, Clone)]
+pub struct OidcCallbackRequest {
+    pub code: String,
+    pub state: String,
+    #[serde(default)]
+    pub device_name: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct OidcAuthorizationResponse {
+    pub authorization_url: String,
+    pub state: String,
+}
+
+impl From<OidcAuthorization> for OidcAuthorizationResponse {
+    fn from(authorization: OidcAuthorization) -> Self {
+        Self {
+            authorization_url: authorization.authorization_url,
+            state: authorization.state,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct IdentityResponse {
+    pub id: i32,
+    pub issuer: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub created_at: DateTime<Local>,
+    pub last_login_at: Option<DateTime<Local>>,
+}
+
+impl From<Identity> for IdentityResponse {
+    fn from(identity: Identity) -> Self {
+        Self {
+            id: identity.id,
+            issuer: identity.issuer,
+            subject: identity.subject,
+            email: identity.email,
+            created_at: identity.created_at,
+            last_login_at: identity.last_login_at,
+        }
+    }
+}
+
+// 发起登录, 前端保存 state 后跳转到 authorization_url
+pub async fn authorize(State(oidc_service): State<Arc<dyn OidcService>>) -> AppResult<Response> {
+    let authorization = oidc_service.begin(None).await?;
+    Ok(success_response(
+        serde_json::to_value(OidcAuthorizationResponse::from(authorization)).unwrap(),
+    ))
+}
+
+// 开启两步验证的用户同样需要提交验证码
+pub async fn callback(
+    State(oidc_service): State<Arc<dyn OidcService>>,
+    State(auth_service): State<Arc<dyn AuthService>>,
+    State(account_service): State<Arc<dyn AccountService>>,
+    State(two_factor_service): State<Arc<dyn TwoFactorService>>,
+    client: ClientInfo,
+    playload: Json<OidcCallbackRequest>,
+) -> AppResult<Response> {
+    let info = SessionInfo::new(&client, playload.device_name.clone());
+    let ctx = AuditContext::new(&client, None);
+    let user = oidc_service.complete(playload.0, &ctx).await?;
+    finish_login(
+        auth_service.as_ref(),
+        account_service.as_ref(),
+        two_factor_service.as_ref(),
+        user,
+        info,
+    )
+    .await
+}
+
+pub async fn list_identities(
+    State(oidc_service): State<Arc<dyn OidcService>>,
+    auth_user: AuthUser,
+) -> AppResult<Response> {
+    let identities: Vec<IdentityResponse> = oidc_service
+        .list_identities(auth_user.user_id)
+        .await?
+        .into_iter()
+        .map(IdentityResponse::from)
+        .collect();
+    Ok(success_response(serde_json::to_value(identities).unwrap()))
+}
+
+// 为当前用户关联外部身份, 回调仍然通过 /api/auth/oidc/callback 完成
+pub async fn link_identity(
+    State(oidc_service): State<Arc<dyn OidcService>>,
+    auth_user: AuthUser,
+) -> AppResult<Response> {
+    let authorization = oidc_service.begin(Some(auth_user.user_id)).await?;
+    Ok(success_response(
+        serde_json::to_value(OidcAuthorizationResponse::from(authorization)).unwrap(),
+    ))
+}
+
+pub async fn unlink_identity(
+    State(oidc_service): State<Arc<dyn OidcService>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    path::Path(id): path::Path<i32>,
+) -> AppResult<Response> {
+    let ctx = AuditContext::new(&client, Some(auth_user.user_id));
+    oidc_service.unlink(auth_user.user_id, id, &ctx).await?;
+    Ok(success_response(serde_json::Value::Null))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        http::{Method, StatusCode},
+        Router,
+    };
+    use serde_json::{json, Value};
+
+    use crate::{
+        api::{
+            router::build_router,
+            state::AppState,
+            testing::{
+                register_and_login, send, test_config, test_router, test_router_with,
+                test_state_with_mailer, TEST_PASSWORD,
+            },
+        },
+        domain::{
+            entities::audit_event::{AuditEvent, AuditOutcome},
+            repository::audit::{AuditFilter, AuditQuery},
+        },
+        infastructure::oidc::mock::{MockProvider, MockUser},
+    };
+
+    async fn oidc_router() -> (Router, MockProvider) {
+        let provider = MockProvider::start().await;
+        let mut config = test_config();
+        config.oidc = Some(provider.config());
+        (test_router_with(config), provider)
+    }
+
+    fn mock_user(subject: &str, email: &str, email_verified: bool) -> MockUser {
+        MockUser {
+            subject: subject.to_string(),
+            email: Some(email.to_string()),
+            email_verified,
+            preferred_username: Some("oidc-user".to_string()),
+            ..MockUser::default()
+        }
+    }
+
+    // 走完授权码流程, 返回回调接口的响应
+    async fn login_via(
+        router: &Router,
+        provider: &MockProvider,
+        token: Option<&str>,
+        user: MockUser,
+    ) -> (StatusCode, Value) {
+        let uri = match token {
+            Some(_) => "/api/me/identities/oidc",
+            None => "/api/auth/oidc/authorize",
+        };
+        let (status, body) = send(router, Method::POST, uri, token, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (code, state) =
+            provider.authorize(body["data"]["authorization_url"].as_str().unwrap(), user);
+        assert_eq!(state, body["data"]["state"]);
+        send(
+            router,
+            Method::POST,
+            "/api/auth/oidc/callback",
+            None,
+            Some(json!({ "code": code, "state": state })),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_login_creates_user_and_reuses_identity() {
+        let (router, provider) = oidc_router().await;
+        let (status, body) = login_via(
+            &router,
+            &provider,
+            None,
+            mock_user("subject-1", "oidc@example.com", true),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let user = &body["data"]["user"];
+        assert_eq!(user["email"], "oidc@example.com");
+        assert_eq!(user["username"], "oidc-user");
+        assert!(!user["email_verified_at"].is_null());
+
+        // 第二次登录即使邮箱变化也按 subject 找到同一个用户
+        let (status, second) = login_via(
+            &router,
+            &provider,
+            None,
+            mock_user("subject-1", "renamed@example.com", true),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(second["data"]["user"]["id"], user["id"]);
+
+        let token = second["data"]["token"].as_str().unwrap();
+        let (status, body) = send(
+            &router,
+            Method::GET,
+            "/api/me/identities",
+            Some(token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let identities = body["data"].as_array().unwrap();
+        assert_eq!(identities.len(), 1);
+        assert_eq!(identities[0]["issuer"], provider.issuer());
+        assert_eq!(identities[0]["subject"], "subject-1");
+    }
+
+    #[tokio::test]
+    async fn test_links_existing_user_only_by_verified_email() {
+        let (router, provider) = oidc_router().await;
+        let token = register_and_login(&router, "local@example.com").await;
+        let (_, me) = send(&router, Method::GET, "/api/me", Some(&token), None).await;
+
+        let (status, _) = login_via(
+            &router,
+            &provider,
+            None,
+            mock_user("subject-1", "local@example.com", false),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let (status, body) = login_via(
+            &router,
+            &provider,
+            None,
+            mock_user("subject-1", "local@example.com", true),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["user"]["id"], me["data"]["id"]);
+    }
+
+    #[tokio::test]
+    async fn test_link_and_unlink_identity_from_account() {
+        let (router, provider) = oidc_router().await;
+        let token = register_and_login(&router, "owner@example.com").await;
+        let (_, me) = send(&router, Method::GET, "/api/me", Some(&token), None).await;
+
+        // 身份提供方中的邮箱与本地账户不同, 也没有验证
+        let (status, body) = login_via(
+            &router,
+            &provider,
+            Some(&token),
+            mock_user("subject-1", "someone-else@example.com", false),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["user"]["id"], me["data"]["id"]);
+
+        // 已关联的身份不能再关联到其他账户
+        let other = register_and_login(&router, "other@example.com").await;
+        let (status, _) = login_via(
+            &router,
+            &provider,
+            Some(&other),
+            mock_user("subject-1", "someone-else@example.com", false),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let (_, body) = send(
+            &router,
+            Method::GET,
+            "/api/me/identities",
+            Some(&token),
+            None,
+        )
+        .await;
+        let id = body["data"][0]["id"].as_i64().unwrap();
+        let uri = format!("/api/me/identities/{id}");
+        let (status, _) = send(&router, Method::DELETE, &uri, Some(&other), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(&router, Method::DELETE, &uri, Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, body) = send(
+            &router,
+            Method::GET,
+            "/api/me/identities",
+            Some(&token),
+            None,
+        )
+        .await;
+        assert!(body["data"].as_array().unwrap().is_empty());
+    }
+
+    async fn audit_events(state: &AppState, action: &str) -> Vec<AuditEvent> {
+        let query = AuditQuery {
+            filter: AuditFilter {
+                action: Some(action.to_string()),
+                ..AuditFilter::default()
+            },
+            page: 1,
+            page_size: 10,
+        };
+        state.audit_service.search(query).await.unwrap().items
+    }
+
+    #[tokio::test]
+    async fn test_identity_changes_are_audited() {
+        let provider = MockProvider::start().await;
+        let mut config = test_config();
+        config.oidc = Some(provider.config());
+        let (state, _) = test_state_with_mailer(config);
+        let router = build_router(state.clone());
+        let token = register_and_login(&router, "owner@example.com").await;
+        let (_, me) = send(&router, Method::GET, "/api/me", Some(&token), None).await;
+        let user_id = me["data"]["id"].as_i64().unwrap() as i32;
+
+        let (status, _) = login_via(
+            &router,
+            &provider,
+            Some(&token),
+            mock_user("subject-1", "idp@example.com", true),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let other = register_and_login(&router, "other@example.com").await;
+        let (status, _) = login_via(
+            &router,
+            &provider,
+            Some(&other),
+            mock_user("subject-1", "idp@example.com", true),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (_, body) = send(
+            &router,
+            Method::GET,
+            "/api/me/identities",
+            Some(&token),
+            None,
+        )
+        .await;
+        let id = body["data"][0]["id"].as_i64().unwrap();
+        let (status, _) = send(
+            &router,
+            Method::DELETE,
+            &format!("/api/me/identities/{id}"),
+            Some(&token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let target = Some(format!("identity:{id}"));
+        let links = audit_events(&state, "user.identity_link").await;
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].actor_id, Some(user_id));
+        assert_eq!(links[0].target, target);
+        assert_eq!(links[0].detail.as_deref(), Some(provider.issuer()));
+        let unlinks = audit_events(&state, "user.identity_unlink").await;
+        assert_eq!(unlinks.len(), 1);
+        assert_eq!(unlinks[0].actor_id, Some(user_id));
+        assert_eq!(unlinks[0].target, target);
+        let failures = audit_events(&state, "auth.login_oidc").await;
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].outcome, AuditOutcome::Failure);
+        assert!(failures[0].detail.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_identity_of_deleted_account_can_sign_up_again() {
+        let (router, provider) = oidc_router().await;
+        let token = register_and_login(&router, "gone@example.com").await;
+        let (status, _) = login_via(
+            &router,
+            &provider,
+            Some(&token),
+            mock_user("subject-1", "idp@example.com", true),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(
+            &router,
+            Method::DELETE,
+            "/api/me",
+            Some(&token),
+            Some(json!({ "password": TEST_PASSWORD })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        // 身份随账户一起删除, 再次登录按首次登录注册新账户
+        let (status, body) = login_via(
+            &router,
+            &provider,
+            None,
+            mock_user("subject-1", "idp@example.com", true),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["user"]["email"], "idp@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_callback_rejects_unknown_or_reused_state() {
+        let (router, provider) = oidc_router().await;
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/auth/oidc/callback",
+            None,
+            Some(json!({ "code": "code", "state": "unknown" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (_, body) = send(
+            &router,
+            Method::POST,
+            "/api/auth/oidc/authorize",
+            None,
+            None,
+        )
+        .await;
+        let (code, state) = provider.authorize(
+            body["data"]["authorization_url"].as_str().unwrap(),
+            mock_user("subject-1", "oidc@example.com", true),
+        );
+        let callback = json!({ "code": code, "state": state });
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/auth/oidc/callback",
+            None,
+            Some(callback.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/auth/oidc/callback",
+            None,
+            Some(callback),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_without_config() {
+        let router = test_router();
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/auth/oidc/authorize",
+            None,
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
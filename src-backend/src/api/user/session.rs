@@ -0,0 +1,265 @@
+use std::sync::Arc;
+
+use axum::extract::{path, State};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::{
+        middleware::{auth::AuthUser, client::ClientInfo},
+        request::{success_response, Response},
+    },
+    application::{audit::service::AuditContext, auth::service::AuthService},
+    domain::{
+        entities::session::Session,
+        error::{AppError, AppResult},
+    },
+};
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct SessionResponse {
+    pub id: i32,
+    pub device_name: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: String,
+    pub created_at: DateTime<Local>,
+    pub last_active_at: DateTime<Local>,
+    // 是否为发起本次请求的会话
+    pub current: bool,
+}
+
+impl SessionResponse {
+    fn new(session: Session, current_session_id: Option<i32>) -> Self {
+        Self {
+            current: current_session_id == Some(session.id),
+            id: session.id,
+            device_name: session.device_name,
+            user_agent: session.user_agent,
+            ip_address: session.ip_address,
+            created_at: session.created_at,
+            last_active_at: session.last_active_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct RevokeSessionsResponse {
+    pub revoked: u64,
+}
+
+pub async fn list_sessions(
+    State(auth_service): State<Arc<dyn AuthService>>,
+    auth_user: AuthUser,
+) -> AppResult<Response> {
+    let sessions: Vec<SessionResponse> = auth_service
+        .list_sessions(auth_user.user_id)
+        .await?
+        .into_iter()
+        .map(|session| SessionResponse::new(session, auth_user.session_id))
+        .collect();
+    Ok(success_response(serde_json::to_value(sessions).unwrap()))
+}
+
+// 吊销指定会话, 该会话的 access token 和刷新令牌立即失效
+pub async fn revoke_session(
+    State(auth_service): State<Arc<dyn AuthService>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    path::Path(id): path::Path<i32>,
+) -> AppResult<Response> {
+    let ctx = AuditContext::new(&client, Some(auth_user.user_id));
+    auth_service
+        .revoke_session(auth_user.user_id, id, &ctx)
+        .await?;
+    Ok(success_response(serde_json::Value::Null))
+}
+
+// 在其他设备上退出登录, 保留当前会话
+pub async fn revoke_other_sessions(
+    State(auth_service): State<Arc<dyn AuthService>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+) -> AppResult<Response> {
+    let current = auth_user
+        .session_id
+        .ok_or_else(|| AppError::Forbidden("API tokens cannot access this endpoint".to_string()))?;
+    let revoked = auth_service
+        .revoke_other_sessions(
+            auth_user.user_id,
+            current,
+            &AuditContext::new(&client, Some(auth_user.user_id)),
+        )
+        .await?;
+    Ok(success_response(
+        serde_json::to_value(RevokeSessionsResponse { revoked }).unwrap(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        Router,
+    };
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    use crate::api::testing::{register_and_login, send, test_router, TEST_PASSWORD};
+
+    // 带 User-Agent 和设备名称登录, 返回令牌
+    async fn login_from(router: &Router, email: &str, device_name: &str, agent: &str) -> Value {
+        let body = json!({ "email": email, "password": TEST_PASSWORD, "device_name": device_name });
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/api/auth/login")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::USER_AGENT, agent)
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice::<Value>(&bytes).unwrap()["data"].clone()
+    }
+
+    async fn sessions(router: &Router, token: &str) -> Vec<Value> {
+        let (status, body) = send(router, Method::GET, "/api/me/sessions", Some(token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        body["data"].as_array().unwrap().clone()
+    }
+
+    async fn todo_status(router: &Router, token: &Value) -> StatusCode {
+        send(router, Method::GET, "/api/todo", token.as_str(), None)
+            .await
+            .0
+    }
+
+    #[tokio::test]
+    async fn test_list_sessions_marks_current() {
+        let router = test_router();
+        let first = register_and_login(&router, "sessions@example.com").await;
+        let second = login_from(&router, "sessions@example.com", " Work laptop ", "Firefox").await;
+
+        let list = sessions(&router, second["token"].as_str().unwrap()).await;
+        assert_eq!(list.len(), 2);
+        let current: Vec<&Value> = list.iter().filter(|s| s["current"] == true).collect();
+        assert_eq!(current.len(), 1);
+        assert_eq!(current[0]["device_name"], "Work laptop");
+        assert_eq!(current[0]["user_agent"], "Firefox");
+        assert!(current[0]["ip_address"].is_string());
+
+        // 刷新令牌沿用原来的会话
+        let (status, body) = send(
+            &router,
+            Method::POST,
+            "/api/auth/refresh",
+            None,
+            Some(json!({ "refresh_token": second["refresh_token"] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let list = sessions(&router, body["data"]["token"].as_str().unwrap()).await;
+        assert_eq!(list.len(), 2);
+        assert!(list
+            .iter()
+            .any(|s| s["current"] == true && s["id"] == current[0]["id"]));
+
+        let list = sessions(&router, &first).await;
+        assert!(list
+            .iter()
+            .any(|s| s["current"] == true && s["device_name"].is_null()));
+    }
+
+    #[tokio::test]
+    async fn test_revoke_session_invalidates_its_tokens() {
+        let router = test_router();
+        let first = register_and_login(&router, "revoke@example.com").await;
+        let second = login_from(&router, "revoke@example.com", "Phone", "Safari").await;
+        let other = register_and_login(&router, "other@example.com").await;
+
+        let id = sessions(&router, &first)
+            .await
+            .into_iter()
+            .find(|s| s["current"] == false)
+            .unwrap()["id"]
+            .as_i64()
+            .unwrap();
+        let uri = format!("/api/me/sessions/{id}");
+        let (status, _) = send(&router, Method::DELETE, &uri, Some(&other), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(todo_status(&router, &second["token"]).await, StatusCode::OK);
+
+        let (status, _) = send(&router, Method::DELETE, &uri, Some(&first), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&router, Method::DELETE, &uri, Some(&first), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // access token 尚未过期也立即失效
+        assert_eq!(
+            todo_status(&router, &second["token"]).await,
+            StatusCode::UNAUTHORIZED
+        );
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/auth/refresh",
+            None,
+            Some(json!({ "refresh_token": second["refresh_token"] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(sessions(&router, &first).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_revoke_other_sessions_keeps_current() {
+        let router = test_router();
+        let current = register_and_login(&router, "others@example.com").await;
+        let phone = login_from(&router, "others@example.com", "Phone", "Safari").await;
+        let tablet = login_from(&router, "others@example.com", "Tablet", "Chrome").await;
+
+        let (status, body) = send(
+            &router,
+            Method::POST,
+            "/api/me/sessions/revoke-others",
+            Some(&current),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["revoked"], 2);
+        for tokens in [&phone, &tablet] {
+            assert_eq!(
+                todo_status(&router, &tokens["token"]).await,
+                StatusCode::UNAUTHORIZED
+            );
+        }
+        let list = sessions(&router, &current).await;
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0]["current"], true);
+    }
+
+    #[tokio::test]
+    async fn test_logout_ends_session() {
+        let router = test_router();
+        register_and_login(&router, "bye@example.com").await;
+        let tokens = login_from(&router, "bye@example.com", "Laptop", "Firefox").await;
+
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/auth/logout",
+            None,
+            Some(json!({ "refresh_token": tokens["refresh_token"] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            todo_status(&router, &tokens["token"]).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{path, State},
+    Json,
+};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::{
+        middleware::{auth::AuthUser, client::ClientInfo},
+        request::{success_response, Response},
+    },
+    application::{api_token::service::ApiTokenService, audit::service::AuditContext},
+    domain::{
+        entities::api_token::{ApiScope, ApiToken},
+        error::AppResult,
+    },
+};
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct CreateApiTokenRequest {
+    pub name: String,
+    pub scopes: Vec<ApiScope>,
+    // 为空时永不过期
+    pub expires_in_days: Option<u32>,
+}
+
+// 令牌列表中不包含明文和哈希
+#[derive(Deserialize, Serialize, Clone)]
+pub struct ApiTokenResponse {
+    pub id: i32,
+    pub name: String,
+    pub token_prefix: String,
+    pub scopes: Vec<ApiScope>,
+    pub created_at: DateTime<Local>,
+    pub last_used_at: Option<DateTime<Local>>,
+    pub expires_at: Option<DateTime<Local>>,
+}
+
+impl From<ApiToken> for ApiTokenResponse {
+    fn from(token: ApiToken) -> Self {
+        Self {
+            scopes: token.scopes(),
+            id: token.id,
+            name: token.name,
+            token_prefix: token.token_prefix,
+            created_at: token.created_at,
+            last_used_at: token.last_used_at,
+            expires_at: token.expires_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct CreatedApiTokenResponse {
+    #[serde(flatten)]
+    pub token: ApiTokenResponse,
+    // 令牌明文只在创建时返回一次
+    pub secret: String,
+}
+
+pub async fn list_tokens(
+    State(api_token_service): State<Arc<dyn ApiTokenService>>,
+    auth_user: AuthUser,
+) -> AppResult<Response> {
+    let tokens: Vec<ApiTokenResponse> = api_token_service
+        .list(auth_user.user_id)
+        .await?
+        .into_iter()
+        .map(ApiTokenResponse::from)
+        .collect();
+    Ok(success_response(serde_json::to_value(tokens).unwrap()))
+}
+
+pub async fn create_token(
+    State(api_token_service): State<Arc<dyn ApiTokenService>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    playload: Json<CreateApiTokenRequest>,
+) -> AppResult<Response> {
+    let ctx = AuditContext::new(&client, Some(auth_user.user_id));
+    let created = api_token_service
+        .create(auth_user.user_id, playload.0, &ctx)
+        .await?;
+    Ok(success_response(
+        serde_json::to_value(CreatedApiTokenResponse {
+            token: created.token.into(),
+            secret: created.secret,
+        })
+        .unwrap(),
+    ))
+}
+
+pub async fn revoke_token(
+    State(api_token_service): State<Arc<dyn ApiTokenService>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    path::Path(id): path::Path<i32>,
+) -> AppResult<Response> {
+    let ctx = AuditContext::new(&client, Some(auth_user.user_id));
+    api_token_service
+        .revoke(auth_user.user_id, id, &ctx)
+        .await?;
+    Ok(success_response(serde_json::Value::Null))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        http::{Method, StatusCode},
+        Router,
+    };
+    use serde_json::{json, Value};
+
+    use crate::api::testing::{register_and_login, send, test_router};
+
+    async fn create(router: &Router, token: &str, body: Value) -> (StatusCode, Value) {
+        send(
+            router,
+            Method::POST,
+            "/api/me/tokens",
+            Some(token),
+            Some(body),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_api_token_lifecycle_and_scopes() {
+        let router = test_router();
+        let session = register_and_login(&router, "tokens@example.com").await;
+
+        let (status, body) = create(
+            &router,
+            &session,
+            json!({ "name": " ci ", "scopes": ["todo:read"] }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["name"], "ci");
+        assert!(body["data"]["expires_at"].is_null());
+        let read_only = body["data"]["secret"].as_str().unwrap().to_string();
+        assert!(read_only.starts_with(body["data"]["token_prefix"].as_str().unwrap()));
+
+        let (status, body) = create(
+            &router,
+            &session,
+            json!({ "name": "deploy", "scopes": ["todo:write"], "expires_in_days": 30 }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(!body["data"]["expires_at"].is_null());
+        let writer = body["data"]["secret"].as_str().unwrap().to_string();
+        let writer_id = body["data"]["id"].as_i64().unwrap();
+
+        // 只读令牌可以读取但不能写入
+        let (status, _) = send(&router, Method::GET, "/api/todo", Some(&read_only), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let todo = json!({ "title": "From CI", "description": "" });
+        let (status, body) = send(
+            &router,
+            Method::POST,
+            "/api/todo",
+            Some(&read_only),
+            Some(todo.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(body["message"].as_str().unwrap().contains("todo:write"));
+
+        // todo:write 同时包含读取权限
+        let (status, _) = send(
+            &router,
+            Method::POST,
+            "/api/todo",
+            Some(&writer),
+            Some(todo),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, body) = send(&router, Method::GET, "/api/todo", Some(&writer), None).await;
+        assert_eq!(body["data"]["total"], 1);
+
+        // 账户管理接口不接受 API 令牌
+        for uri in ["/api/me", "/api/me/tokens"] {
+            let (status, _) = send(&router, Method::GET, uri, Some(&writer), None).await;
+            assert_eq!(status, StatusCode::FORBIDDEN);
+        }
+
+        let (status, body) =
+            send(&router, Method::GET, "/api/me/tokens", Some(&session), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let tokens = body["data"].as_array().unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert!(tokens.iter().all(|token| token.get("secret").is_none()));
+        let used = tokens
+            .iter()
+            .find(|token| token["id"] == writer_id)
+            .unwrap();
+        assert!(!used["last_used_at"].is_null());
+        assert_eq!(used["scopes"], json!(["todo:write"]));
+
+        let uri = format!("/api/me/tokens/{writer_id}");
+        let (status, _) = send(&router, Method::DELETE, &uri, Some(&session), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&router, Method::DELETE, &uri, Some(&session), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(&router, Method::GET, "/api/todo", Some(&writer), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_api_tokens_are_scoped_to_their_owner() {
+        let router = test_router();
+        let owner = register_and_login(&router, "owner@example.com").await;
+        let other = register_and_login(&router, "other@example.com").await;
+        let (_, body) = create(
+            &router,
+            &owner,
+            json!({ "name": "ci", "scopes": ["todo:read"] }),
+        )
+        .await;
+        let id = body["data"]["id"].as_i64().unwrap();
+
+        let uri = format!("/api/me/tokens/{id}");
+        let (status, _) = send(&router, Method::DELETE, &uri, Some(&other), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (_, body) = send(&router, Method::GET, "/api/me/tokens", Some(&other), None).await;
+        assert!(body["data"].as_array().unwrap().is_empty());
+
+        let (status, _) = send(
+            &router,
+            Method::GET,
+            "/api/todo",
+            Some("mth_0000000000000000"),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_create_api_token_validation() {
+        let router = test_router();
+        let session = register_and_login(&router, "invalid@example.com").await;
+        for body in [
+            json!({ "name": "", "scopes": ["todo:read"] }),
+            json!({ "name": "ci", "scopes": [] }),
+            json!({ "name": "ci", "scopes": ["admin"] }),
+            json!({ "name": "ci", "scopes": ["todo:read"], "expires_in_days": 0 }),
+            json!({ "name": "ci", "scopes": ["todo:read"], "expires_in_days": 366 }),
+        ] {
+            let (status, _) = create(&router, &session, body).await;
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    }
+}
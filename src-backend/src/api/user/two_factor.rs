@@ -0,0 +1,376 @@
+use std::sync::Arc;
+
+use axum::{extract::State, Json};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::{
+        middleware::{auth::AuthUser, client::ClientInfo},
+        request::{success_response, Response},
+        user::api::LoginResponse,
+    },
+    application::{
+        account::service::AccountService,
+        audit::service::AuditContext,
+        auth::service::{AuthService, SessionInfo},
+        two_factor::service::TwoFactorService,
+    },
+    domain::{entities::user::User, error::AppResult},
+};
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct TotpCodeRequest {
+    pub code: String,
+}
+
+// 关闭两步验证或重新生成恢复码前重新认证, code 可以是 TOTP 或恢复码
+#[derive(Deserialize, Serialize, Clone)]
+pub struct ReauthenticateRequest {
+    pub password: String,
+    pub code: String,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct TwoFactorLoginRequest {
+    pub challenge_token: String,
+    pub code: String,
+    #[serde(default)]
+    pub device_name: Option<String>,
+}
+
+// 密码校验通过但还需要两步验证时, 登录接口返回该结构而不是令牌
+#[derive(Deserialize, Serialize, Clone)]
+pub struct TwoFactorChallengeResponse {
+    pub two_factor_required: bool,
+    pub challenge_token: String,
+    pub expires_at: DateTime<Local>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct TwoFactorStatusResponse {
+    pub enabled: bool,
+    pub recovery_codes_remaining: i64,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct TotpEnrollmentResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+// 所有登录方式的最后一步: 开启两步验证的用户先返回 challenge, 否则直接签发令牌
+pub async fn finish_login(
+    auth_service: &dyn AuthService,
+    account_service: &dyn AccountService,
+    two_factor_service: &dyn TwoFactorService,
+    user: User,
+    info: SessionInfo,
+) -> AppResult<Response> {
+    if two_factor_service.is_enabled(user.id).await? {
+        let challenge = account_service.issue_login_challenge(user.id).await?;
+        let res = TwoFactorChallengeResponse {
+            two_factor_required: true,
+            challenge_token: challenge.token,
+            expires_at: challenge.expires_at,
+        };
+        return Ok(success_response(serde_json::to_value(res).unwrap()));
+    }
+    let res = LoginResponse {
+        tokens: auth_service.issue(user.id, info).await?,
+        user: user.into(),
+    };
+    Ok(success_response(serde_json::to_value(res).unwrap()))
+}
+
+pub async fn login_two_factor(
+    State(two_factor_service): State<Arc<dyn TwoFactorService>>,
+    State(auth_service): State<Arc<dyn AuthService>>,
+    client: ClientInfo,
+    playload: Json<TwoFactorLoginRequest>,
+) -> AppResult<Response> {
+    let info = SessionInfo::new(&client, playload.device_name.clone());
+    let user = two_factor_service
+        .verify_login(playload.0, &AuditContext::new(&client, None))
+        .await?;
+    let res = LoginResponse {
+        tokens: auth_service.issue(user.id, info).await?,
+        user: user.into(),
+    };
+    Ok(success_response(serde_json::to_value(res).unwrap()))
+}
+
+pub async fn get_two_factor(
+    State(two_factor_service): State<Arc<dyn TwoFactorService>>,
+    auth_user: AuthUser,
+) -> AppResult<Response> {
+    let status = two_factor_service.status(auth_user.user_id).await?;
+    Ok(success_response(
+        serde_json::to_value(TwoFactorStatusResponse {
+            enabled: status.enabled,
+            recovery_codes_remaining: status.recovery_codes_remaining,
+        })
+        .unwrap(),
+    ))
+}
+
+pub async fn enroll_totp(
+    State(two_factor_service): State<Arc<dyn TwoFactorService>>,
+    auth_user: AuthUser,
+) -> AppResult<Response> {
+    let enrollment = two_factor_service
+        .begin_enrollment(auth_user.user_id)
+        .await?;
+    Ok(success_response(
+        serde_json::to_value(TotpEnrollmentResponse {
+            secret: enrollment.secret,
+            otpauth_uri: enrollment.otpauth_uri,
+        })
+        .unwrap(),
+    ))
+}
+
+pub async fn confirm_totp(
+    State(two_factor_service): State<Arc<dyn TwoFactorService>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    playload: Json<TotpCodeRequest>,
+) -> AppResult<Response> {
+    let ctx = AuditContext::new(&client, Some(auth_user.user_id));
+    let recovery_codes = two_factor_service
+        .confirm_enrollment(auth_user.user_id, &playload.code, &ctx)
+        .await?;
+    Ok(success_response(
+        serde_json::to_value(RecoveryCodesResponse { recovery_codes }).unwrap(),
+    ))
+}
+
+pub async fn disable_totp(
+    State(two_factor_service): State<Arc<dyn TwoFactorService>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    playload: Json<ReauthenticateRequest>,
+) -> AppResult<Response> {
+    let ctx = AuditContext::new(&client, Some(auth_user.user_id));
+    two_factor_service
+        .disable(auth_user.user_id, playload.0, &ctx)
+        .await?;
+    Ok(success_response(serde_json::Value::Null))
+}
+
+pub async fn regenerate_recovery_codes(
+    State(two_factor_service): State<Arc<dyn TwoFactorService>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    playload: Json<ReauthenticateRequest>,
+) -> AppResult<Response> {
+    let ctx = AuditContext::new(&client, Some(auth_user.user_id));
+    let recovery_codes = two_factor_service
+        .regenerate_recovery_codes(auth_user.user_id, playload.0, &ctx)
+        .await?;
+    Ok(success_response(
+        serde_json::to_value(RecoveryCodesResponse { recovery_codes }).unwrap(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        http::{Method, StatusCode},
+        Router,
+    };
+    use serde_json::{json, Value};
+
+    use crate::{
+        api::testing::{register_and_login, send, test_router, TEST_PASSWORD},
+        utils::totp,
+    };
+
+    // 当前时间步之后的第 offset 个验证码, 仍在允许的时钟偏差内
+    fn code(secret: &str, offset: i64) -> String {
+        totp::code_at(
+            secret,
+            totp::step_at(chrono::Local::now().timestamp()) + offset,
+        )
+        .unwrap()
+    }
+
+    // 开启两步验证, 返回密钥和恢复码
+    async fn enable(router: &Router, token: &str) -> (String, Vec<String>) {
+        let (status, body) =
+            send(router, Method::POST, "/api/me/2fa/totp", Some(token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let secret = body["data"]["secret"].as_str().unwrap().to_string();
+        assert!(body["data"]["otpauth_uri"]
+            .as_str()
+            .unwrap()
+            .starts_with("otpauth://totp/Mithril:"));
+
+        let (status, _) = send(
+            router,
+            Method::POST,
+            "/api/me/2fa/totp/confirm",
+            Some(token),
+            Some(json!({ "code": "000000x" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (status, body) = send(
+            router,
+            Method::POST,
+            "/api/me/2fa/totp/confirm",
+            Some(token),
+            Some(json!({ "code": code(&secret, 0) })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let recovery_codes = body["data"]["recovery_codes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|code| code.as_str().unwrap().to_string())
+            .collect();
+        (secret, recovery_codes)
+    }
+
+    async fn password_login(router: &Router, email: &str) -> Value {
+        let (status, body) = send(
+            router,
+            Method::POST,
+            "/api/auth/login",
+            None,
+            Some(json!({ "email": email, "password": TEST_PASSWORD })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        body["data"].clone()
+    }
+
+    async fn second_step(router: &Router, challenge: &Value, code: &str) -> (StatusCode, Value) {
+        send(
+            router,
+            Method::POST,
+            "/api/auth/login/2fa",
+            None,
+            Some(json!({ "challenge_token": challenge["challenge_token"], "code": code })),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_two_step_login_with_totp() {
+        let router = test_router();
+        let token = register_and_login(&router, "totp@example.com").await;
+        let (secret, recovery_codes) = enable(&router, &token).await;
+        assert_eq!(recovery_codes.len(), 10);
+
+        let (_, body) = send(&router, Method::GET, "/api/me/2fa", Some(&token), None).await;
+        assert_eq!(body["data"]["enabled"], true);
+        assert_eq!(body["data"]["recovery_codes_remaining"], 10);
+
+        let challenge = password_login(&router, "totp@example.com").await;
+        assert_eq!(challenge["two_factor_required"], true);
+        assert!(challenge["token"].is_null());
+
+        let (status, _) = second_step(&router, &challenge, "123456x").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let totp_code = code(&secret, 1);
+        let (status, body) = second_step(&router, &challenge, &totp_code).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["data"]["token"].is_string());
+        assert_eq!(body["data"]["user"]["email"], "totp@example.com");
+
+        // challenge 只能使用一次
+        let (status, _) = second_step(&router, &challenge, &recovery_codes[0]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        // 已使用过的验证码不能再次使用
+        let challenge = password_login(&router, "totp@example.com").await;
+        let (status, _) = second_step(&router, &challenge, &totp_code).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_recovery_codes_are_single_use() {
+        let router = test_router();
+        let token = register_and_login(&router, "recovery@example.com").await;
+        let (_, recovery_codes) = enable(&router, &token).await;
+
+        let challenge = password_login(&router, "recovery@example.com").await;
+        let (status, _) = second_step(&router, &challenge, &recovery_codes[0].to_uppercase()).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let challenge = password_login(&router, "recovery@example.com").await;
+        let (status, _) = second_step(&router, &challenge, &recovery_codes[0]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (_, body) = send(&router, Method::GET, "/api/me/2fa", Some(&token), None).await;
+        assert_eq!(body["data"]["recovery_codes_remaining"], 9);
+    }
+
+    #[tokio::test]
+    async fn test_wrong_codes_lock_the_account() {
+        let router = test_router();
+        let token = register_and_login(&router, "brute@example.com").await;
+        let (secret, _) = enable(&router, &token).await;
+
+        let challenge = password_login(&router, "brute@example.com").await;
+        for _ in 0..5 {
+            let (status, _) = second_step(&router, &challenge, "aaaaa-aaaaa").await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+        }
+        let (status, _) = second_step(&router, &challenge, &code(&secret, 1)).await;
+        assert_eq!(status, StatusCode::LOCKED);
+    }
+
+    #[tokio::test]
+    async fn test_disable_requires_reauthentication() {
+        let router = test_router();
+        let token = register_and_login(&router, "disable@example.com").await;
+        let (secret, recovery_codes) = enable(&router, &token).await;
+
+        let (status, _) = send(
+            &router,
+            Method::DELETE,
+            "/api/me/2fa/totp",
+            Some(&token),
+            Some(json!({ "password": "wrong-password", "code": code(&secret, 1) })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (status, body) = send(
+            &router,
+            Method::POST,
+            "/api/me/2fa/recovery-codes",
+            Some(&token),
+            Some(json!({ "password": TEST_PASSWORD, "code": recovery_codes[0] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let fresh = body["data"]["recovery_codes"][0].as_str().unwrap();
+        assert!(!recovery_codes.iter().any(|code| code == fresh));
+
+        let (status, _) = send(
+            &router,
+            Method::DELETE,
+            "/api/me/2fa/totp",
+            Some(&token),
+            Some(json!({ "password": TEST_PASSWORD, "code": fresh })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let body = password_login(&router, "disable@example.com").await;
+        assert!(body["token"].is_string());
+        let (_, body) = send(&router, Method::GET, "/api/me/2fa", Some(&token), None).await;
+        assert_eq!(body["data"]["enabled"], false);
+    }
+}
@@ -0,0 +1,397 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Local};
+
+use crate::{
+    api::user::api::ResetPasswordRequest,
+    application::{
+        audit::service::{user_target, AuditAction, AuditContext, AuditEntry, AuditService},
+        auth::service::AuthService,
+        user::service::UserService,
+    },
+    domain::{
+        entities::{
+            user::User,
+            user_token::{TokenPurpose, UserToken},
+        },
+        error::{AppError, AppResult},
+        repository::user_token::UserTokenRepository,
+    },
+    infastructure::mail::{Email, Mailer},
+    utils::encryption::token::{generate_token, hash_token},
+};
+
+// 邮箱验证和密码找回, 令牌通过邮件发送, 一次性且有有效期.
+// 两步登录的 challenge 也使用同一张一次性令牌表
+#[async_trait::async_trait]
+pub trait AccountService: Send + Sync {
+    async fn send_email_verification(&self, user: &User) -> AppResult<()>;
+    // 邮箱不存在或已验证时静默返回, 避免暴露账号是否存在
+    async fn request_email_verification(&self, email: &str) -> AppResult<()>;
+    async fn verify_email(&self, token: &str, ctx: &AuditContext) -> AppResult<User>;
+    // 邮箱不存在时同样返回成功, 邮件在后台发送
+    async fn request_password_reset(&self, email: &str) -> AppResult<()>;
+    // 管理员强制重置: 当前密码立即失效, 吊销所有刷新令牌并发送重置邮件
+    async fn force_password_reset(&self, user: &User, ctx: &AuditContext) -> AppResult<()>;
+    // 重置成功后吊销该用户所有的刷新令牌
+    async fn reset_password(&self, req: ResetPasswordRequest, ctx: &AuditContext) -> AppResult<()>;
+    // 同一用户只保留最新的 challenge
+    async fn issue_login_challenge(&self, user_id: i32) -> AppResult<LoginChallenge>;
+    // 只校验不消耗, 验证码错误时用户可以重试
+    async fn check_login_challenge(&self, token: &str) -> AppResult<i32>;
+    async fn consume_login_challenge(&self, token: &str) -> AppResult<i32>;
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64>;
+}
+
+#[derive(Debug, Clone)]
+pub struct LoginChallenge {
+    pub token: String,
+    pub expires_at: DateTime<Local>,
+}
+
+fn invalid_challenge() -> AppError {
+    AppError::Unauthorized("Invalid or expired login challenge".to_string())
+}
+
+fn invalid_token() -> AppError {
+    AppError::Validation("Invalid or expired token".to_string())
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountSettings {
+    pub email_verification_ttl: chrono::Duration,
+    pub password_reset_ttl: chrono::Duration,
+    pub login_challenge_ttl: chrono::Duration,
+    // 邮件中链接的前缀, 如 https://todo.example.com
+    pub link_base_url: String,
+}
+
+pub struct AccountServiceImpl<T> {
+    // 后台发送邮件的任务同样需要签发令牌
+    user_token_repository: Arc<T>,
+    user_service: Arc<dyn UserService>,
+    auth_service: Arc<dyn AuthService>,
+    audit_service: Arc<dyn AuditService>,
+    mailer: Arc<dyn Mailer>,
+    settings: AccountSettings,
+}
+
+impl AccountSettings {
+    fn password_reset_email(&self, user: &User, token: &str, intro: &str, outro: &str) -> Email {
+        Email {
+            to: user.email.clone(),
+            subject: "Reset your password".to_string(),
+            body: format!(
+                "Hi {},\n\n{intro}\n\n{}/reset-password?token={token}\n\nThe link expires in {} minutes.{outro}\n",
+                user.username,
+                self.link_base_url,
+                self.password_reset_ttl.num_minutes()
+            ),
+        }
+    }
+
+    fn verification_email(&self, user: &User, token: &str) -> Email {
+        Email {
+            to: user.email.clone(),
+            subject: "Verify your email address".to_string(),
+            body: format!(
+                "Hi {},\n\nConfirm your email address by opening the link below:\n\n{}/verify-email?token={token}\n\nThe link expires in {} hours.\n",
+                user.username,
+                self.link_base_url,
+                self.email_verification_ttl.num_hours()
+            ),
+        }
+    }
+}
+
+// 签发新令牌前使同一用途下的旧令牌失效, 同一时间只有最后一封邮件中的链接可用
+async fn issue_token<T: UserTokenRepository>(
+    repository: &T,
+    user_id: i32,
+    purpose: TokenPurpose,
+    ttl: chrono::Duration,
+) -> AppResult<String> {
+    let now = Local::now();
+    repository
+        .invalidate_by_user_id(user_id, purpose, now)
+        .await?;
+    let token = generate_token();
+    repository
+        .create(&UserToken::new(
+            user_id,
+            purpose,
+            hash_token(&token),
+            now,
+            now + ttl,
+        ))
+        .await?;
+    Ok(token)
+}
+
+impl<T: UserTokenRepository + 'static> AccountServiceImpl<T> {
+    pub fn new(
+        user_token_repository: T,
+        user_service: Arc<dyn UserService>,
+        auth_service: Arc<dyn AuthService>,
+        audit_service: Arc<dyn AuditService>,
+        mailer: Arc<dyn Mailer>,
+        settings: AccountSettings,
+    ) -> Self {
+        Self {
+            user_token_repository: Arc::new(user_token_repository),
+            user_service,
+            auth_service,
+            audit_service,
+            mailer,
+            settings,
+        }
+    }
+
+    async fn issue(
+        &self,
+        user_id: i32,
+        purpose: TokenPurpose,
+        ttl: chrono::Duration,
+    ) -> AppResult<String> {
+        issue_token(self.user_token_repository.as_ref(), user_id, purpose, ttl).await
+    }
+
+    // 返回未使用且未过期的令牌
+    async fn find_valid(&self, token: &str, purpose: TokenPurpose) -> AppResult<Option<UserToken>> {
+        let now = Local::now();
+        Ok(self
+            .user_token_repository
+            .get_by_hash(&hash_token(token))
+            .await?
+            .filter(|token| {
+                token.purpose == purpose.as_str()
+                    && token.used_at.is_none()
+                    && token.expires_at > now
+            }))
+    }
+
+    async fn consume(&self, token: &str, purpose: TokenPurpose) -> AppResult<UserToken> {
+        let token = self
+            .find_valid(token, purpose)
+            .await?
+            .ok_or_else(invalid_token)?;
+        if !self
+            .user_token_repository
+            .mark_used(token.id, Local::now())
+            .await?
+        {
+            return Err(invalid_token());
+        }
+        Ok(token)
+    }
+
+    async fn send(&self, email: Email) -> AppResult<()> {
+        self.mailer
+            .send(email)
+            .await
+            .map_err(|err| AppError::Internal(format!("failed to send email: {err}")))
+    }
+
+    // 未登录即可触发的邮件: 签发令牌和发送都在后台进行, 已注册的邮箱不会因为
+    // 数据库写入和邮件服务的耗时而响应更慢, 避免通过状态码和响应时间判断账号是否存在
+    fn mail_in_background<F>(
+        &self,
+        user: User,
+        purpose: TokenPurpose,
+        ttl: chrono::Duration,
+        build: F,
+    ) where
+        F: FnOnce(&AccountSettings, &User, &str) -> Email + Send + 'static,
+    {
+        let repository = self.user_token_repository.clone();
+        let settings = self.settings.clone();
+        let mailer = self.mailer.clone();
+        tokio::spawn(async move {
+            let token = match issue_token(repository.as_ref(), user.id, purpose, ttl).await {
+                Ok(token) => token,
+                Err(err) => {
+                    log::warn!("failed to issue {} token: {err}", purpose.as_str());
+                    return;
+                }
+            };
+            if let Err(err) = mailer.send(build(&settings, &user, &token)).await {
+                log::warn!("failed to send email: {err}");
+            }
+        });
+    }
+
+    async fn find_user(&self, email: &str) -> AppResult<Option<User>> {
+        match self.user_service.get_user_by_email(email.to_string()).await {
+            Ok(user) => Ok(Some(user)),
+            Err(AppError::NotFound(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: UserTokenRepository + 'static> AccountService for AccountServiceImpl<T> {
+    async fn send_email_verification(&self, user: &User) -> AppResult<()> {
+        let token = self
+            .issue(
+                user.id,
+                TokenPurpose::EmailVerification,
+                self.settings.email_verification_ttl,
+            )
+            .await?;
+        self.send(self.settings.verification_email(user, &token))
+            .await
+    }
+
+    async fn request_email_verification(&self, email: &str) -> AppResult<()> {
+        match self.find_user(email).await? {
+            Some(user) if user.email_verified_at.is_none() => {
+                self.mail_in_background(
+                    user,
+                    TokenPurpose::EmailVerification,
+                    self.settings.email_verification_ttl,
+                    |settings, user, token| settings.verification_email(user, token),
+                );
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn verify_email(&self, token: &str, ctx: &AuditContext) -> AppResult<User> {
+        let token = self.consume(token, TokenPurpose::EmailVerification).await?;
+        let mut user = self
+            .user_service
+            .get_user_by_id(token.user_id)
+            .await
+            .map_err(|_| invalid_token())?;
+        if user.email_verified_at.is_none() {
+            user.email_verified_at = Some(Local::now());
+            user.updated_at = Local::now();
+            self.user_service.update_user(user.clone()).await?;
+            self.audit_service
+                .record(
+                    ctx,
+                    AuditEntry::success(AuditAction::EmailVerify)
+                        .actor(user.id)
+                        .target(user_target(user.id)),
+                )
+                .await;
+        }
+        Ok(user)
+    }
+
+    async fn request_password_reset(&self, email: &str) -> AppResult<()> {
+        let Some(user) = self.find_user(email).await? else {
+            return Ok(());
+        };
+        self.mail_in_background(
+            user,
+            TokenPurpose::PasswordReset,
+            self.settings.password_reset_ttl,
+            |settings, user, token| {
+                settings.password_reset_email(
+                    user,
+                    token,
+                    "Someone requested a password reset for your account. If it was you, open the link below:",
+                    " If you did not request it, you can ignore this email.",
+                )
+            },
+        );
+        Ok(())
+    }
+
+    async fn force_password_reset(&self, user: &User, ctx: &AuditContext) -> AppResult<()> {
+        self.user_service.invalidate_password(user.id).await?;
+        self.auth_service.logout_all(user.id, ctx).await?;
+        self.audit_service
+            .record(
+                ctx,
+                AuditEntry::success(AuditAction::AdminPasswordReset).target(user_target(user.id)),
+            )
+            .await;
+        let token = self
+            .issue(
+                user.id,
+                TokenPurpose::PasswordReset,
+                self.settings.password_reset_ttl,
+            )
+            .await?;
+        self.send(self.settings.password_reset_email(
+            user,
+            &token,
+            "An administrator has reset your password. Choose a new one by opening the link below:",
+            "",
+        ))
+        .await
+    }
+
+    async fn reset_password(&self, req: ResetPasswordRequest, ctx: &AuditContext) -> AppResult<()> {
+        if req.password != req.password_confirmation {
+            return Err(AppError::Validation("Passwords do not match".to_string()));
+        }
+        // 先校验密码再消耗令牌, 密码不符合策略时令牌仍然可用
+        self.user_service
+            .check_password_policy(&req.password)
+            .await?;
+        let token = self
+            .consume(&req.token, TokenPurpose::PasswordReset)
+            .await?;
+        self.user_service
+            .set_password(token.user_id, &req.password)
+            .await
+            .map_err(|err| match err {
+                AppError::NotFound(_) => invalid_token(),
+                err => err,
+            })?;
+        self.user_token_repository
+            .invalidate_by_user_id(token.user_id, TokenPurpose::PasswordReset, Local::now())
+            .await?;
+        // 持有重置令牌即视为该用户本人
+        let ctx = AuditContext {
+            actor_id: Some(token.user_id),
+            ..ctx.clone()
+        };
+        self.audit_service
+            .record(
+                &ctx,
+                AuditEntry::success(AuditAction::PasswordReset).target(user_target(token.user_id)),
+            )
+            .await;
+        self.auth_service.logout_all(token.user_id, &ctx).await
+    }
+
+    async fn issue_login_challenge(&self, user_id: i32) -> AppResult<LoginChallenge> {
+        let ttl = self.settings.login_challenge_ttl;
+        let token = self
+            .issue(user_id, TokenPurpose::LoginChallenge, ttl)
+            .await?;
+        Ok(LoginChallenge {
+            token,
+            expires_at: Local::now() + ttl,
+        })
+    }
+
+    async fn check_login_challenge(&self, token: &str) -> AppResult<i32> {
+        self.find_valid(token, TokenPurpose::LoginChallenge)
+            .await?
+            .map(|token| token.user_id)
+            .ok_or_else(invalid_challenge)
+    }
+
+    async fn consume_login_challenge(&self, token: &str) -> AppResult<i32> {
+        self.consume(token, TokenPurpose::LoginChallenge)
+            .await
+            .map(|token| token.user_id)
+            .map_err(|err| match err {
+                AppError::Validation(_) => invalid_challenge(),
+                err => err,
+            })
+    }
+
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        self.user_token_repository
+            .purge_expired_before(cutoff)
+            .await
+    }
+}
@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Local};
+
+use crate::{
+    api::user::tokens::CreateApiTokenRequest,
+    application::audit::service::{
+        api_token_target, AuditAction, AuditContext, AuditEntry, AuditService,
+    },
+    domain::{
+        entities::api_token::ApiToken,
+        error::{AppError, AppResult},
+        repository::api_token::ApiTokenRepository,
+    },
+    utils::encryption::token::{generate_token, hash_token},
+};
+
+// API 令牌明文的固定前缀, 认证时据此与 JWT 区分
+pub const API_TOKEN_PREFIX: &str = "mth_";
+
+// 列表中展示的明文长度, 包含前缀
+const DISPLAY_PREFIX_LEN: usize = 12;
+const MAX_TOKENS_PER_USER: i64 = 50;
+const MAX_NAME_LEN: usize = 255;
+const MAX_EXPIRES_IN_DAYS: u32 = 365;
+
+pub fn is_api_token(token: &str) -> bool {
+    token.starts_with(API_TOKEN_PREFIX)
+}
+
+// 新建的令牌, 明文只在创建时返回一次
+#[derive(Debug, Clone)]
+pub struct CreatedApiToken {
+    pub token: ApiToken,
+    pub secret: String,
+}
+
+#[async_trait::async_trait]
+pub trait ApiTokenService: Send + Sync {
+    async fn create(
+        &self,
+        user_id: i32,
+        req: CreateApiTokenRequest,
+        ctx: &AuditContext,
+    ) -> AppResult<CreatedApiToken>;
+    async fn list(&self, user_id: i32) -> AppResult<Vec<ApiToken>>;
+    async fn revoke(&self, user_id: i32, id: i32, ctx: &AuditContext) -> AppResult<()>;
+    // 校验令牌明文, 只接受未过期且未吊销的令牌
+    async fn authenticate(&self, secret: &str) -> AppResult<ApiToken>;
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64>;
+}
+
+fn invalid_token() -> AppError {
+    AppError::Unauthorized("Invalid token".to_string())
+}
+
+pub struct ApiTokenServiceImpl<T> {
+    api_token_repository: T,
+    audit_service: Arc<dyn AuditService>,
+    // 距上次记录超过该间隔才更新 last_used_at, 避免每个请求都写库
+    touch_interval: chrono::Duration,
+}
+
+impl<T: ApiTokenRepository> ApiTokenServiceImpl<T> {
+    pub fn new(api_token_repository: T, audit_service: Arc<dyn AuditService>) -> Self {
+        Self {
+            api_token_repository,
+            audit_service,
+            touch_interval: chrono::Duration::minutes(1),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: ApiTokenRepository> ApiTokenService for ApiTokenServiceImpl<T> {
+    async fn create(
+        &self,
+        user_id: i32,
+        req: CreateApiTokenRequest,
+        ctx: &AuditContext,
+    ) -> AppResult<CreatedApiToken> {
+        let name = req.name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+            return Err(AppError::Validation(format!(
+                "Token name must be between 1 and {MAX_NAME_LEN} characters"
+            )));
+        }
+        if req.scopes.is_empty() {
+            return Err(AppError::Validation(
+                "At least one scope is required".to_string(),
+            ));
+        }
+        let expires_at = match req.expires_in_days {
+            Some(days) if days == 0 || days > MAX_EXPIRES_IN_DAYS => {
+                return Err(AppError::Validation(format!(
+                    "expires_in_days must be between 1 and {MAX_EXPIRES_IN_DAYS}"
+                )));
+            }
+            Some(days) => Some(Local::now() + chrono::Duration::days(i64::from(days))),
+            None => None,
+        };
+        if self.api_token_repository.count_by_user_id(user_id).await? >= MAX_TOKENS_PER_USER {
+            return Err(AppError::Validation(format!(
+                "A user can have at most {MAX_TOKENS_PER_USER} API tokens"
+            )));
+        }
+
+        let mut scopes = req.scopes;
+        scopes.sort_by_key(|scope| scope.as_str());
+        scopes.dedup();
+        let secret = format!("{API_TOKEN_PREFIX}{}", generate_token());
+        let token = self
+            .api_token_repository
+            .create(&ApiToken::new(
+                user_id,
+                name.to_string(),
+                secret[..DISPLAY_PREFIX_LEN].to_string(),
+                hash_token(&secret),
+                &scopes,
+                Local::now(),
+                expires_at,
+            ))
+            .await?;
+        self.audit_service
+            .record(
+                ctx,
+                AuditEntry::success(AuditAction::ApiTokenCreate)
+                    .target(api_token_target(token.id))
+                    .detail(token.scopes.clone()),
+            )
+            .await;
+        Ok(CreatedApiToken { token, secret })
+    }
+
+    async fn list(&self, user_id: i32) -> AppResult<Vec<ApiToken>> {
+        self.api_token_repository.list_by_user_id(user_id).await
+    }
+
+    async fn revoke(&self, user_id: i32, id: i32, ctx: &AuditContext) -> AppResult<()> {
+        if !self
+            .api_token_repository
+            .revoke(user_id, id, Local::now())
+            .await?
+        {
+            return Err(AppError::NotFound("API token not found".to_string()));
+        }
+        self.audit_service
+            .record(
+                ctx,
+                AuditEntry::success(AuditAction::ApiTokenRevoke).target(api_token_target(id)),
+            )
+            .await;
+        Ok(())
+    }
+
+    async fn authenticate(&self, secret: &str) -> AppResult<ApiToken> {
+        let mut token = self
+            .api_token_repository
+            .get_by_hash(&hash_token(secret))
+            .await?
+            .ok_or_else(invalid_token)?;
+        let now = Local::now();
+        if !token.is_active(now) {
+            return Err(invalid_token());
+        }
+        if token
+            .last_used_at
+            .is_none_or(|last_used_at| now - last_used_at >= self.touch_interval)
+        {
+            // 记录失败不影响本次请求
+            match self.api_token_repository.touch(token.id, now).await {
+                Ok(()) => token.last_used_at = Some(now),
+                Err(err) => log::warn!("failed to record usage of API token {}: {err}", token.id),
+            }
+        }
+        Ok(token)
+    }
+
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        self.api_token_repository.purge_expired_before(cutoff).await
+    }
+}
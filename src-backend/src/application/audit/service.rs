@@ -0,0 +1,228 @@
+use chrono::Local;
+
+use crate::{
+    api::middleware::client::ClientInfo,
+    domain::{
+        entities::audit_event::{AuditEvent, AuditOutcome},
+        error::AppResult,
+        repository::{
+            audit::{AuditFilter, AuditQuery, AuditRepository},
+            Page,
+        },
+    },
+};
+
+// 与迁移脚本中的列长度一致, 超出部分截断
+const TARGET_MAX_LEN: usize = 255;
+const USER_AGENT_MAX_LEN: usize = 512;
+const DETAIL_MAX_LEN: usize = 255;
+
+// 审计事件的动作, 保存为字符串以便新增动作时不需要迁移
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    Register,
+    Login,
+    LoginTwoFactor,
+    Refresh,
+    Logout,
+    LogoutAll,
+    SessionRevoke,
+    SessionRevokeOthers,
+    PasswordChange,
+    PasswordReset,
+    AccountDelete,
+    TwoFactorEnable,
+    TwoFactorDisable,
+    RecoveryCodesRegenerate,
+    RoleChange,
+    StatusChange,
+    AdminPasswordReset,
+    ApiTokenCreate,
+    ApiTokenRevoke,
+    EmailChange,
+    EmailVerify,
+    IdentityLink,
+    IdentityUnlink,
+    OidcLogin,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Register => "user.register",
+            AuditAction::Login => "auth.login",
+            AuditAction::LoginTwoFactor => "auth.login_2fa",
+            AuditAction::Refresh => "auth.refresh",
+            AuditAction::Logout => "auth.logout",
+            AuditAction::LogoutAll => "auth.logout_all",
+            AuditAction::SessionRevoke => "auth.session_revoke",
+            AuditAction::SessionRevokeOthers => "auth.session_revoke_others",
+            AuditAction::PasswordChange => "user.password_change",
+            AuditAction::PasswordReset => "user.password_reset",
+            AuditAction::AccountDelete => "user.delete",
+            AuditAction::TwoFactorEnable => "user.2fa_enable",
+            AuditAction::TwoFactorDisable => "user.2fa_disable",
+            AuditAction::RecoveryCodesRegenerate => "user.2fa_recovery_codes",
+            AuditAction::RoleChange => "admin.role_change",
+            AuditAction::StatusChange => "admin.status_change",
+            AuditAction::AdminPasswordReset => "admin.password_reset",
+            AuditAction::ApiTokenCreate => "user.api_token_create",
+            AuditAction::ApiTokenRevoke => "user.api_token_revoke",
+            AuditAction::EmailChange => "user.email_change",
+            AuditAction::EmailVerify => "user.email_verify",
+            AuditAction::IdentityLink => "user.identity_link",
+            AuditAction::IdentityUnlink => "user.identity_unlink",
+            AuditAction::OidcLogin => "auth.login_oidc",
+        }
+    }
+}
+
+pub fn user_target(user_id: i32) -> String {
+    format!("user:{user_id}")
+}
+
+pub fn session_target(session_id: i32) -> String {
+    format!("session:{session_id}")
+}
+
+pub fn api_token_target(token_id: i32) -> String {
+    format!("api_token:{token_id}")
+}
+
+pub fn identity_target(identity_id: i32) -> String {
+    format!("identity:{identity_id}")
+}
+
+// 发起操作的请求信息, 由处理函数构造后传给服务
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+    pub actor_id: Option<i32>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl AuditContext {
+    pub fn new(client: &ClientInfo, actor_id: Option<i32>) -> Self {
+        Self {
+            actor_id,
+            ip_address: Some(client.ip.to_string()),
+            user_agent: client
+                .user_agent
+                .as_deref()
+                .map(|agent| truncate(agent, USER_AGENT_MAX_LEN)),
+        }
+    }
+
+    // 命令行等非 HTTP 请求发起的操作
+    pub fn system() -> Self {
+        Self::default()
+    }
+}
+
+// 一条待写入的审计记录, actor 为空时使用 AuditContext 中的用户
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    action: AuditAction,
+    outcome: AuditOutcome,
+    actor_id: Option<i32>,
+    target: Option<String>,
+    detail: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn success(action: AuditAction) -> Self {
+        Self {
+            action,
+            outcome: AuditOutcome::Success,
+            actor_id: None,
+            target: None,
+            detail: None,
+        }
+    }
+
+    pub fn failure(action: AuditAction, detail: impl Into<String>) -> Self {
+        Self {
+            outcome: AuditOutcome::Failure,
+            ..Self::success(action)
+        }
+        .detail(detail)
+    }
+
+    pub fn actor(mut self, actor_id: i32) -> Self {
+        self.actor_id = Some(actor_id);
+        self
+    }
+
+    pub fn target(mut self, target: String) -> Self {
+        self.target = Some(truncate(&target, TARGET_MAX_LEN));
+        self
+    }
+
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(truncate(&detail.into(), DETAIL_MAX_LEN));
+        self
+    }
+}
+
+fn truncate(value: &str, max_chars: usize) -> String {
+    value.chars().take(max_chars).collect()
+}
+
+#[async_trait::async_trait]
+pub trait AuditService: Send + Sync {
+    // 写入失败只记录日志, 不影响正在进行的操作
+    async fn record(&self, ctx: &AuditContext, entry: AuditEntry);
+    async fn search(&self, query: AuditQuery) -> AppResult<Page<AuditEvent>>;
+    // 导出时按 id 升序分批读取
+    async fn list_after(
+        &self,
+        filter: &AuditFilter,
+        after_id: i64,
+        limit: u32,
+    ) -> AppResult<Vec<AuditEvent>>;
+}
+
+pub struct AuditServiceImpl<T> {
+    audit_repository: T,
+}
+
+impl<T: AuditRepository> AuditServiceImpl<T> {
+    pub fn new(audit_repository: T) -> Self {
+        Self { audit_repository }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: AuditRepository> AuditService for AuditServiceImpl<T> {
+    async fn record(&self, ctx: &AuditContext, entry: AuditEntry) {
+        let event = AuditEvent {
+            id: 0,
+            actor_id: entry.actor_id.or(ctx.actor_id),
+            action: entry.action.as_str().to_string(),
+            target: entry.target,
+            ip_address: ctx.ip_address.clone(),
+            user_agent: ctx.user_agent.clone(),
+            outcome: entry.outcome,
+            detail: entry.detail,
+            created_at: Local::now(),
+        };
+        if let Err(err) = self.audit_repository.append(&event).await {
+            log::error!("failed to record audit event {}: {err}", event.action);
+        }
+    }
+
+    async fn search(&self, query: AuditQuery) -> AppResult<Page<AuditEvent>> {
+        self.audit_repository.search(&query).await
+    }
+
+    async fn list_after(
+        &self,
+        filter: &AuditFilter,
+        after_id: i64,
+        limit: u32,
+    ) -> AppResult<Vec<AuditEvent>> {
+        self.audit_repository
+            .list_after(filter, after_id, limit)
+            .await
+    }
+}
@@ -0,0 +1,404 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+use crate::{
+    api::middleware::client::ClientInfo,
+    application::{
+        audit::service::{
+            session_target, user_target, AuditAction, AuditContext, AuditEntry, AuditService,
+        },
+        user::service::UserService,
+    },
+    domain::{
+        entities::{refresh_token::RefreshToken, session::Session},
+        error::{AppError, AppResult},
+        repository::{refresh_token::RefreshTokenRepository, session::SessionRepository},
+    },
+    utils::{
+        encryption::token::{generate_token, hash_token},
+        jwt::JwtKeys,
+    },
+};
+
+// 登录或刷新后返回给客户端的令牌
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenPair {
+    pub token: String,
+    pub refresh_token: String,
+    // access token 的有效期, 单位秒
+    pub expires_in: i64,
+}
+
+// 设备名称和 User-Agent 的最大长度, 超出部分截断
+const DEVICE_NAME_MAX_LEN: usize = 100;
+const USER_AGENT_MAX_LEN: usize = 512;
+// 会话最近活跃时间的更新间隔, 避免每个请求都写数据库
+const SESSION_TOUCH_INTERVAL_SECS: i64 = 60;
+
+// 登录时记录到会话中的客户端信息
+#[derive(Debug, Clone)]
+pub struct SessionInfo {
+    pub device_name: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: String,
+}
+
+impl SessionInfo {
+    pub fn new(client: &ClientInfo, device_name: Option<String>) -> Self {
+        Self {
+            device_name: device_name
+                .map(|name| truncate(name.trim(), DEVICE_NAME_MAX_LEN))
+                .filter(|name| !name.is_empty()),
+            user_agent: client
+                .user_agent
+                .as_deref()
+                .map(|agent| truncate(agent, USER_AGENT_MAX_LEN)),
+            ip_address: client.ip.to_string(),
+        }
+    }
+}
+
+fn truncate(value: &str, max_chars: usize) -> String {
+    value.chars().take(max_chars).collect()
+}
+
+#[async_trait::async_trait]
+pub trait AuthService: Send + Sync {
+    // 为通过认证的用户开启一个新的会话和令牌 family
+    async fn issue(&self, user_id: i32, info: SessionInfo) -> AppResult<TokenPair>;
+    async fn refresh(&self, refresh_token: &str, client: &ClientInfo) -> AppResult<TokenPair>;
+    async fn logout(&self, refresh_token: &str, ctx: &AuditContext) -> AppResult<()>;
+    async fn logout_all(&self, user_id: i32, ctx: &AuditContext) -> AppResult<()>;
+    // 校验 access token 所属的会话仍然有效, 由 auth 中间件在每个请求上调用
+    async fn authenticate_session(
+        &self,
+        user_id: i32,
+        session_id: i32,
+        client: &ClientInfo,
+    ) -> AppResult<()>;
+    async fn list_sessions(&self, user_id: i32) -> AppResult<Vec<Session>>;
+    async fn revoke_session(
+        &self,
+        user_id: i32,
+        session_id: i32,
+        ctx: &AuditContext,
+    ) -> AppResult<()>;
+    // 吊销除当前会话以外的所有会话, 返回吊销的数量
+    async fn revoke_other_sessions(
+        &self,
+        user_id: i32,
+        current_session_id: i32,
+        ctx: &AuditContext,
+    ) -> AppResult<u64>;
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64>;
+}
+
+fn invalid_refresh_token() -> AppError {
+    AppError::Unauthorized("Invalid refresh token".to_string())
+}
+
+fn session_revoked() -> AppError {
+    AppError::Unauthorized("Session has been revoked".to_string())
+}
+
+pub struct AuthServiceImpl<T, S> {
+    refresh_token_repository: T,
+    session_repository: S,
+    user_service: Arc<dyn UserService>,
+    audit_service: Arc<dyn AuditService>,
+    jwt: Arc<JwtKeys>,
+    refresh_token_ttl: chrono::Duration,
+}
+
+impl<T: RefreshTokenRepository, S: SessionRepository> AuthServiceImpl<T, S> {
+    pub fn new(
+        refresh_token_repository: T,
+        session_repository: S,
+        user_service: Arc<dyn UserService>,
+        audit_service: Arc<dyn AuditService>,
+        jwt: Arc<JwtKeys>,
+        refresh_token_ttl: chrono::Duration,
+    ) -> Self {
+        Self {
+            refresh_token_repository,
+            session_repository,
+            user_service,
+            audit_service,
+            jwt,
+            refresh_token_ttl,
+        }
+    }
+
+    // 签发新的刷新令牌并把会话的过期时间延长到与之一致
+    async fn issue_in_session(
+        &self,
+        session: &Session,
+        ip_address: &str,
+        now: DateTime<Local>,
+    ) -> AppResult<TokenPair> {
+        let refresh_token = generate_token();
+        let expires_at = now + self.refresh_token_ttl;
+        self.refresh_token_repository
+            .create(&RefreshToken::new(
+                session.user_id,
+                session.family_id.clone(),
+                hash_token(&refresh_token),
+                now,
+                expires_at,
+            ))
+            .await?;
+        self.session_repository
+            .touch(session.id, ip_address, now, expires_at)
+            .await?;
+        Ok(TokenPair {
+            token: self.jwt.generate_token(session.user_id, session.id)?,
+            refresh_token,
+            expires_in: self.jwt.access_token_ttl().num_seconds(),
+        })
+    }
+
+    // 吊销会话及其刷新令牌 family
+    async fn revoke_family(&self, family_id: &str, now: DateTime<Local>) -> AppResult<()> {
+        self.refresh_token_repository
+            .revoke_family(family_id, now)
+            .await?;
+        if let Some(session) = self.session_repository.get_by_family_id(family_id).await? {
+            self.session_repository.revoke(session.id, now).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: RefreshTokenRepository, S: SessionRepository> AuthService for AuthServiceImpl<T, S> {
+    // 每次签发都对应一次成功的登录
+    async fn issue(&self, user_id: i32, info: SessionInfo) -> AppResult<TokenPair> {
+        let now = Local::now();
+        let session = self
+            .session_repository
+            .create(&Session::new(
+                user_id,
+                generate_token(),
+                info.device_name,
+                info.user_agent,
+                info.ip_address.clone(),
+                now,
+                now + self.refresh_token_ttl,
+            ))
+            .await?;
+        let tokens = self
+            .issue_in_session(&session, &info.ip_address, now)
+            .await?;
+        let ctx = AuditContext {
+            actor_id: Some(user_id),
+            ip_address: Some(info.ip_address),
+            user_agent: session.user_agent.clone(),
+        };
+        self.audit_service
+            .record(
+                &ctx,
+                AuditEntry::success(AuditAction::Login).target(session_target(session.id)),
+            )
+            .await;
+        Ok(tokens)
+    }
+
+    async fn refresh(&self, refresh_token: &str, client: &ClientInfo) -> AppResult<TokenPair> {
+        let token = self
+            .refresh_token_repository
+            .get_by_hash(&hash_token(refresh_token))
+            .await?
+            .ok_or_else(invalid_refresh_token)?;
+        let now = Local::now();
+        if token.revoked_at.is_some() || token.expires_at <= now {
+            return Err(invalid_refresh_token());
+        }
+        let session = self
+            .session_repository
+            .get_by_family_id(&token.family_id)
+            .await?
+            .filter(|session| session.revoked_at.is_none())
+            .ok_or_else(invalid_refresh_token)?;
+
+        // 已轮换过的令牌被再次使用, 说明令牌可能已泄露, 吊销整个 family
+        if token.used_at.is_some()
+            || !self
+                .refresh_token_repository
+                .mark_used(token.id, now)
+                .await?
+        {
+            self.revoke_family(&token.family_id, now).await?;
+            log::warn!(
+                "refresh token reuse detected for user {}, family revoked",
+                token.user_id
+            );
+            self.audit_service
+                .record(
+                    &AuditContext::new(client, None),
+                    AuditEntry::failure(AuditAction::Refresh, "refresh token reuse detected")
+                        .target(session_target(session.id)),
+                )
+                .await;
+            return Err(invalid_refresh_token());
+        }
+
+        // 用户已被删除或停用时不再签发新令牌
+        match self.user_service.get_user_by_id(token.user_id).await {
+            Ok(user) if user.is_suspended() => {
+                self.revoke_family(&token.family_id, now).await?;
+                return Err(AppError::AccountSuspended);
+            }
+            Ok(_) => {}
+            Err(AppError::NotFound(_)) => {
+                self.revoke_family(&token.family_id, now).await?;
+                return Err(invalid_refresh_token());
+            }
+            Err(err) => return Err(err),
+        }
+
+        let tokens = self
+            .issue_in_session(&session, &client.ip.to_string(), now)
+            .await?;
+        self.audit_service
+            .record(
+                &AuditContext::new(client, Some(token.user_id)),
+                AuditEntry::success(AuditAction::Refresh).target(session_target(session.id)),
+            )
+            .await;
+        Ok(tokens)
+    }
+
+    async fn logout(&self, refresh_token: &str, ctx: &AuditContext) -> AppResult<()> {
+        let Some(token) = self
+            .refresh_token_repository
+            .get_by_hash(&hash_token(refresh_token))
+            .await?
+        else {
+            return Ok(());
+        };
+        self.revoke_family(&token.family_id, Local::now()).await?;
+        let mut entry = AuditEntry::success(AuditAction::Logout).actor(token.user_id);
+        if let Some(session) = self
+            .session_repository
+            .get_by_family_id(&token.family_id)
+            .await?
+        {
+            entry = entry.target(session_target(session.id));
+        }
+        self.audit_service.record(ctx, entry).await;
+        Ok(())
+    }
+
+    async fn logout_all(&self, user_id: i32, ctx: &AuditContext) -> AppResult<()> {
+        let now = Local::now();
+        self.refresh_token_repository
+            .revoke_all_by_user_id(user_id, now)
+            .await?;
+        let revoked = self
+            .session_repository
+            .revoke_all_by_user_id(user_id, now)
+            .await?;
+        self.audit_service
+            .record(
+                ctx,
+                AuditEntry::success(AuditAction::LogoutAll)
+                    .target(user_target(user_id))
+                    .detail(format!("revoked {revoked} sessions")),
+            )
+            .await;
+        Ok(())
+    }
+
+    async fn authenticate_session(
+        &self,
+        user_id: i32,
+        session_id: i32,
+        client: &ClientInfo,
+    ) -> AppResult<()> {
+        let now = Local::now();
+        let session = self
+            .session_repository
+            .get(session_id)
+            .await?
+            .filter(|session| session.user_id == user_id && session.is_active(now))
+            .ok_or_else(session_revoked)?;
+        let ip_address = client.ip.to_string();
+        if session.ip_address != ip_address
+            || now - session.last_active_at
+                >= chrono::Duration::seconds(SESSION_TOUCH_INTERVAL_SECS)
+        {
+            self.session_repository
+                .touch(session.id, &ip_address, now, session.expires_at)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn list_sessions(&self, user_id: i32) -> AppResult<Vec<Session>> {
+        self.session_repository
+            .list_active_by_user_id(user_id, Local::now())
+            .await
+    }
+
+    async fn revoke_session(
+        &self,
+        user_id: i32,
+        session_id: i32,
+        ctx: &AuditContext,
+    ) -> AppResult<()> {
+        let session = self
+            .session_repository
+            .get(session_id)
+            .await?
+            .filter(|session| session.user_id == user_id && session.revoked_at.is_none())
+            .ok_or_else(|| AppError::NotFound("Session not found".to_string()))?;
+        self.revoke_family(&session.family_id, Local::now()).await?;
+        self.audit_service
+            .record(
+                ctx,
+                AuditEntry::success(AuditAction::SessionRevoke).target(session_target(session.id)),
+            )
+            .await;
+        Ok(())
+    }
+
+    async fn revoke_other_sessions(
+        &self,
+        user_id: i32,
+        current_session_id: i32,
+        ctx: &AuditContext,
+    ) -> AppResult<u64> {
+        let now = Local::now();
+        let mut count = 0;
+        for session in self
+            .session_repository
+            .list_active_by_user_id(user_id, now)
+            .await?
+        {
+            if session.id != current_session_id {
+                self.revoke_family(&session.family_id, now).await?;
+                count += 1;
+            }
+        }
+        self.audit_service
+            .record(
+                ctx,
+                AuditEntry::success(AuditAction::SessionRevokeOthers)
+                    .target(user_target(user_id))
+                    .detail(format!("revoked {count} sessions")),
+            )
+            .await;
+        Ok(count)
+    }
+
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let tokens = self
+            .refresh_token_repository
+            .purge_expired_before(cutoff)
+            .await?;
+        let sessions = self.session_repository.purge_expired_before(cutoff).await?;
+        Ok(tokens + sessions)
+    }
+}
@@ -1,2 +1,10 @@
+pub mod account;
+pub mod api_token;
+pub mod audit;
+pub mod auth;
+pub mod oidc;
+pub mod tag;
 pub mod todo;
+pub mod trash;
+pub mod two_factor;
 pub mod user;
@@ -0,0 +1,277 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Local};
+
+use crate::{
+    api::user::oidc::OidcCallbackRequest,
+    application::{
+        audit::service::{identity_target, AuditAction, AuditContext, AuditEntry, AuditService},
+        user::service::UserService,
+    },
+    domain::{
+        entities::{identity::Identity, user::User},
+        error::{AppError, AppResult},
+        repository::identity::IdentityRepository,
+    },
+    infastructure::oidc::{IdTokenClaims, OidcClient},
+    utils::encryption::token::generate_token,
+};
+
+// 从发起登录到身份提供方回调的最长时间
+const PENDING_LOGIN_TTL_SECS: i64 = 10 * 60;
+// 未完成的登录数量上限, 超出时拒绝新的登录请求
+const MAX_PENDING_LOGINS: usize = 10_000;
+
+// 发起登录后返回给前端, 前端跳转到 authorization_url
+#[derive(Debug, Clone)]
+pub struct OidcAuthorization {
+    pub authorization_url: String,
+    pub state: String,
+}
+
+#[async_trait::async_trait]
+pub trait OidcService: Send + Sync {
+    // link_user_id 不为空时, 回调成功后把外部身份关联到该用户
+    async fn begin(&self, link_user_id: Option<i32>) -> AppResult<OidcAuthorization>;
+    // 校验回调并返回登录的用户, 首次登录时关联或创建本地用户
+    async fn complete(&self, req: OidcCallbackRequest, ctx: &AuditContext) -> AppResult<User>;
+    async fn list_identities(&self, user_id: i32) -> AppResult<Vec<Identity>>;
+    async fn unlink(&self, user_id: i32, id: i32, ctx: &AuditContext) -> AppResult<()>;
+}
+
+struct PendingLogin {
+    code_verifier: String,
+    nonce: String,
+    link_user_id: Option<i32>,
+    expires_at: DateTime<Local>,
+}
+
+fn not_enabled() -> AppError {
+    AppError::NotFound("OIDC login is not enabled".to_string())
+}
+
+// 依次使用 preferred_username, name 和邮箱的本地部分作为用户名
+fn username_from_claims(claims: &IdTokenClaims, email: &str) -> String {
+    [&claims.preferred_username, &claims.name]
+        .into_iter()
+        .flatten()
+        .map(|name| name.trim())
+        .find(|name| !name.is_empty())
+        .unwrap_or_else(|| email.split('@').next().unwrap_or(email))
+        .to_string()
+}
+
+// 待完成的登录只保存在内存中, 多实例部署时回调需要路由到发起登录的实例
+pub struct OidcServiceImpl<T> {
+    identity_repository: T,
+    client: Option<OidcClient>,
+    user_service: Arc<dyn UserService>,
+    audit_service: Arc<dyn AuditService>,
+    pending: Mutex<HashMap<String, PendingLogin>>,
+}
+
+impl<T: IdentityRepository> OidcServiceImpl<T> {
+    pub fn new(
+        identity_repository: T,
+        client: Option<OidcClient>,
+        user_service: Arc<dyn UserService>,
+        audit_service: Arc<dyn AuditService>,
+    ) -> Self {
+        Self {
+            identity_repository,
+            client,
+            user_service,
+            audit_service,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn client(&self) -> AppResult<&OidcClient> {
+        self.client.as_ref().ok_or_else(not_enabled)
+    }
+
+    // state 只能使用一次, 过期的登录一并清理
+    fn take_pending(&self, state: &str) -> Option<PendingLogin> {
+        let now = Local::now();
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|_, login| login.expires_at > now);
+        pending.remove(state)
+    }
+
+    // 校验回调并找到或创建对应的用户, 失败时由 complete 记录审计日志
+    async fn complete_login(
+        &self,
+        req: OidcCallbackRequest,
+        ctx: &AuditContext,
+    ) -> AppResult<User> {
+        let client = self.client()?;
+        let login = self.take_pending(&req.state).ok_or_else(|| {
+            AppError::Unauthorized("Invalid or expired OIDC login state".to_string())
+        })?;
+        let claims = client
+            .exchange_code(&req.code, &login.code_verifier, &login.nonce)
+            .await?;
+
+        let now = Local::now();
+        let linked = match self
+            .identity_repository
+            .get_by_subject(client.issuer(), &claims.sub)
+            .await?
+        {
+            Some(identity) => match self.user_service.get_user_by_id(identity.user_id).await {
+                Ok(user) => Some((identity, user)),
+                // 关联的用户已被删除, 身份随之失效, 删除后按首次登录处理
+                Err(AppError::NotFound(_)) => {
+                    self.identity_repository
+                        .delete(identity.user_id, identity.id)
+                        .await?;
+                    None
+                }
+                Err(err) => return Err(err),
+            },
+            None => None,
+        };
+
+        let user = match linked {
+            Some((identity, user)) => {
+                if login.link_user_id.is_some_and(|id| id != identity.user_id) {
+                    return Err(AppError::Conflict(
+                        "This identity is already linked to another account".to_string(),
+                    ));
+                }
+                self.identity_repository.touch(identity.id, now).await?;
+                user
+            }
+            None => {
+                let user = self.resolve_user(&claims, login.link_user_id).await?;
+                let identity = self
+                    .identity_repository
+                    .create(&Identity::new(
+                        user.id,
+                        client.issuer().to_string(),
+                        claims.sub.clone(),
+                        claims.email.clone(),
+                        now,
+                    ))
+                    .await?;
+                self.audit_service
+                    .record(
+                        ctx,
+                        AuditEntry::success(AuditAction::IdentityLink)
+                            .actor(user.id)
+                            .target(identity_target(identity.id))
+                            .detail(identity.issuer),
+                    )
+                    .await;
+                user
+            }
+        };
+        self.user_service.check_login_allowed(&user).await?;
+        Ok(user)
+    }
+
+    // 外部身份首次登录时对应的本地用户
+    async fn resolve_user(
+        &self,
+        claims: &IdTokenClaims,
+        link_user_id: Option<i32>,
+    ) -> AppResult<User> {
+        if let Some(user_id) = link_user_id {
+            return self.user_service.get_user_by_id(user_id).await;
+        }
+        let email = claims
+            .email
+            .clone()
+            .filter(|email| !email.trim().is_empty())
+            .ok_or_else(|| {
+                AppError::Validation("The identity provider did not return an email".to_string())
+            })?;
+        match self.user_service.get_user_by_email(email.clone()).await {
+            // 只有身份提供方确认过邮箱时才自动关联, 否则任何人都能用他人邮箱接管账户
+            Ok(mut user) if claims.email_verified => {
+                if user.email_verified_at.is_none() {
+                    user.email_verified_at = Some(Local::now());
+                    self.user_service.update_user(user.clone()).await?;
+                }
+                Ok(user)
+            }
+            Ok(_) => Err(AppError::Conflict(
+                "Email already registered, sign in with your password and link the identity from your account".to_string(),
+            )),
+            Err(AppError::NotFound(_)) => {
+                self.user_service
+                    .create_external_user(
+                        username_from_claims(claims, &email),
+                        email,
+                        claims.email_verified,
+                    )
+                    .await
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: IdentityRepository> OidcService for OidcServiceImpl<T> {
+    async fn begin(&self, link_user_id: Option<i32>) -> AppResult<OidcAuthorization> {
+        let client = self.client()?;
+        let state = generate_token();
+        let login = PendingLogin {
+            code_verifier: generate_token(),
+            nonce: generate_token(),
+            link_user_id,
+            expires_at: Local::now() + chrono::Duration::seconds(PENDING_LOGIN_TTL_SECS),
+        };
+        let authorization_url = client
+            .authorization_url(&state, &login.nonce, &login.code_verifier)
+            .await?;
+
+        let now = Local::now();
+        let mut pending = self.pending.lock().unwrap();
+        if pending.len() >= MAX_PENDING_LOGINS {
+            pending.retain(|_, login| login.expires_at > now);
+            if pending.len() >= MAX_PENDING_LOGINS {
+                return Err(AppError::RateLimited { retry_after: 60 });
+            }
+        }
+        pending.insert(state.clone(), login);
+        Ok(OidcAuthorization {
+            authorization_url,
+            state,
+        })
+    }
+
+    async fn complete(&self, req: OidcCallbackRequest, ctx: &AuditContext) -> AppResult<User> {
+        let result = self.complete_login(req, ctx).await;
+        if let Err(err) = &result {
+            self.audit_service
+                .record(
+                    ctx,
+                    AuditEntry::failure(AuditAction::OidcLogin, err.to_string()),
+                )
+                .await;
+        }
+        result
+    }
+
+    async fn list_identities(&self, user_id: i32) -> AppResult<Vec<Identity>> {
+        self.identity_repository.list_by_user_id(user_id).await
+    }
+
+    async fn unlink(&self, user_id: i32, id: i32, ctx: &AuditContext) -> AppResult<()> {
+        if !self.identity_repository.delete(user_id, id).await? {
+            return Err(AppError::NotFound("Identity not found".to_string()));
+        }
+        self.audit_service
+            .record(
+                ctx,
+                AuditEntry::success(AuditAction::IdentityUnlink).target(identity_target(id)),
+            )
+            .await;
+        Ok(())
+    }
+}
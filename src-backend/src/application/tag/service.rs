@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use chrono::Local;
+
+use crate::{
+    application::todo::service::TodoAppService,
+    domain::{
+        entities::tag::Tag,
+        error::{AppError, AppResult},
+        repository::tag::TagRepository,
+    },
+};
+
+pub const DEFAULT_TAG_COLOR: &str = "#808080";
+const MAX_NAME_LEN: usize = 64;
+
+// 部分更新标签, 为 None 的字段保持不变
+#[derive(Debug, Default, Clone)]
+pub struct UpdateTag {
+    pub name: Option<String>,
+    pub color: Option<String>,
+}
+
+#[async_trait::async_trait]
+pub trait TagService: Send + Sync {
+    async fn list(&self, user_id: i32) -> AppResult<Vec<Tag>>;
+    // color 为空时使用默认颜色
+    async fn create(&self, user_id: i32, name: String, color: Option<String>) -> AppResult<Tag>;
+    async fn update(&self, user_id: i32, id: i32, update: UpdateTag) -> AppResult<Tag>;
+    async fn delete(&self, user_id: i32, id: i32) -> AppResult<()>;
+    async fn list_for_todo(&self, user_id: i32, todo_id: i32) -> AppResult<Vec<Tag>>;
+    // 关联和解除关联都是幂等的, 返回任务当前的标签
+    async fn attach(&self, user_id: i32, todo_id: i32, tag_id: i32) -> AppResult<Vec<Tag>>;
+    async fn detach(&self, user_id: i32, todo_id: i32, tag_id: i32) -> AppResult<Vec<Tag>>;
+}
+
+fn tag_not_found() -> AppError {
+    AppError::NotFound("Tag not found".to_string())
+}
+
+fn validate_name(name: &str) -> AppResult<String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(AppError::Validation(format!(
+            "Tag name must be between 1 and {MAX_NAME_LEN} characters"
+        )));
+    }
+    Ok(name.to_string())
+}
+
+// 只接受 #rrggbb, 统一保存为小写
+fn validate_color(color: &str) -> AppResult<String> {
+    match color.strip_prefix('#') {
+        Some(hex) if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Ok(color.to_ascii_lowercase())
+        }
+        _ => Err(AppError::Validation(
+            "Tag color must be in #rrggbb format".to_string(),
+        )),
+    }
+}
+
+fn name_conflict(err: AppError) -> AppError {
+    match err {
+        AppError::Conflict(_) => {
+            AppError::Conflict("A tag with this name already exists".to_string())
+        }
+        err => err,
+    }
+}
+
+pub struct TagServiceImpl<T> {
+    tag_repository: T,
+    todo_service: Arc<dyn TodoAppService>,
+}
+
+impl<T: TagRepository> TagServiceImpl<T> {
+    pub fn new(tag_repository: T, todo_service: Arc<dyn TodoAppService>) -> Self {
+        Self {
+            tag_repository,
+            todo_service,
+        }
+    }
+
+    async fn get_tag(&self, user_id: i32, id: i32) -> AppResult<Tag> {
+        self.tag_repository
+            .get_by_id(user_id, id)
+            .await?
+            .ok_or_else(tag_not_found)
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: TagRepository> TagService for TagServiceImpl<T> {
+    async fn list(&self, user_id: i32) -> AppResult<Vec<Tag>> {
+        self.tag_repository.list_by_user_id(user_id).await
+    }
+
+    async fn create(&self, user_id: i32, name: String, color: Option<String>) -> AppResult<Tag> {
+        let name = validate_name(&name)?;
+        let color = validate_color(color.as_deref().unwrap_or(DEFAULT_TAG_COLOR))?;
+        self.tag_repository
+            .create(&Tag::new(user_id, name, color, Local::now()))
+            .await
+            .map_err(name_conflict)
+    }
+
+    async fn update(&self, user_id: i32, id: i32, update: UpdateTag) -> AppResult<Tag> {
+        let mut tag = self.get_tag(user_id, id).await?;
+        if let Some(name) = update.name {
+            tag.name = validate_name(&name)?;
+        }
+        if let Some(color) = update.color {
+            tag.color = validate_color(&color)?;
+        }
+        tag.updated_at = Local::now();
+        self.tag_repository
+            .save(tag.clone())
+            .await
+            .map_err(name_conflict)?;
+        Ok(tag)
+    }
+
+    async fn delete(&self, user_id: i32, id: i32) -> AppResult<()> {
+        if self.tag_repository.delete(user_id, id).await? {
+            Ok(())
+        } else {
+            Err(tag_not_found())
+        }
+    }
+
+    async fn list_for_todo(&self, user_id: i32, todo_id: i32) -> AppResult<Vec<Tag>> {
+        self.todo_service.get_by_id(user_id, todo_id).await?;
+        self.tag_repository.list_by_todo_id(todo_id).await
+    }
+
+    async fn attach(&self, user_id: i32, todo_id: i32, tag_id: i32) -> AppResult<Vec<Tag>> {
+        self.todo_service.get_by_id(user_id, todo_id).await?;
+        self.get_tag(user_id, tag_id).await?;
+        self.tag_repository.attach(todo_id, tag_id).await?;
+        self.tag_repository.list_by_todo_id(todo_id).await
+    }
+
+    async fn detach(&self, user_id: i32, todo_id: i32, tag_id: i32) -> AppResult<Vec<Tag>> {
+        self.todo_service.get_by_id(user_id, todo_id).await?;
+        self.get_tag(user_id, tag_id).await?;
+        self.tag_repository.detach(todo_id, tag_id).await?;
+        self.tag_repository.list_by_todo_id(todo_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_color() {
+        assert_eq!(validate_color("#A0b1C2").unwrap(), "#a0b1c2");
+        for color in ["a0b1c2", "#a0b1c", "#a0b1c2d", "#ghijkl", ""] {
+            assert!(matches!(
+                validate_color(color),
+                Err(AppError::Validation(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validate_name_trims() {
+        assert_eq!(validate_name("  work ").unwrap(), "work");
+        assert!(validate_name("   ").is_err());
+        assert!(validate_name(&"x".repeat(MAX_NAME_LEN + 1)).is_err());
+    }
+}
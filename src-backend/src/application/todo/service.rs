@@ -1,21 +1,137 @@
-use anyhow::Result;
+use std::collections::HashMap;
+
 use chrono::{DateTime, Local};
+use serde::Serialize;
 
 use crate::domain::{
     entities::todo::{Priority, Status, Todo},
-    repository::todo::TodoRepository,
+    error::{AppError, AppResult},
+    repository::{
+        todo::{TodoQuery, TodoRepository},
+        Page,
+    },
 };
 
+// 部分更新 todo, 为 None 的字段保持不变, deadline 为 Some(None) 时清除截止时间
+#[derive(Debug, Default, Clone)]
+pub struct UpdateTodo {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub status: Option<Status>,
+    pub priority: Option<Priority>,
+    pub deadline: Option<Option<DateTime<Local>>>,
+    pub done: Option<bool>,
+    pub auto_complete: Option<bool>,
+}
+
+// 任务及其所有子任务, progress 为子树中已完成任务所占的百分比,
+// 没有子任务时完成为 100, 否则为 0
+#[derive(Debug, Clone, Serialize)]
+pub struct TodoTree {
+    #[serde(flatten)]
+    pub todo: Todo,
+    pub progress: u8,
+    pub children: Vec<TodoTree>,
+}
+
 #[async_trait::async_trait]
 pub trait TodoAppService: Send + Sync {
-    async fn get_all_by_user_id(&self, user_id: i32) -> Vec<Todo>;
-    async fn get_by_id(&self, id: i32) -> Option<Todo>;
-    async fn create(&self, todo: Todo) -> Result<Todo>;
-    async fn update_status(&self, id: i32, status: Status) -> bool;
-    async fn update_priority(&self, id: i32, priority: Priority) -> bool;
-    async fn update_deadline(&self, id: i32, deadline: DateTime<Local>) -> bool;
-    async fn update_done(&self, id: i32, done: bool) -> bool;
-    async fn delete(&self, id: i32) -> bool;
+    async fn get_all_by_user_id(&self, user_id: i32) -> AppResult<Vec<Todo>>;
+    async fn list(&self, user_id: i32, query: TodoQuery) -> AppResult<Page<Todo>>;
+    async fn get_by_id(&self, user_id: i32, id: i32) -> AppResult<Todo>;
+    async fn create(&self, todo: Todo) -> AppResult<Todo>;
+    async fn update(&self, user_id: i32, id: i32, update: UpdateTodo) -> AppResult<Todo>;
+    async fn update_status(&self, user_id: i32, id: i32, status: Status) -> AppResult<Todo>;
+    async fn update_priority(&self, user_id: i32, id: i32, priority: Priority) -> AppResult<Todo>;
+    async fn update_deadline(
+        &self,
+        user_id: i32,
+        id: i32,
+        deadline: DateTime<Local>,
+    ) -> AppResult<Todo>;
+    async fn update_done(&self, user_id: i32, id: i32, done: bool) -> AppResult<Todo>;
+    async fn get_tree(&self, user_id: i32, id: i32) -> AppResult<TodoTree>;
+    // 把任务及其子树移动到 parent_id 下, 为 None 时移动到顶层
+    async fn move_to(&self, user_id: i32, id: i32, parent_id: Option<i32>) -> AppResult<Todo>;
+    // 同时删除所有子任务
+    async fn delete(&self, user_id: i32, id: i32) -> AppResult<()>;
+    async fn list_trash(&self, user_id: i32) -> AppResult<Vec<Todo>>;
+    async fn restore(&self, user_id: i32, id: i32) -> AppResult<Todo>;
+    async fn purge_deleted_before(&self, cutoff: DateTime<Local>) -> AppResult<u64>;
+}
+
+fn todo_not_found() -> AppError {
+    AppError::NotFound("Todo not found".to_string())
+}
+
+fn parent_not_found() -> AppError {
+    AppError::Validation("Parent todo does not exist".to_string())
+}
+
+// 用户所有未删除的任务, 按父任务建立索引
+struct TodoForest {
+    todos: HashMap<i32, Todo>,
+    children: HashMap<i32, Vec<i32>>,
+}
+
+impl TodoForest {
+    fn new(todos: Vec<Todo>) -> Self {
+        let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+        for todo in &todos {
+            if let Some(parent_id) = todo.parent_id {
+                children.entry(parent_id).or_default().push(todo.id);
+            }
+        }
+        for ids in children.values_mut() {
+            ids.sort_unstable();
+        }
+        Self {
+            todos: todos.into_iter().map(|todo| (todo.id, todo)).collect(),
+            children,
+        }
+    }
+
+    fn children(&self, id: i32) -> &[i32] {
+        self.children.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    // 不包含 id 本身
+    fn descendants(&self, id: i32) -> Vec<i32> {
+        let mut result = Vec::new();
+        let mut stack = self.children(id).to_vec();
+        while let Some(child) = stack.pop() {
+            result.push(child);
+            stack.extend_from_slice(self.children(child));
+        }
+        result
+    }
+
+    // 返回子树和其中 (任务数, 已完成数), 不包含根节点
+    fn build(&self, id: i32) -> (TodoTree, usize, usize) {
+        let mut children = Vec::new();
+        let (mut total, mut done) = (0, 0);
+        for child in self.children(id) {
+            let (tree, child_total, child_done) = self.build(*child);
+            total += child_total + 1;
+            done += child_done + usize::from(tree.todo.done);
+            children.push(tree);
+        }
+        let todo = self.todos[&id].clone();
+        let progress = match total {
+            0 if todo.done => 100,
+            0 => 0,
+            _ => (done * 100 / total) as u8,
+        };
+        (
+            TodoTree {
+                todo,
+                progress,
+                children,
+            },
+            total,
+            done,
+        )
+    }
 }
 
 pub struct TodoAppServiceImpl<T> {
@@ -26,47 +142,230 @@ impl<T: TodoRepository> TodoAppServiceImpl<T> {
     pub fn new(todo_repository: T) -> Self {
         Self { todo_repository }
     }
+
+    // 只修改属于 user_id 的 todo, 找不到时返回 NotFound
+    async fn modify<F>(&self, user_id: i32, id: i32, f: F) -> AppResult<Todo>
+    where
+        F: FnOnce(&mut Todo) + Send,
+    {
+        let mut todo = self.get_by_id(user_id, id).await?;
+        f(&mut todo);
+        todo.updated_at = Local::now();
+        self.todo_repository.save(todo.clone()).await?;
+        Ok(todo)
+    }
+
+    async fn forest(&self, user_id: i32) -> AppResult<TodoForest> {
+        Ok(TodoForest::new(
+            self.todo_repository.get_all_by_user_id(user_id).await?,
+        ))
+    }
+
+    // 从 start 开始向上检查, 开启了 auto_complete 且所有子任务都已完成的任务自动完成
+    async fn roll_up(&self, user_id: i32, start: Option<i32>) -> AppResult<()> {
+        let mut forest = self.forest(user_id).await?;
+        let mut next = start;
+        while let Some(id) = next {
+            let Some(todo) = forest.todos.get(&id) else {
+                break;
+            };
+            let children = forest.children(id);
+            if !todo.auto_complete
+                || todo.done
+                || children.is_empty()
+                || !children.iter().all(|child| forest.todos[child].done)
+            {
+                break;
+            }
+            let mut todo = todo.clone();
+            todo.done = true;
+            todo.status = Status::Done;
+            todo.updated_at = Local::now();
+            self.todo_repository.save(todo.clone()).await?;
+            next = todo.parent_id;
+            forest.todos.insert(id, todo);
+        }
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
 impl<T: TodoRepository> TodoAppService for TodoAppServiceImpl<T> {
-    async fn get_all_by_user_id(&self, user_id: i32) -> Vec<Todo> {
+    async fn get_all_by_user_id(&self, user_id: i32) -> AppResult<Vec<Todo>> {
         self.todo_repository.get_all_by_user_id(user_id).await
     }
 
-    async fn get_by_id(&self, id: i32) -> Option<Todo> {
-        self.todo_repository.get_by_id(id).await
+    async fn list(&self, user_id: i32, query: TodoQuery) -> AppResult<Page<Todo>> {
+        self.todo_repository.list_by_user_id(user_id, &query).await
+    }
+
+    async fn get_by_id(&self, user_id: i32, id: i32) -> AppResult<Todo> {
+        self.todo_repository
+            .get_by_id(user_id, id)
+            .await?
+            .ok_or_else(todo_not_found)
     }
 
-    async fn create(&self, todo: Todo) -> Result<Todo> {
+    async fn create(&self, todo: Todo) -> AppResult<Todo> {
+        if todo.title.trim().is_empty() {
+            return Err(AppError::Validation("Title must not be empty".to_string()));
+        }
+        if let Some(parent_id) = todo.parent_id {
+            self.todo_repository
+                .get_by_id(todo.user_id, parent_id)
+                .await?
+                .ok_or_else(parent_not_found)?;
+        }
         self.todo_repository.create(&todo).await
     }
 
-    async fn update_status(&self, id: i32, status: Status) -> bool {
-        let mut todo = self.todo_repository.get_by_id(id).await.unwrap();
-        todo.status = status;
-        self.todo_repository.save(todo).await.unwrap()
+    async fn update(&self, user_id: i32, id: i32, update: UpdateTodo) -> AppResult<Todo> {
+        if matches!(&update.title, Some(title) if title.trim().is_empty()) {
+            return Err(AppError::Validation("Title must not be empty".to_string()));
+        }
+        let (done, auto_complete) = (update.done, update.auto_complete);
+        let todo = self
+            .modify(user_id, id, |todo| {
+                if let Some(title) = update.title {
+                    todo.title = title;
+                }
+                if let Some(description) = update.description {
+                    todo.description = description;
+                }
+                if let Some(status) = update.status {
+                    todo.status = status;
+                }
+                if let Some(priority) = update.priority {
+                    todo.priority = priority;
+                }
+                if let Some(deadline) = update.deadline {
+                    todo.deadline = deadline;
+                }
+                if let Some(done) = update.done {
+                    todo.done = done;
+                }
+                if let Some(auto_complete) = update.auto_complete {
+                    todo.auto_complete = auto_complete;
+                }
+            })
+            .await?;
+        if auto_complete == Some(true) {
+            self.roll_up(user_id, Some(id)).await?;
+            return self.get_by_id(user_id, id).await;
+        }
+        if done.is_some() {
+            self.roll_up(user_id, todo.parent_id).await?;
+        }
+        Ok(todo)
+    }
+
+    async fn update_status(&self, user_id: i32, id: i32, status: Status) -> AppResult<Todo> {
+        self.modify(user_id, id, |todo| todo.status = status).await
+    }
+
+    async fn update_priority(&self, user_id: i32, id: i32, priority: Priority) -> AppResult<Todo> {
+        self.modify(user_id, id, |todo| todo.priority = priority)
+            .await
+    }
+
+    async fn update_deadline(
+        &self,
+        user_id: i32,
+        id: i32,
+        deadline: DateTime<Local>,
+    ) -> AppResult<Todo> {
+        self.modify(user_id, id, |todo| todo.deadline = Some(deadline))
+            .await
+    }
+
+    async fn update_done(&self, user_id: i32, id: i32, done: bool) -> AppResult<Todo> {
+        let todo = self.modify(user_id, id, |todo| todo.done = done).await?;
+        self.roll_up(user_id, todo.parent_id).await?;
+        Ok(todo)
+    }
+
+    async fn get_tree(&self, user_id: i32, id: i32) -> AppResult<TodoTree> {
+        let forest = self.forest(user_id).await?;
+        if !forest.todos.contains_key(&id) {
+            return Err(todo_not_found());
+        }
+        Ok(forest.build(id).0)
+    }
+
+    async fn move_to(&self, user_id: i32, id: i32, parent_id: Option<i32>) -> AppResult<Todo> {
+        let forest = self.forest(user_id).await?;
+        let old_parent_id = forest.todos.get(&id).ok_or_else(todo_not_found)?.parent_id;
+        if let Some(parent_id) = parent_id {
+            if !forest.todos.contains_key(&parent_id) {
+                return Err(parent_not_found());
+            }
+            if parent_id == id || forest.descendants(id).contains(&parent_id) {
+                return Err(AppError::Validation(
+                    "Cannot move a todo under itself or one of its subtasks".to_string(),
+                ));
+            }
+        }
+        let todo = self
+            .modify(user_id, id, |todo| todo.parent_id = parent_id)
+            .await?;
+        // 移出最后一个未完成的子任务或移入已完成的子任务都可能使父任务完成
+        self.roll_up(user_id, old_parent_id).await?;
+        self.roll_up(user_id, parent_id).await?;
+        Ok(todo)
     }
 
-    async fn update_priority(&self, id: i32, priority: Priority) -> bool {
-        let mut todo = self.todo_repository.get_by_id(id).await.unwrap();
-        todo.priority = priority;
-        self.todo_repository.save(todo).await.unwrap()
+    async fn delete(&self, user_id: i32, id: i32) -> AppResult<()> {
+        let forest = self.forest(user_id).await?;
+        let parent_id = forest.todos.get(&id).ok_or_else(todo_not_found)?.parent_id;
+        if !self
+            .todo_repository
+            .delete(user_id, id, &forest.descendants(id))
+            .await?
+        {
+            return Err(todo_not_found());
+        }
+        self.roll_up(user_id, parent_id).await
     }
 
-    async fn update_deadline(&self, id: i32, deadline: DateTime<Local>) -> bool {
-        let mut todo = self.todo_repository.get_by_id(id).await.unwrap();
-        todo.deadline = Some(deadline);
-        self.todo_repository.save(todo).await.unwrap()
+    async fn list_trash(&self, user_id: i32) -> AppResult<Vec<Todo>> {
+        self.todo_repository.get_trashed_by_user_id(user_id).await
     }
 
-    async fn update_done(&self, id: i32, done: bool) -> bool {
-        let mut todo = self.todo_repository.get_by_id(id).await.unwrap();
-        todo.done = done;
-        self.todo_repository.save(todo).await.unwrap()
+    async fn restore(&self, user_id: i32, id: i32) -> AppResult<Todo> {
+        let trash = self.todo_repository.get_trashed_by_user_id(user_id).await?;
+        let todo = trash
+            .iter()
+            .find(|todo| todo.id == id)
+            .cloned()
+            .ok_or_else(todo_not_found)?;
+        // 只恢复与它一起删除的子任务, 之前单独删除的仍留在回收站中
+        let descendants = TodoForest::new(
+            trash
+                .into_iter()
+                .filter(|trashed| trashed.deleted_at == todo.deleted_at)
+                .collect(),
+        )
+        .descendants(id);
+        // 父任务仍在回收站中时恢复为顶层任务
+        let detach = match todo.parent_id {
+            Some(parent_id) => self
+                .todo_repository
+                .get_by_id(user_id, parent_id)
+                .await?
+                .is_none(),
+            None => false,
+        };
+        if !self
+            .todo_repository
+            .restore(user_id, id, &descendants, detach)
+            .await?
+        {
+            return Err(todo_not_found());
+        }
+        self.get_by_id(user_id, id).await
     }
 
-    async fn delete(&self, id: i32) -> bool {
-        self.todo_repository.delete(id).await
+    async fn purge_deleted_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        self.todo_repository.purge_deleted_before(cutoff).await
     }
 }
@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use chrono::Local;
+use tokio::task::JoinHandle;
+
+use crate::api::state::AppState;
+
+// 回收站清理间隔
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+// 定期物理删除软删除时间超过 retention 的 todo 和用户, 以及已过期的各类令牌
+pub fn spawn_trash_purger(state: &AppState) -> JoinHandle<()> {
+    let todo_service = state.todo_service.clone();
+    let user_service = state.user_service.clone();
+    let auth_service = state.auth_service.clone();
+    let account_service = state.account_service.clone();
+    let api_token_service = state.api_token_service.clone();
+    let retention = state.config.trash_retention;
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let cutoff = Local::now() - retention;
+            match todo_service.purge_deleted_before(cutoff).await {
+                Ok(count) => log::info!("purged {count} todos deleted before {cutoff}"),
+                Err(err) => log::error!("failed to purge deleted todos: {err}"),
+            }
+            match user_service.purge_deleted_before(cutoff).await {
+                Ok(count) => log::info!("purged {count} users deleted before {cutoff}"),
+                Err(err) => log::error!("failed to purge deleted users: {err}"),
+            }
+            match auth_service.purge_expired_before(Local::now()).await {
+                Ok(count) => log::info!("purged {count} expired refresh tokens and sessions"),
+                Err(err) => {
+                    log::error!("failed to purge expired refresh tokens and sessions: {err}")
+                }
+            }
+            match account_service.purge_expired_before(Local::now()).await {
+                Ok(count) => log::info!("purged {count} expired user tokens"),
+                Err(err) => log::error!("failed to purge expired user tokens: {err}"),
+            }
+            match api_token_service.purge_expired_before(Local::now()).await {
+                Ok(count) => log::info!("purged {count} expired or revoked API tokens"),
+                Err(err) => log::error!("failed to purge API tokens: {err}"),
+            }
+        }
+    })
+}
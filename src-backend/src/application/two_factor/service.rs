@@ -0,0 +1,393 @@
+use std::sync::Arc;
+
+use chrono::Local;
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::{
+    api::user::two_factor::{ReauthenticateRequest, TwoFactorLoginRequest},
+    application::{
+        account::service::AccountService,
+        audit::service::{user_target, AuditAction, AuditContext, AuditEntry, AuditService},
+        user::service::{login_failure_detail, UserService},
+    },
+    domain::{
+        entities::{two_factor::TotpCredential, user::User},
+        error::{AppError, AppResult},
+        repository::two_factor::TwoFactorRepository,
+    },
+    utils::{encryption::token::hash_token, totp},
+};
+
+const RECOVERY_CODE_COUNT: usize = 10;
+// 恢复码为 10 位 base32 字符, 展示时以 - 分成两组
+const RECOVERY_CODE_LEN: usize = 10;
+// 允许验证器与服务器之间相差一个时间步
+const TOTP_SKEW_STEPS: i64 = 1;
+
+#[derive(Debug, Clone)]
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct TwoFactorStatus {
+    pub enabled: bool,
+    pub recovery_codes_remaining: i64,
+}
+
+#[async_trait::async_trait]
+pub trait TwoFactorService: Send + Sync {
+    async fn status(&self, user_id: i32) -> AppResult<TwoFactorStatus>;
+    async fn is_enabled(&self, user_id: i32) -> AppResult<bool>;
+    // 生成新的密钥, 用户用验证码确认后才生效
+    async fn begin_enrollment(&self, user_id: i32) -> AppResult<TotpEnrollment>;
+    // 确认后开启两步验证, 返回恢复码明文, 只返回这一次
+    async fn confirm_enrollment(
+        &self,
+        user_id: i32,
+        code: &str,
+        ctx: &AuditContext,
+    ) -> AppResult<Vec<String>>;
+    async fn disable(
+        &self,
+        user_id: i32,
+        req: ReauthenticateRequest,
+        ctx: &AuditContext,
+    ) -> AppResult<()>;
+    async fn regenerate_recovery_codes(
+        &self,
+        user_id: i32,
+        req: ReauthenticateRequest,
+        ctx: &AuditContext,
+    ) -> AppResult<Vec<String>>;
+    // 两步登录的第二步, 验证码可以是 TOTP 或恢复码. 只记录失败, 成功在签发令牌时记录
+    async fn verify_login(&self, req: TwoFactorLoginRequest, ctx: &AuditContext)
+        -> AppResult<User>;
+}
+
+fn invalid_code() -> AppError {
+    AppError::Unauthorized("Invalid two-factor authentication code".to_string())
+}
+
+fn not_enabled() -> AppError {
+    AppError::Validation("Two-factor authentication is not enabled".to_string())
+}
+
+// 恢复码不区分大小写, 忽略分隔符和空白
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn generate_recovery_codes() -> Vec<String> {
+    let rng = SystemRandom::new();
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut bytes = [0u8; 8];
+            rng.fill(&mut bytes).unwrap();
+            let code = totp::base32_encode(&bytes).to_ascii_lowercase();
+            let (head, tail) = code[..RECOVERY_CODE_LEN].split_at(RECOVERY_CODE_LEN / 2);
+            format!("{head}-{tail}")
+        })
+        .collect()
+}
+
+pub struct TwoFactorServiceImpl<T> {
+    two_factor_repository: T,
+    user_service: Arc<dyn UserService>,
+    account_service: Arc<dyn AccountService>,
+    audit_service: Arc<dyn AuditService>,
+    // 验证器应用中显示的服务名称
+    issuer: String,
+}
+
+impl<T: TwoFactorRepository> TwoFactorServiceImpl<T> {
+    pub fn new(
+        two_factor_repository: T,
+        user_service: Arc<dyn UserService>,
+        account_service: Arc<dyn AccountService>,
+        audit_service: Arc<dyn AuditService>,
+        issuer: String,
+    ) -> Self {
+        Self {
+            two_factor_repository,
+            user_service,
+            account_service,
+            audit_service,
+            issuer,
+        }
+    }
+
+    async fn enabled_credential(&self, user_id: i32) -> AppResult<Option<TotpCredential>> {
+        Ok(self
+            .two_factor_repository
+            .get_totp(user_id)
+            .await?
+            .filter(TotpCredential::is_enabled))
+    }
+
+    // 每个时间步的验证码只能使用一次
+    async fn verify_totp(&self, credential: &TotpCredential, code: &str) -> AppResult<bool> {
+        match totp::verify(
+            &credential.secret,
+            code,
+            Local::now().timestamp(),
+            TOTP_SKEW_STEPS,
+        ) {
+            Some(step) => {
+                self.two_factor_repository
+                    .record_totp_step(credential.user_id, step)
+                    .await
+            }
+            None => Ok(false),
+        }
+    }
+
+    // 6 位数字按 TOTP 校验, 其余按恢复码校验
+    async fn verify_code(&self, credential: &TotpCredential, code: &str) -> AppResult<bool> {
+        let code = code.trim();
+        if code.len() == totp::DIGITS as usize && code.bytes().all(|b| b.is_ascii_digit()) {
+            return self.verify_totp(credential, code).await;
+        }
+        self.two_factor_repository
+            .use_recovery_code(
+                credential.user_id,
+                &hash_token(&normalize_recovery_code(code)),
+                Local::now(),
+            )
+            .await
+    }
+
+    async fn replace_recovery_codes(&self, user_id: i32) -> AppResult<Vec<String>> {
+        let codes = generate_recovery_codes();
+        let hashes: Vec<String> = codes
+            .iter()
+            .map(|code| hash_token(&normalize_recovery_code(code)))
+            .collect();
+        self.two_factor_repository
+            .replace_recovery_codes(user_id, &hashes, Local::now())
+            .await?;
+        Ok(codes)
+    }
+
+    // 重新认证失败时记录审计事件
+    async fn reauthenticate_audited(
+        &self,
+        user_id: i32,
+        req: &ReauthenticateRequest,
+        action: AuditAction,
+        ctx: &AuditContext,
+    ) -> AppResult<TotpCredential> {
+        let result = self.reauthenticate(user_id, req).await;
+        if let Err(err) = &result {
+            self.audit_service
+                .record(
+                    ctx,
+                    AuditEntry::failure(action, err.to_string()).target(user_target(user_id)),
+                )
+                .await;
+        }
+        result
+    }
+
+    // 修改两步验证设置前要求重新输入密码和验证码
+    async fn reauthenticate(
+        &self,
+        user_id: i32,
+        req: &ReauthenticateRequest,
+    ) -> AppResult<TotpCredential> {
+        let user = self
+            .user_service
+            .verify_password(user_id, &req.password)
+            .await?;
+        let credential = self
+            .enabled_credential(user_id)
+            .await?
+            .ok_or_else(not_enabled)?;
+        if !self.verify_code(&credential, &req.code).await? {
+            self.user_service.record_login_failure(&user).await?;
+            return Err(invalid_code());
+        }
+        Ok(credential)
+    }
+
+    async fn verify_second_factor(
+        &self,
+        user: &User,
+        req: &TwoFactorLoginRequest,
+    ) -> AppResult<()> {
+        // 验证码错误与密码错误共用失败计数, 防止暴力尝试
+        self.user_service.check_not_locked(user).await?;
+        let credential = self
+            .enabled_credential(user.id)
+            .await?
+            .ok_or_else(invalid_code)?;
+        if !self.verify_code(&credential, &req.code).await? {
+            self.user_service.record_login_failure(user).await?;
+            return Err(invalid_code());
+        }
+        self.user_service.reset_login_failures(user).await?;
+        self.account_service
+            .consume_login_challenge(&req.challenge_token)
+            .await?;
+        self.user_service.check_login_allowed(user).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: TwoFactorRepository> TwoFactorService for TwoFactorServiceImpl<T> {
+    async fn status(&self, user_id: i32) -> AppResult<TwoFactorStatus> {
+        let enabled = self.is_enabled(user_id).await?;
+        let recovery_codes_remaining = if enabled {
+            self.two_factor_repository
+                .count_unused_recovery_codes(user_id)
+                .await?
+        } else {
+            0
+        };
+        Ok(TwoFactorStatus {
+            enabled,
+            recovery_codes_remaining,
+        })
+    }
+
+    async fn is_enabled(&self, user_id: i32) -> AppResult<bool> {
+        Ok(self.enabled_credential(user_id).await?.is_some())
+    }
+
+    async fn begin_enrollment(&self, user_id: i32) -> AppResult<TotpEnrollment> {
+        if self.is_enabled(user_id).await? {
+            return Err(AppError::Conflict(
+                "Two-factor authentication is already enabled".to_string(),
+            ));
+        }
+        let user = self.user_service.get_user_by_id(user_id).await?;
+        let secret = totp::generate_secret();
+        self.two_factor_repository
+            .save_totp(&TotpCredential::new(user_id, secret.clone(), Local::now()))
+            .await?;
+        Ok(TotpEnrollment {
+            otpauth_uri: totp::otpauth_uri(&self.issuer, &user.email, &secret),
+            secret,
+        })
+    }
+
+    async fn confirm_enrollment(
+        &self,
+        user_id: i32,
+        code: &str,
+        ctx: &AuditContext,
+    ) -> AppResult<Vec<String>> {
+        let credential = match self.two_factor_repository.get_totp(user_id).await? {
+            Some(credential) if credential.is_enabled() => {
+                return Err(AppError::Conflict(
+                    "Two-factor authentication is already enabled".to_string(),
+                ))
+            }
+            Some(credential) => credential,
+            None => {
+                return Err(AppError::Validation(
+                    "Start two-factor enrollment first".to_string(),
+                ))
+            }
+        };
+        if !self.verify_totp(&credential, code).await? {
+            return Err(AppError::Validation(
+                "Invalid verification code".to_string(),
+            ));
+        }
+        self.two_factor_repository
+            .enable_totp(user_id, Local::now())
+            .await?;
+        let codes = self.replace_recovery_codes(user_id).await?;
+        self.audit_service
+            .record(
+                ctx,
+                AuditEntry::success(AuditAction::TwoFactorEnable).target(user_target(user_id)),
+            )
+            .await;
+        Ok(codes)
+    }
+
+    async fn disable(
+        &self,
+        user_id: i32,
+        req: ReauthenticateRequest,
+        ctx: &AuditContext,
+    ) -> AppResult<()> {
+        self.reauthenticate_audited(user_id, &req, AuditAction::TwoFactorDisable, ctx)
+            .await?;
+        self.two_factor_repository.delete(user_id).await?;
+        self.audit_service
+            .record(
+                ctx,
+                AuditEntry::success(AuditAction::TwoFactorDisable).target(user_target(user_id)),
+            )
+            .await;
+        Ok(())
+    }
+
+    async fn regenerate_recovery_codes(
+        &self,
+        user_id: i32,
+        req: ReauthenticateRequest,
+        ctx: &AuditContext,
+    ) -> AppResult<Vec<String>> {
+        self.reauthenticate_audited(user_id, &req, AuditAction::RecoveryCodesRegenerate, ctx)
+            .await?;
+        let codes = self.replace_recovery_codes(user_id).await?;
+        self.audit_service
+            .record(
+                ctx,
+                AuditEntry::success(AuditAction::RecoveryCodesRegenerate)
+                    .target(user_target(user_id)),
+            )
+            .await;
+        Ok(codes)
+    }
+
+    async fn verify_login(
+        &self,
+        req: TwoFactorLoginRequest,
+        ctx: &AuditContext,
+    ) -> AppResult<User> {
+        let user_id = self
+            .account_service
+            .check_login_challenge(&req.challenge_token)
+            .await?;
+        let user = self.user_service.get_user_by_id(user_id).await?;
+        let result = self.verify_second_factor(&user, &req).await;
+        if let Err(err) = &result {
+            self.audit_service
+                .record(
+                    ctx,
+                    AuditEntry::failure(AuditAction::LoginTwoFactor, login_failure_detail(err))
+                        .target(user_target(user_id)),
+                )
+                .await;
+        }
+        result.map(|_| user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recovery_codes_format() {
+        let codes = generate_recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        for code in &codes {
+            assert_eq!(code.len(), RECOVERY_CODE_LEN + 1);
+            assert_eq!(normalize_recovery_code(code).len(), RECOVERY_CODE_LEN);
+        }
+        assert_eq!(
+            normalize_recovery_code(" ABCDE-fghij "),
+            normalize_recovery_code("abcdefghij")
+        );
+    }
+}
@@ -1,93 +1,733 @@
-use ::anyhow::Result;
+use std::sync::{Arc, OnceLock};
+
+use chrono::{DateTime, Local};
 
 use crate::{
-    api::user::api::{CreateUserRequest, UserLoginRequest},
-    domain::{entities::user::User, repository::user::UserRepository},
-    utils::verification::verify_email,
+    api::user::{
+        api::{CreateUserRequest, UserLoginRequest},
+        me::{ChangePasswordRequest, UpdateProfileRequest},
+    },
+    application::audit::service::{
+        user_target, AuditAction, AuditContext, AuditEntry, AuditService,
+    },
+    domain::{
+        entities::user::{User, UserRole, UserStatus},
+        error::{AppError, AppResult},
+        repository::{
+            user::{UserQuery, UserRepository},
+            Page,
+        },
+    },
+    utils::{
+        encryption::{
+            password::{Argon2Hasher, PasswordHasher},
+            token::generate_token,
+        },
+        password_policy::PasswordPolicy,
+        verification::verify_email,
+    },
 };
 
 #[async_trait::async_trait]
 pub trait UserService: Send + Sync {
-    async fn register(&self, req: CreateUserRequest) -> Result<User>;
-    async fn login(&self, req: UserLoginRequest) -> Result<User>;
-    async fn get_user_by_id(&self, id: i32) -> Option<User>;
-    async fn get_user_by_email(&self, email: String) -> Option<User>;
-    async fn get_user_by_token(&self, token: String) -> Option<User>;
-    async fn update_user(&self, user: User) -> bool;
+    async fn register(&self, req: CreateUserRequest, ctx: &AuditContext) -> AppResult<User>;
+    async fn login(&self, req: UserLoginRequest, ctx: &AuditContext) -> AppResult<User>;
+    // 重新认证: 校验当前密码, 失败计入登录失败次数
+    async fn verify_password(&self, id: i32, password: &str) -> AppResult<User>;
+    // 两步验证等后续登录步骤复用密码登录的失败计数和锁定
+    async fn check_not_locked(&self, user: &User) -> AppResult<()>;
+    async fn record_login_failure(&self, user: &User) -> AppResult<()>;
+    async fn reset_login_failures(&self, user: &User) -> AppResult<()>;
+    // 账户停用或邮箱未验证 (开启 require_verified_email 时) 时拒绝登录
+    async fn check_login_allowed(&self, user: &User) -> AppResult<()>;
+    // 通过外部身份提供方首次登录时创建用户, 密码为随机值
+    async fn create_external_user(
+        &self,
+        username: String,
+        email: String,
+        email_verified: bool,
+    ) -> AppResult<User>;
+    async fn get_user_by_id(&self, id: i32) -> AppResult<User>;
+    async fn get_user_by_email(&self, email: String) -> AppResult<User>;
+    async fn update_user(&self, user: User) -> AppResult<()>;
+    async fn check_password_policy(&self, password: &str) -> AppResult<()>;
+    // 按密码策略校验后设置新密码, 同时解除登录失败锁定
+    async fn set_password(&self, id: i32, password: &str) -> AppResult<()>;
+    // 修改用户名或邮箱, 修改邮箱后需要重新验证
+    async fn update_profile(
+        &self,
+        id: i32,
+        req: UpdateProfileRequest,
+        ctx: &AuditContext,
+    ) -> AppResult<User>;
+    async fn change_password(
+        &self,
+        id: i32,
+        req: ChangePasswordRequest,
+        ctx: &AuditContext,
+    ) -> AppResult<()>;
+    // 校验密码后软删除账户
+    async fn delete_account(&self, id: i32, password: &str, ctx: &AuditContext) -> AppResult<()>;
+    async fn search_users(&self, query: UserQuery) -> AppResult<Page<User>>;
+    async fn set_role(&self, id: i32, role: UserRole, ctx: &AuditContext) -> AppResult<User>;
+    async fn set_status(&self, id: i32, status: UserStatus, ctx: &AuditContext) -> AppResult<User>;
+    // 将密码替换为随机值, 用户只能通过重置密码重新登录
+    async fn invalidate_password(&self, id: i32) -> AppResult<()>;
+    async fn purge_deleted_before(&self, cutoff: DateTime<Local>) -> AppResult<u64>;
+}
+
+const MAX_USERNAME_LEN: usize = 255;
+
+fn user_not_found() -> AppError {
+    AppError::NotFound("User not found".to_string())
+}
+
+// 已删除但尚未清理的账户仍占用邮箱的唯一索引, 冲突时给出明确的提示
+fn email_conflict(err: AppError) -> AppError {
+    match err {
+        AppError::Conflict(_) => AppError::Conflict(
+            "Email belongs to a deleted account and can be reused after it is purged".to_string(),
+        ),
+        err => err,
+    }
+}
+
+fn invalid_credentials() -> AppError {
+    AppError::Unauthorized("Invalid email or password".to_string())
+}
+
+// 审计日志中记录的登录失败原因
+pub fn login_failure_detail(err: &AppError) -> String {
+    match err {
+        AppError::Unauthorized(_) => "invalid credentials".to_string(),
+        AppError::AccountLocked { .. } => "account locked".to_string(),
+        AppError::AccountSuspended => "account suspended".to_string(),
+        AppError::EmailNotVerified => "email not verified".to_string(),
+        err => err.to_string(),
+    }
+}
+
+// 连续登录失败 max_attempts 次后锁定 duration, max_attempts 为 0 时不锁定
+#[derive(Debug, Clone, Copy)]
+pub struct LockoutPolicy {
+    pub max_attempts: u32,
+    pub duration: chrono::Duration,
+}
+
+impl Default for LockoutPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            duration: chrono::Duration::minutes(15),
+        }
+    }
 }
 
 pub struct UserServiceImpl<T> {
     user_repository: T,
+    hasher: Arc<dyn PasswordHasher>,
+    password_policy: PasswordPolicy,
+    lockout: LockoutPolicy,
+    require_verified_email: bool,
+    audit_service: Option<Arc<dyn AuditService>>,
 }
 
 impl<T: UserRepository> UserServiceImpl<T> {
     pub fn new(user_repository: T) -> Self {
-        Self { user_repository }
+        Self {
+            user_repository,
+            hasher: Arc::new(Argon2Hasher::default()),
+            password_policy: PasswordPolicy::default(),
+            lockout: LockoutPolicy::default(),
+            require_verified_email: false,
+            audit_service: None,
+        }
+    }
+
+    pub fn with_hasher(mut self, hasher: Arc<dyn PasswordHasher>) -> Self {
+        self.hasher = hasher;
+        self
+    }
+
+    pub fn with_password_policy(mut self, password_policy: PasswordPolicy) -> Self {
+        self.password_policy = password_policy;
+        self
+    }
+
+    pub fn with_lockout(mut self, lockout: LockoutPolicy) -> Self {
+        self.lockout = lockout;
+        self
+    }
+
+    pub fn with_require_verified_email(mut self, require_verified_email: bool) -> Self {
+        self.require_verified_email = require_verified_email;
+        self
+    }
+
+    pub fn with_audit(mut self, audit_service: Arc<dyn AuditService>) -> Self {
+        self.audit_service = Some(audit_service);
+        self
+    }
+
+    async fn audit(&self, ctx: &AuditContext, entry: AuditEntry) {
+        if let Some(audit_service) = &self.audit_service {
+            audit_service.record(ctx, entry).await;
+        }
+    }
+
+    // 校验用户密码并维护失败计数, 账户锁定期间直接返回 AccountLocked
+    async fn verify_credentials(&self, user: &mut User, password: &str) -> AppResult<bool> {
+        self.check_not_locked(user).await?;
+
+        if !self.hasher.verify_password(password, &user.password) {
+            self.record_login_failure(user).await?;
+            return Ok(false);
+        }
+
+        self.reset_login_failures(user).await?;
+        user.failed_login_attempts = 0;
+        user.locked_until = None;
+        Ok(true)
+    }
+
+    async fn verify_current_password(&self, id: i32, password: &str) -> AppResult<User> {
+        let mut user = self.get_user_by_id(id).await?;
+        if !self.verify_credentials(&mut user, password).await? {
+            return Err(AppError::Validation(
+                "Current password is incorrect".to_string(),
+            ));
+        }
+        Ok(user)
+    }
+
+    // 邮箱不存在时也校验一次哈希, 使响应时间与密码错误时一致
+    fn verify_dummy_password(&self, password: &str) {
+        static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+        let hash = DUMMY_HASH.get_or_init(|| {
+            self.hasher
+                .hash_password("dummy-password")
+                .unwrap_or_default()
+        });
+        self.hasher.verify_password(password, hash);
     }
 }
 
 #[async_trait::async_trait]
 impl<T: UserRepository> UserService for UserServiceImpl<T> {
-    async fn register(&self, req: CreateUserRequest) -> Result<User> {
-        if verify_email(&req.email) {
-            return Err(anyhow::anyhow!("email not valid"));
+    async fn register(&self, req: CreateUserRequest, ctx: &AuditContext) -> AppResult<User> {
+        if !verify_email(&req.email) {
+            return Err(AppError::Validation("Email is not valid".to_string()));
         }
 
         if req.password != req.password_confirmation {
-            return Err(anyhow::anyhow!("password not match"));
+            return Err(AppError::Validation("Passwords do not match".to_string()));
         }
 
-        let hasher = crate::utils::encryption::password::PasswordHasher::new();
-        let (password_hash, salt) = hasher.hash_password(req.password.as_str())?;
+        self.check_password_policy(&req.password).await?;
 
-        self.user_repository
+        if self
+            .user_repository
+            .get_by_email(req.email.clone())
+            .await?
+            .is_some()
+        {
+            return Err(AppError::Conflict("Email already registered".to_string()));
+        }
+
+        let password_hash = self.hasher.hash_password(req.password.as_str())?;
+
+        let user = self
+            .user_repository
             .create(&User::new(
                 req.username,
                 password_hash,
                 req.email,
-                salt,
                 chrono::Local::now(),
                 chrono::Local::now(),
                 None,
             ))
             .await
+            .map_err(email_conflict)?;
+        self.audit(
+            ctx,
+            AuditEntry::success(AuditAction::Register)
+                .actor(user.id)
+                .target(user_target(user.id)),
+        )
+        .await;
+        Ok(user)
     }
-    async fn login(&self, req: UserLoginRequest) -> Result<User> {
-        if verify_email(&req.email) {
-            return Err(anyhow::anyhow!("email not valid"));
+    // 只记录失败的登录, 成功的登录在签发令牌时记录
+    async fn login(&self, req: UserLoginRequest, ctx: &AuditContext) -> AppResult<User> {
+        if !verify_email(&req.email) {
+            return Err(AppError::Validation("Email is not valid".to_string()));
         }
 
-        let user = self
+        let Some(mut user) = self.user_repository.get_by_email(req.email.clone()).await? else {
+            self.verify_dummy_password(req.password.as_str());
+            self.audit(
+                ctx,
+                AuditEntry::failure(AuditAction::Login, "unknown email"),
+            )
+            .await;
+            return Err(invalid_credentials());
+        };
+
+        let checked = match self
+            .verify_credentials(&mut user, req.password.as_str())
+            .await
+        {
+            Ok(true) => self.check_login_allowed(&user).await,
+            Ok(false) => Err(invalid_credentials()),
+            Err(err) => Err(err),
+        };
+        if let Err(err) = checked {
+            self.audit(
+                ctx,
+                AuditEntry::failure(AuditAction::Login, login_failure_detail(&err))
+                    .target(user_target(user.id)),
+            )
+            .await;
+            return Err(err);
+        }
+
+        // 哈希算法或参数过时的用户在登录成功时透明地升级, 失败不影响本次登录
+        if self.hasher.needs_rehash(&user.password) {
+            match self.hasher.hash_password(req.password.as_str()) {
+                Ok(password_hash) => {
+                    user.password = password_hash;
+                    if let Err(err) = self.user_repository.save(user.clone()).await {
+                        log::warn!("failed to rehash password for user {}: {err}", user.id);
+                    }
+                }
+                Err(err) => log::warn!("failed to rehash password for user {}: {err}", user.id),
+            }
+        }
+        Ok(user)
+    }
+    async fn verify_password(&self, id: i32, password: &str) -> AppResult<User> {
+        self.verify_current_password(id, password).await
+    }
+    async fn check_not_locked(&self, user: &User) -> AppResult<()> {
+        let now = Local::now();
+        if let Some(locked_until) = user.locked_until.filter(|until| *until > now) {
+            let retry_after = (locked_until - now).num_seconds().max(1) as u64;
+            return Err(AppError::AccountLocked { retry_after });
+        }
+        Ok(())
+    }
+    async fn record_login_failure(&self, user: &User) -> AppResult<()> {
+        if self.lockout.max_attempts > 0 {
+            self.user_repository
+                .record_login_failure(
+                    user.id,
+                    self.lockout.max_attempts,
+                    Local::now() + self.lockout.duration,
+                )
+                .await?;
+        }
+        Ok(())
+    }
+    async fn reset_login_failures(&self, user: &User) -> AppResult<()> {
+        if user.failed_login_attempts != 0 || user.locked_until.is_some() {
+            self.user_repository.reset_login_failures(user.id).await?;
+        }
+        Ok(())
+    }
+    async fn check_login_allowed(&self, user: &User) -> AppResult<()> {
+        if user.is_suspended() {
+            return Err(AppError::AccountSuspended);
+        }
+        if self.require_verified_email && user.email_verified_at.is_none() {
+            return Err(AppError::EmailNotVerified);
+        }
+        Ok(())
+    }
+    async fn create_external_user(
+        &self,
+        username: String,
+        email: String,
+        email_verified: bool,
+    ) -> AppResult<User> {
+        if !verify_email(&email) {
+            return Err(AppError::Validation("Email is not valid".to_string()));
+        }
+        if self
             .user_repository
-            .get_by_email(req.email.clone())
+            .get_by_email(email.clone())
+            .await?
+            .is_some()
+        {
+            return Err(AppError::Conflict("Email already registered".to_string()));
+        }
+        let username: String = username.trim().chars().take(MAX_USERNAME_LEN).collect();
+        let now = Local::now();
+        let mut user = self
+            .user_repository
+            .create(&User::new(
+                username,
+                self.hasher.hash_password(&generate_token())?,
+                email,
+                now,
+                now,
+                None,
+            ))
             .await
-            .ok_or(anyhow::anyhow!("user not found"))?;
-        let hasher = crate::utils::encryption::password::PasswordHasher::new();
-        let is_valid =
-            hasher.verify_password(req.password.as_str(), &user.salt, user.password.as_str());
-        if !is_valid {
-            return Err(anyhow::anyhow!("password not match"));
+            .map_err(email_conflict)?;
+        if email_verified {
+            user.email_verified_at = Some(now);
+            self.user_repository.save(user.clone()).await?;
         }
         Ok(user)
     }
-    async fn get_user_by_id(&self, id: i32) -> Option<User> {
-        self.user_repository.get_by_id(id).await
+    async fn get_user_by_id(&self, id: i32) -> AppResult<User> {
+        self.user_repository
+            .get_by_id(id)
+            .await?
+            .ok_or_else(user_not_found)
+    }
+    async fn get_user_by_email(&self, email: String) -> AppResult<User> {
+        self.user_repository
+            .get_by_email(email)
+            .await?
+            .ok_or_else(user_not_found)
+    }
+    async fn update_user(&self, user: User) -> AppResult<()> {
+        self.user_repository.save(user).await
+    }
+    async fn check_password_policy(&self, password: &str) -> AppResult<()> {
+        self.password_policy
+            .check(password)
+            .map_err(AppError::WeakPassword)
     }
-    async fn get_user_by_email(&self, email: String) -> Option<User> {
-        self.user_repository.get_by_email(email).await
+    async fn set_password(&self, id: i32, password: &str) -> AppResult<()> {
+        self.check_password_policy(password).await?;
+        let mut user = self.get_user_by_id(id).await?;
+        user.password = self.hasher.hash_password(password)?;
+        user.updated_at = Local::now();
+        self.user_repository.save(user).await?;
+        self.user_repository.reset_login_failures(id).await
     }
-    async fn get_user_by_token(&self, token: String) -> Option<User> {
-        let res = crate::utils::jwt::verify_token(token.as_str()).unwrap();
-        if res.exp < chrono::Local::now().timestamp() as usize {
-            return None;
-        } else {
-            return self
+    async fn update_profile(
+        &self,
+        id: i32,
+        req: UpdateProfileRequest,
+        ctx: &AuditContext,
+    ) -> AppResult<User> {
+        let mut user = self.get_user_by_id(id).await?;
+        let previous_email = user.email.clone();
+        if let Some(username) = req.username {
+            let username = username.trim();
+            if username.is_empty() || username.chars().count() > MAX_USERNAME_LEN {
+                return Err(AppError::Validation(format!(
+                    "Username must be between 1 and {MAX_USERNAME_LEN} characters"
+                )));
+            }
+            user.username = username.to_string();
+        }
+        if let Some(email) = req.email.filter(|email| *email != user.email) {
+            if !verify_email(&email) {
+                return Err(AppError::Validation("Email is not valid".to_string()));
+            }
+            if self
                 .user_repository
-                .get_by_id(res.sub.parse::<i32>().unwrap())
-                .await;
+                .get_by_email(email.clone())
+                .await?
+                .is_some()
+            {
+                return Err(AppError::Conflict("Email already registered".to_string()));
+            }
+            user.email = email;
+            user.email_verified_at = None;
+        }
+        user.updated_at = Local::now();
+        self.user_repository
+            .save(user.clone())
+            .await
+            .map_err(email_conflict)?;
+        if user.email != previous_email {
+            self.audit(
+                ctx,
+                AuditEntry::success(AuditAction::EmailChange)
+                    .target(user_target(id))
+                    .detail(format!("{previous_email} -> {}", user.email)),
+            )
+            .await;
+        }
+        Ok(user)
+    }
+    async fn change_password(
+        &self,
+        id: i32,
+        req: ChangePasswordRequest,
+        ctx: &AuditContext,
+    ) -> AppResult<()> {
+        if req.password != req.password_confirmation {
+            return Err(AppError::Validation("Passwords do not match".to_string()));
+        }
+        self.check_password_policy(&req.password).await?;
+        if let Err(err) = self
+            .verify_current_password(id, &req.current_password)
+            .await
+        {
+            self.audit(
+                ctx,
+                AuditEntry::failure(AuditAction::PasswordChange, err.to_string())
+                    .target(user_target(id)),
+            )
+            .await;
+            return Err(err);
+        }
+        self.set_password(id, &req.password).await?;
+        self.audit(
+            ctx,
+            AuditEntry::success(AuditAction::PasswordChange).target(user_target(id)),
+        )
+        .await;
+        Ok(())
+    }
+    async fn delete_account(&self, id: i32, password: &str, ctx: &AuditContext) -> AppResult<()> {
+        if let Err(err) = self.verify_current_password(id, password).await {
+            self.audit(
+                ctx,
+                AuditEntry::failure(AuditAction::AccountDelete, err.to_string())
+                    .target(user_target(id)),
+            )
+            .await;
+            return Err(err);
+        }
+        if !self.user_repository.delete(id).await? {
+            return Err(user_not_found());
+        }
+        self.audit(
+            ctx,
+            AuditEntry::success(AuditAction::AccountDelete).target(user_target(id)),
+        )
+        .await;
+        Ok(())
+    }
+    async fn search_users(&self, query: UserQuery) -> AppResult<Page<User>> {
+        self.user_repository.search(&query).await
+    }
+    async fn set_role(&self, id: i32, role: UserRole, ctx: &AuditContext) -> AppResult<User> {
+        let mut user = self.get_user_by_id(id).await?;
+        if user.role != role {
+            let detail = format!("{:?} -> {role:?}", user.role).to_lowercase();
+            user.role = role;
+            user.updated_at = Local::now();
+            self.user_repository.save(user.clone()).await?;
+            self.audit(
+                ctx,
+                AuditEntry::success(AuditAction::RoleChange)
+                    .target(user_target(id))
+                    .detail(detail),
+            )
+            .await;
         }
+        Ok(user)
+    }
+    async fn set_status(&self, id: i32, status: UserStatus, ctx: &AuditContext) -> AppResult<User> {
+        let mut user = self.get_user_by_id(id).await?;
+        if user.status != status {
+            let detail = format!("{:?} -> {status:?}", user.status).to_lowercase();
+            user.status = status;
+            user.updated_at = Local::now();
+            self.user_repository.save(user.clone()).await?;
+            self.audit(
+                ctx,
+                AuditEntry::success(AuditAction::StatusChange)
+                    .target(user_target(id))
+                    .detail(detail),
+            )
+            .await;
+        }
+        Ok(user)
     }
-    async fn update_user(&self, user: User) -> bool {
+    async fn invalidate_password(&self, id: i32) -> AppResult<()> {
+        let mut user = self.get_user_by_id(id).await?;
+        user.password = self.hasher.hash_password(&generate_token())?;
+        user.updated_at = Local::now();
         self.user_repository.save(user).await
     }
+    async fn purge_deleted_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        self.user_repository.purge_deleted_before(cutoff).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infastructure::db::user::memory::InMemoryUserRepository;
+
+    // 与迁移脚本生成的旧格式一致, 密码为 password123
+    const LEGACY_HASH: &str = "$pbkdf2-sha256-hex$i=101101$07070707070707070707070707070707$";
+
+    fn legacy_hash() -> String {
+        let mut hash = [0u8; 32];
+        ring::pbkdf2::derive(
+            ring::pbkdf2::PBKDF2_HMAC_SHA256,
+            std::num::NonZeroU32::new(101_101).unwrap(),
+            &[7u8; 16],
+            b"password123",
+            &mut hash,
+        );
+        format!("{LEGACY_HASH}{}", hex::encode(hash))
+    }
+
+    #[tokio::test]
+    async fn test_login_rehashes_legacy_password() {
+        let repository = InMemoryUserRepository::new();
+        let user = repository
+            .create(&User::new(
+                "legacy".to_string(),
+                legacy_hash(),
+                "legacy@example.com".to_string(),
+                Local::now(),
+                Local::now(),
+                None,
+            ))
+            .await
+            .unwrap();
+        let service = UserServiceImpl::new(repository);
+
+        let login = |password: &str| UserLoginRequest {
+            email: "legacy@example.com".to_string(),
+            password: password.to_string(),
+            device_name: None,
+        };
+        assert!(service
+            .login(login("wrong-password"), &AuditContext::system())
+            .await
+            .is_err());
+        service
+            .login(login("password123"), &AuditContext::system())
+            .await
+            .unwrap();
+
+        let stored = service.get_user_by_id(user.id).await.unwrap();
+        assert!(stored.password.starts_with("$argon2id$"));
+        service
+            .login(login("password123"), &AuditContext::system())
+            .await
+            .unwrap();
+    }
+
+    fn create_request(password: &str) -> CreateUserRequest {
+        CreateUserRequest {
+            email: "policy@example.com".to_string(),
+            username: "policy".to_string(),
+            password: password.to_string(),
+            password_confirmation: password.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_enforces_password_policy() {
+        let service = UserServiceImpl::new(InMemoryUserRepository::new()).with_password_policy(
+            PasswordPolicy {
+                require_digit: true,
+                ..PasswordPolicy::default()
+            },
+        );
+        for password in ["short1", "no-digits-here", "password123"] {
+            assert!(matches!(
+                service
+                    .register(create_request(password), &AuditContext::system())
+                    .await,
+                Err(AppError::WeakPassword(_))
+            ));
+        }
+        service
+            .register(
+                create_request("correct-horse-battery-9"),
+                &AuditContext::system(),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_login_locks_account_after_failures() {
+        // 锁定时间很短, 测试中等待锁定自然过期
+        let service =
+            UserServiceImpl::new(InMemoryUserRepository::new()).with_lockout(LockoutPolicy {
+                max_attempts: 3,
+                duration: chrono::Duration::milliseconds(300),
+            });
+        let user_id = service
+            .register(
+                create_request("correct-horse-battery"),
+                &AuditContext::system(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let login = |password: &str| UserLoginRequest {
+            email: "policy@example.com".to_string(),
+            password: password.to_string(),
+            device_name: None,
+        };
+
+        // 成功登录会清零失败计数
+        for _ in 0..2 {
+            assert!(service
+                .login(login("wrong-password"), &AuditContext::system())
+                .await
+                .is_err());
+        }
+        service
+            .login(login("correct-horse-battery"), &AuditContext::system())
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            assert!(matches!(
+                service
+                    .login(login("wrong-password"), &AuditContext::system())
+                    .await,
+                Err(AppError::Unauthorized(_))
+            ));
+        }
+        assert!(service
+            .login(login("wrong-password"), &AuditContext::system())
+            .await
+            .is_err());
+
+        // 锁定期间正确的密码也会被拒绝
+        match service
+            .login(login("correct-horse-battery"), &AuditContext::system())
+            .await
+        {
+            Err(AppError::AccountLocked { retry_after }) => assert_eq!(retry_after, 1),
+            other => panic!(
+                "expected AccountLocked, got {:?}",
+                other.map(|user| user.id)
+            ),
+        }
+
+        // 保存用户资料不会解除锁定
+        let stored = service.get_user_by_id(user_id).await.unwrap();
+        assert!(stored.locked_until.is_some());
+        service
+            .update_user(User {
+                locked_until: None,
+                ..stored
+            })
+            .await
+            .unwrap();
+        assert!(matches!(
+            service
+                .login(login("correct-horse-battery"), &AuditContext::system())
+                .await,
+            Err(AppError::AccountLocked { .. })
+        ));
+
+        // 锁定过期后可以正常登录
+        tokio::time::sleep(std::time::Duration::from_millis(400)).await;
+        let user = service
+            .login(login("correct-horse-battery"), &AuditContext::system())
+            .await
+            .unwrap();
+        assert_eq!(user.failed_login_attempts, 0);
+        assert!(user.locked_until.is_none());
+    }
 }
@@ -0,0 +1,90 @@
+use chrono::{DateTime, Local};
+use sqlx::FromRow;
+
+// API 令牌可以访问的资源范围, todo:write 包含 todo:read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ApiScope {
+    #[serde(rename = "todo:read")]
+    TodoRead,
+    #[serde(rename = "todo:write")]
+    TodoWrite,
+}
+
+impl ApiScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiScope::TodoRead => "todo:read",
+            ApiScope::TodoWrite => "todo:write",
+        }
+    }
+
+    pub fn parse(scope: &str) -> Option<Self> {
+        match scope {
+            "todo:read" => Some(ApiScope::TodoRead),
+            "todo:write" => Some(ApiScope::TodoWrite),
+            _ => None,
+        }
+    }
+
+    pub fn grants(&self, required: ApiScope) -> bool {
+        *self == required || (*self == ApiScope::TodoWrite && required == ApiScope::TodoRead)
+    }
+}
+
+// 用户为脚本和集成创建的长期令牌, 数据库中只保存令牌的哈希值
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, FromRow)]
+pub struct ApiToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    // 令牌明文的前几位, 便于用户在列表中辨认
+    pub token_prefix: String,
+    pub token_hash: String,
+    // 以空格分隔的 scope 列表
+    pub scopes: String,
+    pub created_at: DateTime<Local>,
+    pub last_used_at: Option<DateTime<Local>>,
+    pub expires_at: Option<DateTime<Local>>,
+    pub revoked_at: Option<DateTime<Local>>,
+}
+
+impl ApiToken {
+    pub fn new(
+        user_id: i32,
+        name: String,
+        token_prefix: String,
+        token_hash: String,
+        scopes: &[ApiScope],
+        created_at: DateTime<Local>,
+        expires_at: Option<DateTime<Local>>,
+    ) -> Self {
+        Self {
+            id: 0,
+            user_id,
+            name,
+            token_prefix,
+            token_hash,
+            scopes: scopes
+                .iter()
+                .map(ApiScope::as_str)
+                .collect::<Vec<_>>()
+                .join(" "),
+            created_at,
+            last_used_at: None,
+            expires_at,
+            revoked_at: None,
+        }
+    }
+
+    // 忽略无法识别的 scope
+    pub fn scopes(&self) -> Vec<ApiScope> {
+        self.scopes
+            .split_whitespace()
+            .filter_map(ApiScope::parse)
+            .collect()
+    }
+
+    pub fn is_active(&self, now: DateTime<Local>) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
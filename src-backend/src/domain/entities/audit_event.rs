@@ -0,0 +1,28 @@
+use chrono::{DateTime, Local};
+use sqlx::{FromRow, Type};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, Type)]
+#[serde(rename_all = "lowercase")]
+#[repr(i16)]
+pub enum AuditOutcome {
+    Success = 0,
+    Failure = 1,
+}
+
+// 安全审计事件, 写入后不再修改
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, FromRow)]
+pub struct AuditEvent {
+    pub id: i64,
+    // 发起操作的用户, 未登录的请求 (如登录失败) 或命令行操作为空
+    pub actor_id: Option<i32>,
+    // 形如 auth.login, 见 AuditAction
+    pub action: String,
+    // 操作对象, 形如 user:42 或 session:7
+    pub target: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub outcome: AuditOutcome,
+    // 失败原因等补充说明
+    pub detail: Option<String>,
+    pub created_at: DateTime<Local>,
+}
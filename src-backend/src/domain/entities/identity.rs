@@ -0,0 +1,35 @@
+use chrono::{DateTime, Local};
+use sqlx::FromRow;
+
+// 外部身份提供方的账户, 以 (issuer, subject) 关联到本地用户
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, FromRow)]
+pub struct Identity {
+    pub id: i32,
+    pub user_id: i32,
+    pub issuer: String,
+    pub subject: String,
+    // 关联时身份提供方返回的邮箱, 仅用于展示
+    pub email: Option<String>,
+    pub created_at: DateTime<Local>,
+    pub last_login_at: Option<DateTime<Local>>,
+}
+
+impl Identity {
+    pub fn new(
+        user_id: i32,
+        issuer: String,
+        subject: String,
+        email: Option<String>,
+        created_at: DateTime<Local>,
+    ) -> Self {
+        Self {
+            id: 0,
+            user_id,
+            issuer,
+            subject,
+            email,
+            created_at,
+            last_login_at: Some(created_at),
+        }
+    }
+}
@@ -1,2 +1,10 @@
+pub mod api_token;
+pub mod audit_event;
+pub mod identity;
+pub mod refresh_token;
+pub mod session;
+pub mod tag;
 pub mod todo;
+pub mod two_factor;
 pub mod user;
+pub mod user_token;
@@ -0,0 +1,37 @@
+use chrono::{DateTime, Local};
+use sqlx::FromRow;
+
+// 刷新令牌, 数据库中只保存令牌的哈希值
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, FromRow)]
+pub struct RefreshToken {
+    pub id: i32,
+    pub user_id: i32,
+    // 同一次登录轮换出的令牌属于同一个 family, 检测到重放时整体吊销
+    pub family_id: String,
+    pub token_hash: String,
+    pub created_at: DateTime<Local>,
+    pub expires_at: DateTime<Local>,
+    pub used_at: Option<DateTime<Local>>,
+    pub revoked_at: Option<DateTime<Local>>,
+}
+
+impl RefreshToken {
+    pub fn new(
+        user_id: i32,
+        family_id: String,
+        token_hash: String,
+        created_at: DateTime<Local>,
+        expires_at: DateTime<Local>,
+    ) -> Self {
+        Self {
+            id: 0,
+            user_id,
+            family_id,
+            token_hash,
+            created_at,
+            expires_at,
+            used_at: None,
+            revoked_at: None,
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Local};
+use sqlx::FromRow;
+
+// 一次登录对应的会话, 与刷新令牌的 family 一一对应
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, FromRow)]
+pub struct Session {
+    pub id: i32,
+    pub user_id: i32,
+    pub family_id: String,
+    // 客户端登录时提交的设备名称
+    pub device_name: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: String,
+    pub created_at: DateTime<Local>,
+    pub last_active_at: DateTime<Local>,
+    // 随刷新令牌轮换延长, 与最新刷新令牌的过期时间一致
+    pub expires_at: DateTime<Local>,
+    pub revoked_at: Option<DateTime<Local>>,
+}
+
+impl Session {
+    pub fn new(
+        user_id: i32,
+        family_id: String,
+        device_name: Option<String>,
+        user_agent: Option<String>,
+        ip_address: String,
+        created_at: DateTime<Local>,
+        expires_at: DateTime<Local>,
+    ) -> Self {
+        Self {
+            id: 0,
+            user_id,
+            family_id,
+            device_name,
+            user_agent,
+            ip_address,
+            created_at,
+            last_active_at: created_at,
+            expires_at,
+            revoked_at: None,
+        }
+    }
+
+    pub fn is_active(&self, now: DateTime<Local>) -> bool {
+        self.revoked_at.is_none() && self.expires_at > now
+    }
+}
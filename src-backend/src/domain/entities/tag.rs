@@ -0,0 +1,27 @@
+use chrono::{DateTime, Local};
+use sqlx::FromRow;
+
+// 用户自定义的标签, 名称在同一用户内唯一
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, FromRow)]
+pub struct Tag {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    // #rrggbb 形式的颜色
+    pub color: String,
+    pub created_at: DateTime<Local>,
+    pub updated_at: DateTime<Local>,
+}
+
+impl Tag {
+    pub fn new(user_id: i32, name: String, color: String, created_at: DateTime<Local>) -> Self {
+        Self {
+            id: 0,
+            user_id,
+            name,
+            color,
+            created_at,
+            updated_at: created_at,
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use chrono::{DateTime, Local};
-use sqlx::{Decode, Encode, FromRow, Row, Type};
+use sqlx::{Encode, Row, Type};
 
 #[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, Type)]
 #[repr(i16)]
@@ -30,9 +30,14 @@ pub struct Todo {
     pub deleted_at: Option<DateTime<Local>>,
     pub deadline: Option<DateTime<Local>>,
     pub done: bool,
+    // 父任务, 为空时是顶层任务
+    pub parent_id: Option<i32>,
+    // 所有子任务完成时自动完成该任务
+    pub auto_complete: bool,
 }
 
 impl Todo {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         user_id: i32,
         title: String,
@@ -57,20 +62,22 @@ impl Todo {
             deleted_at,
             deadline,
             done,
+            parent_id: None,
+            auto_complete: false,
         }
     }
 }
 
 impl<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> for Todo {
     fn from_row(row: &'r sqlx::postgres::PgRow) -> Result<Self, sqlx::Error> {
-        let status: i32 = row.try_get("status")?;
+        let status: i16 = row.try_get("status")?;
         let status_enum = match status {
             1 => Status::Open,
             2 => Status::InProgress,
             3 => Status::Done,
             _ => Status::Open,
         };
-        let priority: i32 = row.try_get("priority")?;
+        let priority: i16 = row.try_get("priority")?;
         let priority_enum = match priority {
             1 => Priority::Low,
             2 => Priority::Medium,
@@ -90,6 +97,8 @@ impl<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> for Todo {
             deleted_at: row.try_get("deleted_at")?,
             deadline: row.try_get("deadline")?,
             done: row.try_get("done")?,
+            parent_id: row.try_get("parent_id")?,
+            auto_complete: row.try_get("auto_complete")?,
         })
     }
 }
@@ -123,6 +132,43 @@ impl<'r> sqlx::FromRow<'r, sqlx::mysql::MySqlRow> for Todo {
             deleted_at: row.try_get("deleted_at")?,
             deadline: row.try_get("deadline")?,
             done: row.try_get("done")?,
+            parent_id: row.try_get("parent_id")?,
+            auto_complete: row.try_get("auto_complete")?,
+        })
+    }
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for Todo {
+    fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        let status: i32 = row.try_get("status")?;
+        let status_enum = match status {
+            1 => Status::Open,
+            2 => Status::InProgress,
+            3 => Status::Done,
+            _ => Status::Open,
+        };
+        let priority: i32 = row.try_get("priority")?;
+        let priority_enum = match priority {
+            1 => Priority::Low,
+            2 => Priority::Medium,
+            3 => Priority::High,
+            _ => Priority::Low,
+        };
+
+        Ok(Self {
+            id: row.try_get("id")?,
+            user_id: row.try_get("user_id")?,
+            title: row.try_get("title")?,
+            description: row.try_get("description")?,
+            status: status_enum,
+            priority: priority_enum,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+            deleted_at: row.try_get("deleted_at")?,
+            deadline: row.try_get("deadline")?,
+            done: row.try_get("done")?,
+            parent_id: row.try_get("parent_id")?,
+            auto_complete: row.try_get("auto_complete")?,
         })
     }
 }
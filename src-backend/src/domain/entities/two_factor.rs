@@ -0,0 +1,40 @@
+use chrono::{DateTime, Local};
+use sqlx::FromRow;
+
+// 用户的 TOTP 密钥, 每个用户最多一条
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, FromRow)]
+pub struct TotpCredential {
+    pub user_id: i32,
+    // base32 编码, 验证时需要原文, 因此不能只保存哈希
+    pub secret: String,
+    pub created_at: DateTime<Local>,
+    // 为空表示用户尚未用验证码确认, 此时登录不要求两步验证
+    pub enabled_at: Option<DateTime<Local>>,
+    pub last_used_step: Option<i64>,
+}
+
+impl TotpCredential {
+    pub fn new(user_id: i32, secret: String, created_at: DateTime<Local>) -> Self {
+        Self {
+            user_id,
+            secret,
+            created_at,
+            enabled_at: None,
+            last_used_step: None,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled_at.is_some()
+    }
+}
+
+// 丢失验证器时使用的一次性恢复码
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, FromRow)]
+pub struct RecoveryCode {
+    pub id: i32,
+    pub user_id: i32,
+    pub code_hash: String,
+    pub created_at: DateTime<Local>,
+    pub used_at: Option<DateTime<Local>>,
+}
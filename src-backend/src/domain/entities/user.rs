@@ -1,16 +1,45 @@
 use chrono::{DateTime, Local};
-use sqlx::{Encode, FromRow};
+use sqlx::{Encode, FromRow, Type};
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize, Type,
+)]
+#[serde(rename_all = "lowercase")]
+#[repr(i16)]
+pub enum UserRole {
+    #[default]
+    User = 0,
+    Admin = 1,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize, Type,
+)]
+#[serde(rename_all = "lowercase")]
+#[repr(i16)]
+pub enum UserStatus {
+    #[default]
+    Active = 0,
+    // 停用的账户无法登录, 已签发的令牌也不再被接受
+    Suspended = 1,
+}
 
 #[derive(Debug, Clone, Encode, serde::Serialize, serde::Deserialize, FromRow)]
 pub struct User {
     pub id: i32,
     pub username: String,
     pub email: String,
+    // PHC 格式的密码哈希
     pub password: String,
-    pub salt: String,
     pub created_at: DateTime<Local>,
     pub updated_at: DateTime<Local>,
     pub deleted_at: Option<DateTime<Local>>,
+    // 连续登录失败次数, 达到上限后锁定到 locked_until
+    pub failed_login_attempts: i32,
+    pub locked_until: Option<DateTime<Local>>,
+    pub email_verified_at: Option<DateTime<Local>>,
+    pub role: UserRole,
+    pub status: UserStatus,
 }
 
 impl User {
@@ -18,7 +47,6 @@ impl User {
         username: String,
         password: String,
         email: String,
-        salt: String,
         created_at: DateTime<Local>,
         updated_at: DateTime<Local>,
         deleted_at: Option<DateTime<Local>>,
@@ -28,10 +56,22 @@ impl User {
             username,
             password,
             email,
-            salt,
             created_at,
             updated_at,
             deleted_at,
+            failed_login_attempts: 0,
+            locked_until: None,
+            email_verified_at: None,
+            role: UserRole::User,
+            status: UserStatus::Active,
         }
     }
+
+    pub fn is_admin(&self) -> bool {
+        self.role == UserRole::Admin
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.status == UserStatus::Suspended
+    }
 }
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Local};
+use sqlx::FromRow;
+
+// 一次性令牌的用途, 同一张表中按 purpose 区分
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenPurpose {
+    EmailVerification,
+    PasswordReset,
+    // 两步登录中通过密码校验后签发, 提交验证码时使用
+    LoginChallenge,
+}
+
+impl TokenPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenPurpose::EmailVerification => "email_verification",
+            TokenPurpose::PasswordReset => "password_reset",
+            TokenPurpose::LoginChallenge => "login_challenge",
+        }
+    }
+}
+
+// 邮箱验证, 密码重置和登录验证令牌, 数据库中只保存令牌的哈希值
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, FromRow)]
+pub struct UserToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub purpose: String,
+    pub token_hash: String,
+    pub created_at: DateTime<Local>,
+    pub expires_at: DateTime<Local>,
+    pub used_at: Option<DateTime<Local>>,
+}
+
+impl UserToken {
+    pub fn new(
+        user_id: i32,
+        purpose: TokenPurpose,
+        token_hash: String,
+        created_at: DateTime<Local>,
+        expires_at: DateTime<Local>,
+    ) -> Self {
+        Self {
+            id: 0,
+            user_id,
+            purpose: purpose.as_str().to_string(),
+            token_hash,
+            created_at,
+            expires_at,
+            used_at: None,
+        }
+    }
+}
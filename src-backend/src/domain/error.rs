@@ -0,0 +1,48 @@
+use thiserror::Error;
+
+// 领域/应用层统一错误类型, 由 api 层映射为 HTTP 状态码
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    Validation(String),
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    Unauthorized(String),
+    #[error("{0}")]
+    Forbidden(String),
+    #[error("{0}")]
+    WeakPassword(String),
+    #[error("Email address has not been verified")]
+    EmailNotVerified,
+    #[error("Account has been suspended")]
+    AccountSuspended,
+    #[error("Account is temporarily locked, retry in {retry_after} seconds")]
+    AccountLocked { retry_after: u64 },
+    #[error("Too many requests, retry in {retry_after} seconds")]
+    RateLimited { retry_after: u64 },
+    #[error("{0}")]
+    Internal(String),
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        match err {
+            sqlx::Error::RowNotFound => AppError::NotFound("Record not found".to_string()),
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                AppError::Conflict("Record already exists".to_string())
+            }
+            err => AppError::Internal(err.to_string()),
+        }
+    }
+}
+
+impl From<anyhow::Error> for AppError {
+    fn from(err: anyhow::Error) -> Self {
+        AppError::Internal(err.to_string())
+    }
+}
@@ -1,3 +1,4 @@
 pub mod entities;
+pub mod error;
 
 pub mod repository;
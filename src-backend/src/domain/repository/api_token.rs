@@ -0,0 +1,17 @@
+use chrono::{DateTime, Local};
+
+use crate::domain::{entities::api_token::ApiToken, error::AppResult};
+
+#[async_trait::async_trait]
+pub trait ApiTokenRepository: Send + Sync {
+    async fn create(&self, token: &ApiToken) -> AppResult<ApiToken>;
+    async fn get_by_hash(&self, token_hash: &str) -> AppResult<Option<ApiToken>>;
+    // 用户未吊销的令牌, 按创建时间倒序
+    async fn list_by_user_id(&self, user_id: i32) -> AppResult<Vec<ApiToken>>;
+    async fn count_by_user_id(&self, user_id: i32) -> AppResult<i64>;
+    // 只能吊销属于 user_id 且尚未吊销的令牌, 返回是否吊销成功
+    async fn revoke(&self, user_id: i32, id: i32, revoked_at: DateTime<Local>) -> AppResult<bool>;
+    async fn touch(&self, id: i32, last_used_at: DateTime<Local>) -> AppResult<()>;
+    // 删除在 cutoff 之前过期或被吊销的令牌
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64>;
+}
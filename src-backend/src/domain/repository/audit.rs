@@ -0,0 +1,46 @@
+use chrono::{DateTime, Local};
+
+use crate::domain::{
+    entities::audit_event::{AuditEvent, AuditOutcome},
+    error::AppResult,
+    repository::Page,
+};
+
+// 审计日志的筛选条件, since/until 为左闭右开的时间范围
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub actor_id: Option<i32>,
+    pub action: Option<String>,
+    pub target: Option<String>,
+    pub outcome: Option<AuditOutcome>,
+    pub since: Option<DateTime<Local>>,
+    pub until: Option<DateTime<Local>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditQuery {
+    pub filter: AuditFilter,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+impl AuditQuery {
+    pub fn offset(&self) -> i64 {
+        i64::from(self.page.saturating_sub(1)) * i64::from(self.page_size)
+    }
+}
+
+// 只提供追加和查询, 审计事件不能被修改或删除
+#[async_trait::async_trait]
+pub trait AuditRepository: Send + Sync {
+    async fn append(&self, event: &AuditEvent) -> AppResult<AuditEvent>;
+    // 按 id 倒序分页查询, 最新的事件在前
+    async fn search(&self, query: &AuditQuery) -> AppResult<Page<AuditEvent>>;
+    // 按 id 升序返回 after_id 之后的最多 limit 条事件, 用于导出
+    async fn list_after(
+        &self,
+        filter: &AuditFilter,
+        after_id: i64,
+        limit: u32,
+    ) -> AppResult<Vec<AuditEvent>>;
+}
@@ -0,0 +1,13 @@
+use chrono::{DateTime, Local};
+
+use crate::domain::{entities::identity::Identity, error::AppResult};
+
+#[async_trait::async_trait]
+pub trait IdentityRepository: Send + Sync {
+    async fn create(&self, identity: &Identity) -> AppResult<Identity>;
+    async fn get_by_subject(&self, issuer: &str, subject: &str) -> AppResult<Option<Identity>>;
+    async fn list_by_user_id(&self, user_id: i32) -> AppResult<Vec<Identity>>;
+    // 只能删除属于 user_id 的关联, 返回是否删除成功
+    async fn delete(&self, user_id: i32, id: i32) -> AppResult<bool>;
+    async fn touch(&self, id: i32, last_login_at: DateTime<Local>) -> AppResult<()>;
+}
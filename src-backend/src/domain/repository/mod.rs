@@ -1,2 +1,45 @@
+use serde::Serialize;
+
+pub mod api_token;
+pub mod audit;
+pub mod identity;
+pub mod refresh_token;
+pub mod session;
+pub mod tag;
 pub mod todo;
+pub mod two_factor;
 pub mod user;
+pub mod user_token;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    pub fn parse(order: &str) -> Option<Self> {
+        match order.to_ascii_lowercase().as_str() {
+            "asc" => Some(SortOrder::Asc),
+            "desc" => Some(SortOrder::Desc),
+            _ => None,
+        }
+    }
+
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+// 分页查询结果
+#[derive(Debug, Clone, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+    pub page: u32,
+    pub page_size: u32,
+}
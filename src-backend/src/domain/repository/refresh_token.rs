@@ -0,0 +1,18 @@
+use chrono::{DateTime, Local};
+
+use crate::domain::{entities::refresh_token::RefreshToken, error::AppResult};
+
+#[async_trait::async_trait]
+pub trait RefreshTokenRepository: Send + Sync {
+    async fn create(&self, token: &RefreshToken) -> AppResult<RefreshToken>;
+    async fn get_by_hash(&self, token_hash: &str) -> AppResult<Option<RefreshToken>>;
+    // 仅当令牌未被使用且未被吊销时标记为已使用, 返回是否标记成功
+    async fn mark_used(&self, id: i32, used_at: DateTime<Local>) -> AppResult<bool>;
+    async fn revoke_family(&self, family_id: &str, revoked_at: DateTime<Local>) -> AppResult<u64>;
+    async fn revoke_all_by_user_id(
+        &self,
+        user_id: i32,
+        revoked_at: DateTime<Local>,
+    ) -> AppResult<u64>;
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64>;
+}
@@ -0,0 +1,31 @@
+use chrono::{DateTime, Local};
+
+use crate::domain::{entities::session::Session, error::AppResult};
+
+#[async_trait::async_trait]
+pub trait SessionRepository: Send + Sync {
+    async fn create(&self, session: &Session) -> AppResult<Session>;
+    async fn get(&self, id: i32) -> AppResult<Option<Session>>;
+    async fn get_by_family_id(&self, family_id: &str) -> AppResult<Option<Session>>;
+    // 未吊销且未过期的会话, 最近活跃的在前
+    async fn list_active_by_user_id(
+        &self,
+        user_id: i32,
+        now: DateTime<Local>,
+    ) -> AppResult<Vec<Session>>;
+    async fn touch(
+        &self,
+        id: i32,
+        ip_address: &str,
+        last_active_at: DateTime<Local>,
+        expires_at: DateTime<Local>,
+    ) -> AppResult<()>;
+    // 仅当会话未被吊销时吊销, 返回是否吊销成功
+    async fn revoke(&self, id: i32, revoked_at: DateTime<Local>) -> AppResult<bool>;
+    async fn revoke_all_by_user_id(
+        &self,
+        user_id: i32,
+        revoked_at: DateTime<Local>,
+    ) -> AppResult<u64>;
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64>;
+}
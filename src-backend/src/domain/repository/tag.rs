@@ -0,0 +1,40 @@
+use crate::domain::{entities::tag::Tag, error::AppResult};
+
+// 按标签过滤任务时, 匹配任意一个还是全部标签
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TagMatch {
+    #[default]
+    Any,
+    All,
+}
+
+impl TagMatch {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode.to_ascii_lowercase().as_str() {
+            "any" => Some(TagMatch::Any),
+            "all" => Some(TagMatch::All),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TagFilter {
+    pub tag_ids: Vec<i32>,
+    pub mode: TagMatch,
+}
+
+#[async_trait::async_trait]
+pub trait TagRepository: Send + Sync {
+    // 按名称排序
+    async fn list_by_user_id(&self, user_id: i32) -> AppResult<Vec<Tag>>;
+    async fn get_by_id(&self, user_id: i32, id: i32) -> AppResult<Option<Tag>>;
+    async fn create(&self, tag: &Tag) -> AppResult<Tag>;
+    async fn save(&self, tag: Tag) -> AppResult<()>;
+    // 同时解除该标签与所有任务的关联
+    async fn delete(&self, user_id: i32, id: i32) -> AppResult<bool>;
+    // 已关联时返回 false
+    async fn attach(&self, todo_id: i32, tag_id: i32) -> AppResult<bool>;
+    async fn detach(&self, todo_id: i32, tag_id: i32) -> AppResult<bool>;
+    async fn list_by_todo_id(&self, todo_id: i32) -> AppResult<Vec<Tag>>;
+}
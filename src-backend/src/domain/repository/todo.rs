@@ -1,13 +1,101 @@
-use anyhow::Result;
-use sqlx::Error;
+use chrono::{DateTime, Local};
 
-use crate::domain::entities::todo::Todo;
+use crate::domain::{
+    entities::todo::{Priority, Status, Todo},
+    error::AppResult,
+};
+
+use super::{tag::TagFilter, Page, SortOrder};
+
+// 允许排序的字段白名单, 避免把用户输入拼接进 SQL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TodoSortField {
+    #[default]
+    CreatedAt,
+    Deadline,
+    Priority,
+    Status,
+    Title,
+}
+
+impl TodoSortField {
+    pub fn parse(field: &str) -> Option<Self> {
+        match field {
+            "created_at" => Some(TodoSortField::CreatedAt),
+            "deadline" => Some(TodoSortField::Deadline),
+            "priority" => Some(TodoSortField::Priority),
+            "status" => Some(TodoSortField::Status),
+            "title" => Some(TodoSortField::Title),
+            _ => None,
+        }
+    }
+
+    pub fn column(&self) -> &'static str {
+        match self {
+            TodoSortField::CreatedAt => "created_at",
+            TodoSortField::Deadline => "deadline",
+            TodoSortField::Priority => "priority",
+            TodoSortField::Status => "status",
+            TodoSortField::Title => "title",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TodoFilter {
+    pub status: Option<Status>,
+    pub priority: Option<Priority>,
+    pub done: Option<bool>,
+    pub deadline_from: Option<DateTime<Local>>,
+    pub deadline_to: Option<DateTime<Local>>,
+    // 只返回带有其中任意一个或全部标签的任务, 标签 id 需已去重
+    pub tags: Option<TagFilter>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TodoQuery {
+    pub filter: TodoFilter,
+    pub sort: TodoSortField,
+    pub order: SortOrder,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+impl Default for TodoQuery {
+    fn default() -> Self {
+        Self {
+            filter: TodoFilter::default(),
+            sort: TodoSortField::default(),
+            order: SortOrder::default(),
+            page: 1,
+            page_size: 10,
+        }
+    }
+}
+
+impl TodoQuery {
+    pub fn offset(&self) -> i64 {
+        i64::from(self.page.saturating_sub(1)) * i64::from(self.page_size)
+    }
+}
 
 #[async_trait::async_trait]
 pub trait TodoRepository: Send + Sync {
-    async fn get_all_by_user_id(&self, user_id: i32) -> Vec<Todo>;
-    async fn get_by_id(&self, id: i32) -> Option<Todo>;
-    async fn create(&self, todo: &Todo) -> Result<Todo>;
-    async fn save(&self, todo: Todo) -> Result<bool, Error>;
-    async fn delete(&self, id: i32) -> bool;
+    async fn get_all_by_user_id(&self, user_id: i32) -> AppResult<Vec<Todo>>;
+    async fn list_by_user_id(&self, user_id: i32, query: &TodoQuery) -> AppResult<Page<Todo>>;
+    async fn get_by_id(&self, user_id: i32, id: i32) -> AppResult<Option<Todo>>;
+    async fn create(&self, todo: &Todo) -> AppResult<Todo>;
+    async fn save(&self, todo: Todo) -> AppResult<()>;
+    // 在同一事务中软删除任务及其子任务, 使用相同的删除时间以便一起恢复
+    async fn delete(&self, user_id: i32, id: i32, descendants: &[i32]) -> AppResult<bool>;
+    async fn get_trashed_by_user_id(&self, user_id: i32) -> AppResult<Vec<Todo>>;
+    // 在同一事务中恢复任务及随它一起删除的子任务, detach 时任务同时移到顶层
+    async fn restore(
+        &self,
+        user_id: i32,
+        id: i32,
+        descendants: &[i32],
+        detach: bool,
+    ) -> AppResult<bool>;
+    async fn purge_deleted_before(&self, cutoff: DateTime<Local>) -> AppResult<u64>;
 }
@@ -0,0 +1,30 @@
+use chrono::{DateTime, Local};
+
+use crate::domain::{entities::two_factor::TotpCredential, error::AppResult};
+
+#[async_trait::async_trait]
+pub trait TwoFactorRepository: Send + Sync {
+    async fn get_totp(&self, user_id: i32) -> AppResult<Option<TotpCredential>>;
+    // 插入或替换用户的密钥
+    async fn save_totp(&self, credential: &TotpCredential) -> AppResult<()>;
+    async fn enable_totp(&self, user_id: i32, enabled_at: DateTime<Local>) -> AppResult<()>;
+    // 仅当 step 大于上次使用的时间步时记录, 返回是否记录成功, 用于防止验证码重放
+    async fn record_totp_step(&self, user_id: i32, step: i64) -> AppResult<bool>;
+    // 删除密钥和全部恢复码
+    async fn delete(&self, user_id: i32) -> AppResult<()>;
+    // 用新的恢复码替换旧的恢复码
+    async fn replace_recovery_codes(
+        &self,
+        user_id: i32,
+        code_hashes: &[String],
+        created_at: DateTime<Local>,
+    ) -> AppResult<()>;
+    // 仅当恢复码未被使用时标记为已使用, 返回是否标记成功
+    async fn use_recovery_code(
+        &self,
+        user_id: i32,
+        code_hash: &str,
+        used_at: DateTime<Local>,
+    ) -> AppResult<bool>;
+    async fn count_unused_recovery_codes(&self, user_id: i32) -> AppResult<i64>;
+}
@@ -1,11 +1,48 @@
-use crate::domain::entities::user::User;
-use anyhow::Result;
+use chrono::{DateTime, Local};
+
+use crate::domain::{
+    entities::user::{User, UserRole, UserStatus},
+    error::AppResult,
+    repository::Page,
+};
+
+// 管理后台的用户筛选条件, search 按用户名或邮箱做不区分大小写的包含匹配
+#[derive(Debug, Clone, Default)]
+pub struct UserFilter {
+    pub search: Option<String>,
+    pub role: Option<UserRole>,
+    pub status: Option<UserStatus>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UserQuery {
+    pub filter: UserFilter,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+impl UserQuery {
+    pub fn offset(&self) -> i64 {
+        i64::from(self.page.saturating_sub(1)) * i64::from(self.page_size)
+    }
+}
 
 #[async_trait::async_trait]
 pub trait UserRepository: Send + Sync {
-    async fn get_by_id(&self, id: i32) -> Option<User>;
-    async fn get_by_email(&self, email: String) -> Option<User>;
-    async fn create(&self, user: &User) -> Result<User>;
-    async fn save(&self, user: User) -> bool;
-    async fn delete(&self, id: i32) -> bool;
+    async fn get_by_id(&self, id: i32) -> AppResult<Option<User>>;
+    async fn get_by_email(&self, email: String) -> AppResult<Option<User>>;
+    // 按 id 升序分页查询未删除的用户
+    async fn search(&self, query: &UserQuery) -> AppResult<Page<User>>;
+    async fn create(&self, user: &User) -> AppResult<User>;
+    async fn save(&self, user: User) -> AppResult<()>;
+    async fn delete(&self, id: i32) -> AppResult<bool>;
+    // 记录一次登录失败, 连续失败达到 max_attempts 次时锁定到 locked_until 并重新计数
+    async fn record_login_failure(
+        &self,
+        id: i32,
+        max_attempts: u32,
+        locked_until: DateTime<Local>,
+    ) -> AppResult<()>;
+    async fn reset_login_failures(&self, id: i32) -> AppResult<()>;
+    async fn purge_deleted_before(&self, cutoff: DateTime<Local>) -> AppResult<u64>;
 }
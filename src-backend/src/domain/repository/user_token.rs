@@ -0,0 +1,22 @@
+use chrono::{DateTime, Local};
+
+use crate::domain::{
+    entities::user_token::{TokenPurpose, UserToken},
+    error::AppResult,
+};
+
+#[async_trait::async_trait]
+pub trait UserTokenRepository: Send + Sync {
+    async fn create(&self, token: &UserToken) -> AppResult<UserToken>;
+    async fn get_by_hash(&self, token_hash: &str) -> AppResult<Option<UserToken>>;
+    // 仅当令牌未被使用时标记为已使用, 返回是否标记成功
+    async fn mark_used(&self, id: i32, used_at: DateTime<Local>) -> AppResult<bool>;
+    // 使用户某一用途下尚未使用的令牌全部失效
+    async fn invalidate_by_user_id(
+        &self,
+        user_id: i32,
+        purpose: TokenPurpose,
+        used_at: DateTime<Local>,
+    ) -> AppResult<u64>;
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64>;
+}
@@ -0,0 +1,131 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Local};
+
+use crate::{
+    domain::{
+        entities::api_token::ApiToken,
+        error::{AppError, AppResult},
+        repository::api_token::ApiTokenRepository,
+    },
+    infastructure::db::user::memory::UserOwnedStore,
+};
+
+#[derive(Default)]
+struct Store {
+    tokens: BTreeMap<i32, ApiToken>,
+    next_id: i32,
+}
+
+// 基于内存的实现, 用于单元测试和接口测试, 克隆后共享同一份数据
+#[derive(Default, Clone)]
+pub struct InMemoryApiTokenRepository {
+    store: Arc<Mutex<Store>>,
+}
+
+impl InMemoryApiTokenRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl ApiTokenRepository for InMemoryApiTokenRepository {
+    async fn create(&self, token: &ApiToken) -> AppResult<ApiToken> {
+        let mut store = self.store.lock().unwrap();
+        if store
+            .tokens
+            .values()
+            .any(|existing| existing.token_hash == token.token_hash)
+        {
+            return Err(AppError::Conflict("Record already exists".to_string()));
+        }
+        store.next_id += 1;
+        let token = ApiToken {
+            id: store.next_id,
+            ..token.clone()
+        };
+        store.tokens.insert(token.id, token.clone());
+        Ok(token)
+    }
+
+    async fn get_by_hash(&self, token_hash: &str) -> AppResult<Option<ApiToken>> {
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .tokens
+            .values()
+            .find(|token| token.token_hash == token_hash)
+            .cloned())
+    }
+
+    async fn list_by_user_id(&self, user_id: i32) -> AppResult<Vec<ApiToken>> {
+        let store = self.store.lock().unwrap();
+        let mut tokens: Vec<ApiToken> = store
+            .tokens
+            .values()
+            .filter(|token| token.user_id == user_id && token.revoked_at.is_none())
+            .cloned()
+            .collect();
+        tokens.sort_by_key(|token| std::cmp::Reverse((token.created_at, token.id)));
+        Ok(tokens)
+    }
+
+    async fn count_by_user_id(&self, user_id: i32) -> AppResult<i64> {
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .tokens
+            .values()
+            .filter(|token| token.user_id == user_id && token.revoked_at.is_none())
+            .count() as i64)
+    }
+
+    async fn revoke(&self, user_id: i32, id: i32, revoked_at: DateTime<Local>) -> AppResult<bool> {
+        let mut store = self.store.lock().unwrap();
+        match store.tokens.get_mut(&id) {
+            Some(token) if token.user_id == user_id && token.revoked_at.is_none() => {
+                token.revoked_at = Some(revoked_at);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn touch(&self, id: i32, last_used_at: DateTime<Local>) -> AppResult<()> {
+        let mut store = self.store.lock().unwrap();
+        if let Some(token) = store.tokens.get_mut(&id) {
+            token.last_used_at = Some(last_used_at);
+        }
+        Ok(())
+    }
+
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let mut store = self.store.lock().unwrap();
+        let before = store.tokens.len();
+        store.tokens.retain(|_, token| {
+            token.revoked_at.is_none_or(|at| at >= cutoff)
+                && token.expires_at.is_none_or(|at| at >= cutoff)
+        });
+        Ok((before - store.tokens.len()) as u64)
+    }
+}
+
+impl UserOwnedStore for InMemoryApiTokenRepository {
+    fn delete_user(&self, user_id: i32, at: DateTime<Local>) {
+        let mut store = self.store.lock().unwrap();
+        for token in store.tokens.values_mut() {
+            if token.user_id == user_id && token.revoked_at.is_none() {
+                token.revoked_at = Some(at);
+            }
+        }
+    }
+
+    fn purge_users(&self, user_ids: &[i32]) {
+        let mut store = self.store.lock().unwrap();
+        store
+            .tokens
+            .retain(|_, token| !user_ids.contains(&token.user_id));
+    }
+}
@@ -0,0 +1,95 @@
+use chrono::{DateTime, Local};
+use sqlx::MySqlPool;
+
+use crate::domain::{
+    entities::api_token::ApiToken, error::AppResult, repository::api_token::ApiTokenRepository,
+};
+
+pub struct MySqlApiTokenRepository {
+    pool: MySqlPool,
+}
+
+impl MySqlApiTokenRepository {
+    pub fn new(pool: MySqlPool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait::async_trait]
+impl ApiTokenRepository for MySqlApiTokenRepository {
+    async fn create(&self, token: &ApiToken) -> AppResult<ApiToken> {
+        let query = "INSERT INTO api_tokens (user_id, name, token_prefix, token_hash, scopes, created_at, expires_at) VALUES (?, ?, ?, ?, ?, ?, ?)";
+        let res = sqlx::query(query)
+            .bind(token.user_id)
+            .bind(token.name.clone())
+            .bind(token.token_prefix.clone())
+            .bind(token.token_hash.clone())
+            .bind(token.scopes.clone())
+            .bind(token.created_at)
+            .bind(token.expires_at)
+            .execute(&self.pool)
+            .await?;
+        Ok(ApiToken {
+            id: res.last_insert_id() as i32,
+            ..token.clone()
+        })
+    }
+
+    async fn get_by_hash(&self, token_hash: &str) -> AppResult<Option<ApiToken>> {
+        let query = "SELECT * FROM api_tokens WHERE token_hash = ?";
+        let token = sqlx::query_as::<_, ApiToken>(query)
+            .bind(token_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(token)
+    }
+
+    async fn list_by_user_id(&self, user_id: i32) -> AppResult<Vec<ApiToken>> {
+        let query = "SELECT * FROM api_tokens WHERE user_id = ? AND revoked_at IS NULL ORDER BY created_at DESC, id DESC";
+        let tokens = sqlx::query_as::<_, ApiToken>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(tokens)
+    }
+
+    async fn count_by_user_id(&self, user_id: i32) -> AppResult<i64> {
+        let query = "SELECT COUNT(*) FROM api_tokens WHERE user_id = ? AND revoked_at IS NULL";
+        let count: i64 = sqlx::query_scalar(query)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count)
+    }
+
+    async fn revoke(&self, user_id: i32, id: i32, revoked_at: DateTime<Local>) -> AppResult<bool> {
+        let query = "UPDATE api_tokens SET revoked_at = ? WHERE id = ? AND user_id = ? AND revoked_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(revoked_at)
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn touch(&self, id: i32, last_used_at: DateTime<Local>) -> AppResult<()> {
+        let query = "UPDATE api_tokens SET last_used_at = ? WHERE id = ?";
+        sqlx::query(query)
+            .bind(last_used_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let query = "DELETE FROM api_tokens WHERE revoked_at < ? OR expires_at < ?";
+        let res = sqlx::query(query)
+            .bind(cutoff)
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+}
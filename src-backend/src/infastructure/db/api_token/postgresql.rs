@@ -0,0 +1,92 @@
+use chrono::{DateTime, Local};
+use sqlx::PgPool;
+
+use crate::domain::{
+    entities::api_token::ApiToken, error::AppResult, repository::api_token::ApiTokenRepository,
+};
+
+pub struct PgApiTokenRepository {
+    pool: PgPool,
+}
+
+impl PgApiTokenRepository {
+    pub fn new(pool: PgPool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait::async_trait]
+impl ApiTokenRepository for PgApiTokenRepository {
+    async fn create(&self, token: &ApiToken) -> AppResult<ApiToken> {
+        let query = "INSERT INTO api_tokens (user_id, name, token_prefix, token_hash, scopes, created_at, expires_at) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *";
+        let token = sqlx::query_as::<_, ApiToken>(query)
+            .bind(token.user_id)
+            .bind(token.name.clone())
+            .bind(token.token_prefix.clone())
+            .bind(token.token_hash.clone())
+            .bind(token.scopes.clone())
+            .bind(token.created_at)
+            .bind(token.expires_at)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(token)
+    }
+
+    async fn get_by_hash(&self, token_hash: &str) -> AppResult<Option<ApiToken>> {
+        let query = "SELECT * FROM api_tokens WHERE token_hash = $1";
+        let token = sqlx::query_as::<_, ApiToken>(query)
+            .bind(token_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(token)
+    }
+
+    async fn list_by_user_id(&self, user_id: i32) -> AppResult<Vec<ApiToken>> {
+        let query = "SELECT * FROM api_tokens WHERE user_id = $1 AND revoked_at IS NULL ORDER BY created_at DESC, id DESC";
+        let tokens = sqlx::query_as::<_, ApiToken>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(tokens)
+    }
+
+    async fn count_by_user_id(&self, user_id: i32) -> AppResult<i64> {
+        let query = "SELECT COUNT(*) FROM api_tokens WHERE user_id = $1 AND revoked_at IS NULL";
+        let count: i64 = sqlx::query_scalar(query)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count)
+    }
+
+    async fn revoke(&self, user_id: i32, id: i32, revoked_at: DateTime<Local>) -> AppResult<bool> {
+        let query = "UPDATE api_tokens SET revoked_at = $1 WHERE id = $2 AND user_id = $3 AND revoked_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(revoked_at)
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn touch(&self, id: i32, last_used_at: DateTime<Local>) -> AppResult<()> {
+        let query = "UPDATE api_tokens SET last_used_at = $1 WHERE id = $2";
+        sqlx::query(query)
+            .bind(last_used_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let query = "DELETE FROM api_tokens WHERE revoked_at < $1 OR expires_at < $2";
+        let res = sqlx::query(query)
+            .bind(cutoff)
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+}
@@ -0,0 +1,179 @@
+use chrono::{DateTime, Local};
+use sqlx::SqlitePool;
+
+use crate::domain::{
+    entities::api_token::ApiToken, error::AppResult, repository::api_token::ApiTokenRepository,
+};
+
+pub struct SqliteApiTokenRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteApiTokenRepository {
+    pub fn new(pool: SqlitePool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait::async_trait]
+impl ApiTokenRepository for SqliteApiTokenRepository {
+    async fn create(&self, token: &ApiToken) -> AppResult<ApiToken> {
+        let query = "INSERT INTO api_tokens (user_id, name, token_prefix, token_hash, scopes, created_at, expires_at) VALUES (?, ?, ?, ?, ?, ?, ?) RETURNING *";
+        let token = sqlx::query_as::<_, ApiToken>(query)
+            .bind(token.user_id)
+            .bind(token.name.clone())
+            .bind(token.token_prefix.clone())
+            .bind(token.token_hash.clone())
+            .bind(token.scopes.clone())
+            .bind(token.created_at)
+            .bind(token.expires_at)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(token)
+    }
+
+    async fn get_by_hash(&self, token_hash: &str) -> AppResult<Option<ApiToken>> {
+        let query = "SELECT * FROM api_tokens WHERE token_hash = ?";
+        let token = sqlx::query_as::<_, ApiToken>(query)
+            .bind(token_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(token)
+    }
+
+    async fn list_by_user_id(&self, user_id: i32) -> AppResult<Vec<ApiToken>> {
+        let query = "SELECT * FROM api_tokens WHERE user_id = ? AND revoked_at IS NULL ORDER BY created_at DESC, id DESC";
+        let tokens = sqlx::query_as::<_, ApiToken>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(tokens)
+    }
+
+    async fn count_by_user_id(&self, user_id: i32) -> AppResult<i64> {
+        let query = "SELECT COUNT(*) FROM api_tokens WHERE user_id = ? AND revoked_at IS NULL";
+        let count: i64 = sqlx::query_scalar(query)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count)
+    }
+
+    async fn revoke(&self, user_id: i32, id: i32, revoked_at: DateTime<Local>) -> AppResult<bool> {
+        let query = "UPDATE api_tokens SET revoked_at = ? WHERE id = ? AND user_id = ? AND revoked_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(revoked_at)
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn touch(&self, id: i32, last_used_at: DateTime<Local>) -> AppResult<()> {
+        let query = "UPDATE api_tokens SET last_used_at = ? WHERE id = ?";
+        sqlx::query(query)
+            .bind(last_used_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let query = "DELETE FROM api_tokens WHERE revoked_at < ? OR expires_at < ?";
+        let res = sqlx::query(query)
+            .bind(cutoff)
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::{domain::entities::api_token::ApiScope, infastructure::db::SQLITE_MIGRATOR};
+
+    async fn setup() -> SqliteApiTokenRepository {
+        // 内存数据库只在单个连接内可见
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        SQLITE_MIGRATOR.run(&pool).await.unwrap();
+        SqliteApiTokenRepository::new(pool).unwrap()
+    }
+
+    fn new_token(user_id: i32, token_hash: &str, expires_at: Option<DateTime<Local>>) -> ApiToken {
+        ApiToken::new(
+            user_id,
+            "ci".to_string(),
+            "mth_abcd".to_string(),
+            token_hash.to_string(),
+            &[ApiScope::TodoRead, ApiScope::TodoWrite],
+            Local::now(),
+            expires_at,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_create_list_and_revoke() {
+        let repo = setup().await;
+        let token = repo.create(&new_token(1, "hash", None)).await.unwrap();
+        assert!(token.id > 0);
+        assert_eq!(
+            token.scopes(),
+            vec![ApiScope::TodoRead, ApiScope::TodoWrite]
+        );
+        repo.create(&new_token(2, "other", None)).await.unwrap();
+
+        repo.touch(token.id, Local::now()).await.unwrap();
+        let found = repo.get_by_hash("hash").await.unwrap().unwrap();
+        assert!(found.last_used_at.is_some());
+        assert_eq!(repo.list_by_user_id(1).await.unwrap().len(), 1);
+        assert_eq!(repo.count_by_user_id(1).await.unwrap(), 1);
+
+        // 不能吊销其他用户的令牌
+        assert!(!repo.revoke(2, token.id, Local::now()).await.unwrap());
+        assert!(repo.revoke(1, token.id, Local::now()).await.unwrap());
+        assert!(!repo.revoke(1, token.id, Local::now()).await.unwrap());
+        assert!(repo.list_by_user_id(1).await.unwrap().is_empty());
+        assert_eq!(repo.count_by_user_id(1).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_purge_expired_and_revoked() {
+        let repo = setup().await;
+        let revoked = repo.create(&new_token(1, "revoked", None)).await.unwrap();
+        repo.revoke(1, revoked.id, Local::now()).await.unwrap();
+        repo.create(&new_token(
+            1,
+            "expired",
+            Some(Local::now() - Duration::hours(1)),
+        ))
+        .await
+        .unwrap();
+        repo.create(&new_token(1, "forever", None)).await.unwrap();
+        repo.create(&new_token(
+            1,
+            "later",
+            Some(Local::now() + Duration::days(1)),
+        ))
+        .await
+        .unwrap();
+
+        let purged = repo
+            .purge_expired_before(Local::now() + Duration::minutes(1))
+            .await
+            .unwrap();
+        assert_eq!(purged, 2);
+        assert!(repo.get_by_hash("forever").await.unwrap().is_some());
+        assert!(repo.get_by_hash("later").await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,97 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use crate::domain::{
+    entities::audit_event::AuditEvent,
+    error::AppResult,
+    repository::{
+        audit::{AuditFilter, AuditQuery, AuditRepository},
+        Page,
+    },
+};
+
+#[derive(Default)]
+struct Store {
+    events: BTreeMap<i64, AuditEvent>,
+    next_id: i64,
+}
+
+// 基于内存的实现, 用于单元测试和接口测试
+#[derive(Default)]
+pub struct InMemoryAuditRepository {
+    store: Mutex<Store>,
+}
+
+impl InMemoryAuditRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn matches_filter(event: &AuditEvent, filter: &AuditFilter) -> bool {
+    filter.actor_id.is_none_or(|id| event.actor_id == Some(id))
+        && filter
+            .action
+            .as_deref()
+            .is_none_or(|action| event.action == action)
+        && filter
+            .target
+            .as_deref()
+            .is_none_or(|target| event.target.as_deref() == Some(target))
+        && filter
+            .outcome
+            .is_none_or(|outcome| event.outcome == outcome)
+        && filter.since.is_none_or(|since| event.created_at >= since)
+        && filter.until.is_none_or(|until| event.created_at < until)
+}
+
+#[async_trait::async_trait]
+impl AuditRepository for InMemoryAuditRepository {
+    async fn append(&self, event: &AuditEvent) -> AppResult<AuditEvent> {
+        let mut store = self.store.lock().unwrap();
+        store.next_id += 1;
+        let event = AuditEvent {
+            id: store.next_id,
+            ..event.clone()
+        };
+        store.events.insert(event.id, event.clone());
+        Ok(event)
+    }
+
+    async fn search(&self, query: &AuditQuery) -> AppResult<Page<AuditEvent>> {
+        let store = self.store.lock().unwrap();
+        let events: Vec<_> = store
+            .events
+            .values()
+            .rev()
+            .filter(|event| matches_filter(event, &query.filter))
+            .collect();
+        Ok(Page {
+            total: events.len() as i64,
+            items: events
+                .into_iter()
+                .skip(query.offset() as usize)
+                .take(query.page_size as usize)
+                .cloned()
+                .collect(),
+            page: query.page,
+            page_size: query.page_size,
+        })
+    }
+
+    async fn list_after(
+        &self,
+        filter: &AuditFilter,
+        after_id: i64,
+        limit: u32,
+    ) -> AppResult<Vec<AuditEvent>> {
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .events
+            .range(after_id + 1..)
+            .map(|(_, event)| event)
+            .filter(|event| matches_filter(event, filter))
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+}
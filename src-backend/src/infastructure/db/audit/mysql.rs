@@ -0,0 +1,109 @@
+use sqlx::{MySql, MySqlPool, QueryBuilder};
+
+use crate::domain::{
+    entities::audit_event::AuditEvent,
+    error::AppResult,
+    repository::{
+        audit::{AuditFilter, AuditQuery, AuditRepository},
+        Page,
+    },
+};
+
+pub struct MySqlAuditRepository {
+    pool: MySqlPool,
+}
+
+impl MySqlAuditRepository {
+    pub fn new(pool: MySqlPool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+fn push_conditions(builder: &mut QueryBuilder<'_, MySql>, filter: &AuditFilter) {
+    builder.push(" WHERE 1 = 1");
+    if let Some(actor_id) = filter.actor_id {
+        builder.push(" AND actor_id = ").push_bind(actor_id);
+    }
+    if let Some(action) = filter.action.clone() {
+        builder.push(" AND action = ").push_bind(action);
+    }
+    if let Some(target) = filter.target.clone() {
+        builder.push(" AND target = ").push_bind(target);
+    }
+    if let Some(outcome) = filter.outcome {
+        builder.push(" AND outcome = ").push_bind(outcome);
+    }
+    if let Some(since) = filter.since {
+        builder.push(" AND created_at >= ").push_bind(since);
+    }
+    if let Some(until) = filter.until {
+        builder.push(" AND created_at < ").push_bind(until);
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditRepository for MySqlAuditRepository {
+    async fn append(&self, event: &AuditEvent) -> AppResult<AuditEvent> {
+        let query = "INSERT INTO audit_events (actor_id, action, target, ip_address, user_agent, outcome, detail, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)";
+        let res = sqlx::query(query)
+            .bind(event.actor_id)
+            .bind(event.action.clone())
+            .bind(event.target.clone())
+            .bind(event.ip_address.clone())
+            .bind(event.user_agent.clone())
+            .bind(event.outcome)
+            .bind(event.detail.clone())
+            .bind(event.created_at)
+            .execute(&self.pool)
+            .await?;
+        Ok(AuditEvent {
+            id: res.last_insert_id() as i64,
+            ..event.clone()
+        })
+    }
+
+    async fn search(&self, query: &AuditQuery) -> AppResult<Page<AuditEvent>> {
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM audit_events");
+        push_conditions(&mut count, &query.filter);
+        let total: i64 = count.build_query_scalar().fetch_one(&self.pool).await?;
+
+        let mut select = QueryBuilder::new("SELECT * FROM audit_events");
+        push_conditions(&mut select, &query.filter);
+        select
+            .push(" ORDER BY id DESC LIMIT ")
+            .push_bind(i64::from(query.page_size))
+            .push(" OFFSET ")
+            .push_bind(query.offset());
+        let items = select
+            .build_query_as::<AuditEvent>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Page {
+            items,
+            total,
+            page: query.page,
+            page_size: query.page_size,
+        })
+    }
+
+    async fn list_after(
+        &self,
+        filter: &AuditFilter,
+        after_id: i64,
+        limit: u32,
+    ) -> AppResult<Vec<AuditEvent>> {
+        let mut select = QueryBuilder::new("SELECT * FROM audit_events");
+        push_conditions(&mut select, filter);
+        select
+            .push(" AND id > ")
+            .push_bind(after_id)
+            .push(" ORDER BY id ASC LIMIT ")
+            .push_bind(i64::from(limit));
+        let events = select
+            .build_query_as::<AuditEvent>()
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(events)
+    }
+}
@@ -0,0 +1,207 @@
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+
+use crate::domain::{
+    entities::audit_event::AuditEvent,
+    error::AppResult,
+    repository::{
+        audit::{AuditFilter, AuditQuery, AuditRepository},
+        Page,
+    },
+};
+
+pub struct SqliteAuditRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteAuditRepository {
+    pub fn new(pool: SqlitePool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+fn push_conditions(builder: &mut QueryBuilder<'_, Sqlite>, filter: &AuditFilter) {
+    builder.push(" WHERE 1 = 1");
+    if let Some(actor_id) = filter.actor_id {
+        builder.push(" AND actor_id = ").push_bind(actor_id);
+    }
+    if let Some(action) = filter.action.clone() {
+        builder.push(" AND action = ").push_bind(action);
+    }
+    if let Some(target) = filter.target.clone() {
+        builder.push(" AND target = ").push_bind(target);
+    }
+    if let Some(outcome) = filter.outcome {
+        builder.push(" AND outcome = ").push_bind(outcome);
+    }
+    if let Some(since) = filter.since {
+        builder.push(" AND created_at >= ").push_bind(since);
+    }
+    if let Some(until) = filter.until {
+        builder.push(" AND created_at < ").push_bind(until);
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditRepository for SqliteAuditRepository {
+    async fn append(&self, event: &AuditEvent) -> AppResult<AuditEvent> {
+        let query = "INSERT INTO audit_events (actor_id, action, target, ip_address, user_agent, outcome, detail, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?) RETURNING *";
+        let event = sqlx::query_as::<_, AuditEvent>(query)
+            .bind(event.actor_id)
+            .bind(event.action.clone())
+            .bind(event.target.clone())
+            .bind(event.ip_address.clone())
+            .bind(event.user_agent.clone())
+            .bind(event.outcome)
+            .bind(event.detail.clone())
+            .bind(event.created_at)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(event)
+    }
+
+    async fn search(&self, query: &AuditQuery) -> AppResult<Page<AuditEvent>> {
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM audit_events");
+        push_conditions(&mut count, &query.filter);
+        let total: i64 = count.build_query_scalar().fetch_one(&self.pool).await?;
+
+        let mut select = QueryBuilder::new("SELECT * FROM audit_events");
+        push_conditions(&mut select, &query.filter);
+        select
+            .push(" ORDER BY id DESC LIMIT ")
+            .push_bind(i64::from(query.page_size))
+            .push(" OFFSET ")
+            .push_bind(query.offset());
+        let items = select
+            .build_query_as::<AuditEvent>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Page {
+            items,
+            total,
+            page: query.page,
+            page_size: query.page_size,
+        })
+    }
+
+    async fn list_after(
+        &self,
+        filter: &AuditFilter,
+        after_id: i64,
+        limit: u32,
+    ) -> AppResult<Vec<AuditEvent>> {
+        let mut select = QueryBuilder::new("SELECT * FROM audit_events");
+        push_conditions(&mut select, filter);
+        select
+            .push(" AND id > ")
+            .push_bind(after_id)
+            .push(" ORDER BY id ASC LIMIT ")
+            .push_bind(i64::from(limit));
+        let events = select
+            .build_query_as::<AuditEvent>()
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::{domain::entities::audit_event::AuditOutcome, infastructure::db::SQLITE_MIGRATOR};
+
+    async fn setup() -> SqliteAuditRepository {
+        // 内存数据库只在单个连接内可见
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        SQLITE_MIGRATOR.run(&pool).await.unwrap();
+        SqliteAuditRepository::new(pool).unwrap()
+    }
+
+    fn new_event(actor_id: Option<i32>, action: &str, outcome: AuditOutcome) -> AuditEvent {
+        AuditEvent {
+            id: 0,
+            actor_id,
+            action: action.to_string(),
+            target: actor_id.map(|id| format!("user:{id}")),
+            ip_address: Some("127.0.0.1".to_string()),
+            user_agent: None,
+            outcome,
+            detail: None,
+            created_at: Local::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_filters_and_orders_newest_first() {
+        let repo = setup().await;
+        repo.append(&new_event(Some(1), "auth.login", AuditOutcome::Success))
+            .await
+            .unwrap();
+        repo.append(&new_event(None, "auth.login", AuditOutcome::Failure))
+            .await
+            .unwrap();
+        let last = repo
+            .append(&new_event(Some(1), "auth.logout", AuditOutcome::Success))
+            .await
+            .unwrap();
+        assert!(last.id > 0);
+
+        let page = repo
+            .search(&AuditQuery {
+                filter: AuditFilter {
+                    actor_id: Some(1),
+                    ..AuditFilter::default()
+                },
+                page: 1,
+                page_size: 10,
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.items[0].id, last.id);
+
+        let page = repo
+            .search(&AuditQuery {
+                filter: AuditFilter {
+                    action: Some("auth.login".to_string()),
+                    outcome: Some(AuditOutcome::Failure),
+                    since: Some(Local::now() - Duration::minutes(1)),
+                    until: Some(Local::now() + Duration::minutes(1)),
+                    ..AuditFilter::default()
+                },
+                page: 1,
+                page_size: 10,
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].actor_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_list_after_pages_in_id_order() {
+        let repo = setup().await;
+        for _ in 0..5 {
+            repo.append(&new_event(Some(1), "auth.login", AuditOutcome::Success))
+                .await
+                .unwrap();
+        }
+        let filter = AuditFilter::default();
+        let first = repo.list_after(&filter, 0, 3).await.unwrap();
+        assert_eq!(first.len(), 3);
+        assert!(first.windows(2).all(|pair| pair[0].id < pair[1].id));
+        let rest = repo
+            .list_after(&filter, first.last().unwrap().id, 3)
+            .await
+            .unwrap();
+        assert_eq!(rest.len(), 2);
+        assert!(rest[0].id > first[2].id);
+    }
+}
@@ -0,0 +1,106 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Local};
+
+use crate::{
+    domain::{
+        entities::identity::Identity,
+        error::{AppError, AppResult},
+        repository::identity::IdentityRepository,
+    },
+    infastructure::db::user::memory::UserOwnedStore,
+};
+
+#[derive(Default)]
+struct Store {
+    identities: BTreeMap<i32, Identity>,
+    next_id: i32,
+}
+
+// 基于内存的实现, 用于单元测试和接口测试, 克隆后共享同一份数据
+#[derive(Default, Clone)]
+pub struct InMemoryIdentityRepository {
+    store: Arc<Mutex<Store>>,
+}
+
+impl InMemoryIdentityRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl IdentityRepository for InMemoryIdentityRepository {
+    async fn create(&self, identity: &Identity) -> AppResult<Identity> {
+        let mut store = self.store.lock().unwrap();
+        if store.identities.values().any(|existing| {
+            existing.issuer == identity.issuer && existing.subject == identity.subject
+        }) {
+            return Err(AppError::Conflict("Record already exists".to_string()));
+        }
+        store.next_id += 1;
+        let identity = Identity {
+            id: store.next_id,
+            ..identity.clone()
+        };
+        store.identities.insert(identity.id, identity.clone());
+        Ok(identity)
+    }
+
+    async fn get_by_subject(&self, issuer: &str, subject: &str) -> AppResult<Option<Identity>> {
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .identities
+            .values()
+            .find(|identity| identity.issuer == issuer && identity.subject == subject)
+            .cloned())
+    }
+
+    async fn list_by_user_id(&self, user_id: i32) -> AppResult<Vec<Identity>> {
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .identities
+            .values()
+            .filter(|identity| identity.user_id == user_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn delete(&self, user_id: i32, id: i32) -> AppResult<bool> {
+        let mut store = self.store.lock().unwrap();
+        match store.identities.get(&id) {
+            Some(identity) if identity.user_id == user_id => {
+                store.identities.remove(&id);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn touch(&self, id: i32, last_login_at: DateTime<Local>) -> AppResult<()> {
+        let mut store = self.store.lock().unwrap();
+        if let Some(identity) = store.identities.get_mut(&id) {
+            identity.last_login_at = Some(last_login_at);
+        }
+        Ok(())
+    }
+}
+
+impl UserOwnedStore for InMemoryIdentityRepository {
+    fn delete_user(&self, user_id: i32, _at: DateTime<Local>) {
+        let mut store = self.store.lock().unwrap();
+        store
+            .identities
+            .retain(|_, identity| identity.user_id != user_id);
+    }
+
+    fn purge_users(&self, user_ids: &[i32]) {
+        let mut store = self.store.lock().unwrap();
+        store
+            .identities
+            .retain(|_, identity| !user_ids.contains(&identity.user_id));
+    }
+}
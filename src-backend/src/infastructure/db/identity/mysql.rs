@@ -0,0 +1,75 @@
+use chrono::{DateTime, Local};
+use sqlx::MySqlPool;
+
+use crate::domain::{
+    entities::identity::Identity, error::AppResult, repository::identity::IdentityRepository,
+};
+
+pub struct MySqlIdentityRepository {
+    pool: MySqlPool,
+}
+
+impl MySqlIdentityRepository {
+    pub fn new(pool: MySqlPool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait::async_trait]
+impl IdentityRepository for MySqlIdentityRepository {
+    async fn create(&self, identity: &Identity) -> AppResult<Identity> {
+        let query = "INSERT INTO identities (user_id, issuer, subject, email, created_at, last_login_at) VALUES (?, ?, ?, ?, ?, ?)";
+        let res = sqlx::query(query)
+            .bind(identity.user_id)
+            .bind(identity.issuer.clone())
+            .bind(identity.subject.clone())
+            .bind(identity.email.clone())
+            .bind(identity.created_at)
+            .bind(identity.last_login_at)
+            .execute(&self.pool)
+            .await?;
+        Ok(Identity {
+            id: res.last_insert_id() as i32,
+            ..identity.clone()
+        })
+    }
+
+    async fn get_by_subject(&self, issuer: &str, subject: &str) -> AppResult<Option<Identity>> {
+        let query = "SELECT * FROM identities WHERE issuer = ? AND subject = ?";
+        let identity = sqlx::query_as::<_, Identity>(query)
+            .bind(issuer)
+            .bind(subject)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(identity)
+    }
+
+    async fn list_by_user_id(&self, user_id: i32) -> AppResult<Vec<Identity>> {
+        let query = "SELECT * FROM identities WHERE user_id = ? ORDER BY id";
+        let identities = sqlx::query_as::<_, Identity>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(identities)
+    }
+
+    async fn delete(&self, user_id: i32, id: i32) -> AppResult<bool> {
+        let query = "DELETE FROM identities WHERE id = ? AND user_id = ?";
+        let res = sqlx::query(query)
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn touch(&self, id: i32, last_login_at: DateTime<Local>) -> AppResult<()> {
+        let query = "UPDATE identities SET last_login_at = ? WHERE id = ?";
+        sqlx::query(query)
+            .bind(last_login_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
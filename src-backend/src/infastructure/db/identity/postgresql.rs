@@ -0,0 +1,72 @@
+use chrono::{DateTime, Local};
+use sqlx::PgPool;
+
+use crate::domain::{
+    entities::identity::Identity, error::AppResult, repository::identity::IdentityRepository,
+};
+
+pub struct PgIdentityRepository {
+    pool: PgPool,
+}
+
+impl PgIdentityRepository {
+    pub fn new(pool: PgPool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait::async_trait]
+impl IdentityRepository for PgIdentityRepository {
+    async fn create(&self, identity: &Identity) -> AppResult<Identity> {
+        let query = "INSERT INTO identities (user_id, issuer, subject, email, created_at, last_login_at) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *";
+        let identity = sqlx::query_as::<_, Identity>(query)
+            .bind(identity.user_id)
+            .bind(identity.issuer.clone())
+            .bind(identity.subject.clone())
+            .bind(identity.email.clone())
+            .bind(identity.created_at)
+            .bind(identity.last_login_at)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(identity)
+    }
+
+    async fn get_by_subject(&self, issuer: &str, subject: &str) -> AppResult<Option<Identity>> {
+        let query = "SELECT * FROM identities WHERE issuer = $1 AND subject = $2";
+        let identity = sqlx::query_as::<_, Identity>(query)
+            .bind(issuer)
+            .bind(subject)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(identity)
+    }
+
+    async fn list_by_user_id(&self, user_id: i32) -> AppResult<Vec<Identity>> {
+        let query = "SELECT * FROM identities WHERE user_id = $1 ORDER BY id";
+        let identities = sqlx::query_as::<_, Identity>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(identities)
+    }
+
+    async fn delete(&self, user_id: i32, id: i32) -> AppResult<bool> {
+        let query = "DELETE FROM identities WHERE id = $1 AND user_id = $2";
+        let res = sqlx::query(query)
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn touch(&self, id: i32, last_login_at: DateTime<Local>) -> AppResult<()> {
+        let query = "UPDATE identities SET last_login_at = $1 WHERE id = $2";
+        sqlx::query(query)
+            .bind(last_login_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+use chrono::{DateTime, Local};
+use sqlx::SqlitePool;
+
+use crate::domain::{
+    entities::identity::Identity, error::AppResult, repository::identity::IdentityRepository,
+};
+
+pub struct SqliteIdentityRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteIdentityRepository {
+    pub fn new(pool: SqlitePool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait::async_trait]
+impl IdentityRepository for SqliteIdentityRepository {
+    async fn create(&self, identity: &Identity) -> AppResult<Identity> {
+        let query = "INSERT INTO identities (user_id, issuer, subject, email, created_at, last_login_at) VALUES (?, ?, ?, ?, ?, ?) RETURNING *";
+        let identity = sqlx::query_as::<_, Identity>(query)
+            .bind(identity.user_id)
+            .bind(identity.issuer.clone())
+            .bind(identity.subject.clone())
+            .bind(identity.email.clone())
+            .bind(identity.created_at)
+            .bind(identity.last_login_at)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(identity)
+    }
+
+    async fn get_by_subject(&self, issuer: &str, subject: &str) -> AppResult<Option<Identity>> {
+        let query = "SELECT * FROM identities WHERE issuer = ? AND subject = ?";
+        let identity = sqlx::query_as::<_, Identity>(query)
+            .bind(issuer)
+            .bind(subject)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(identity)
+    }
+
+    async fn list_by_user_id(&self, user_id: i32) -> AppResult<Vec<Identity>> {
+        let query = "SELECT * FROM identities WHERE user_id = ? ORDER BY id";
+        let identities = sqlx::query_as::<_, Identity>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(identities)
+    }
+
+    async fn delete(&self, user_id: i32, id: i32) -> AppResult<bool> {
+        let query = "DELETE FROM identities WHERE id = ? AND user_id = ?";
+        let res = sqlx::query(query)
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn touch(&self, id: i32, last_login_at: DateTime<Local>) -> AppResult<()> {
+        let query = "UPDATE identities SET last_login_at = ? WHERE id = ?";
+        sqlx::query(query)
+            .bind(last_login_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::{domain::error::AppError, infastructure::db::SQLITE_MIGRATOR};
+
+    async fn setup() -> SqliteIdentityRepository {
+        // 内存数据库只在单个连接内可见
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        SQLITE_MIGRATOR.run(&pool).await.unwrap();
+        SqliteIdentityRepository::new(pool).unwrap()
+    }
+
+    fn new_identity(user_id: i32, subject: &str) -> Identity {
+        Identity::new(
+            user_id,
+            "https://issuer.example.com".to_string(),
+            subject.to_string(),
+            Some("user@example.com".to_string()),
+            Local::now(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_subject_is_unique_per_issuer() {
+        let repo = setup().await;
+        let identity = repo.create(&new_identity(1, "sub-1")).await.unwrap();
+        assert!(identity.id > 0);
+        assert!(matches!(
+            repo.create(&new_identity(2, "sub-1")).await,
+            Err(AppError::Conflict(_))
+        ));
+        let mut other_issuer = new_identity(2, "sub-1");
+        other_issuer.issuer = "https://other.example.com".to_string();
+        repo.create(&other_issuer).await.unwrap();
+
+        let found = repo
+            .get_by_subject("https://issuer.example.com", "sub-1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.user_id, 1);
+    }
+
+    #[tokio::test]
+    async fn test_list_and_delete_by_owner() {
+        let repo = setup().await;
+        let identity = repo.create(&new_identity(1, "sub-1")).await.unwrap();
+        repo.create(&new_identity(1, "sub-2")).await.unwrap();
+        assert_eq!(repo.list_by_user_id(1).await.unwrap().len(), 2);
+
+        assert!(!repo.delete(2, identity.id).await.unwrap());
+        assert!(repo.delete(1, identity.id).await.unwrap());
+        assert_eq!(repo.list_by_user_id(1).await.unwrap().len(), 1);
+    }
+}
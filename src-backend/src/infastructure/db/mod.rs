@@ -1,32 +1,125 @@
-use once_cell::sync::Lazy;
-use sqlx::{MySqlPool, PgPool};
-use std::sync::Mutex;
+use sqlx::{
+    migrate::{Migrate, MigrateError, Migrator},
+    mysql::MySqlPoolOptions,
+    postgres::PgPoolOptions,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    MySqlPool, PgPool, SqlitePool,
+};
+use std::str::FromStr;
 
+use crate::config::{DatabaseConfig, DatabaseType};
+
+pub mod api_token;
+pub mod audit;
+pub mod identity;
+pub mod refresh_token;
+pub mod session;
+pub mod tag;
 pub mod todo;
+pub mod two_factor;
 pub mod user;
+pub mod user_token;
 
 pub enum Database {
     MySQL(MySqlPool),
     PgSQL(PgPool),
+    Sqlite(SqlitePool),
+}
+
+// 每种数据库各自维护一套迁移脚本, 编译期嵌入二进制
+pub static MYSQL_MIGRATOR: Migrator = sqlx::migrate!("./migrations/mysql");
+pub static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+pub static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+pub async fn connect_db(config: &DatabaseConfig) -> Result<Database, sqlx::Error> {
+    let database = match config.kind {
+        DatabaseType::MySql => Database::MySQL(
+            MySqlPoolOptions::new()
+                .max_connections(config.max_connections)
+                .min_connections(config.min_connections)
+                .connect(&config.url)
+                .await?,
+        ),
+        DatabaseType::PostgreSql => Database::PgSQL(
+            PgPoolOptions::new()
+                .max_connections(config.max_connections)
+                .min_connections(config.min_connections)
+                .connect(&config.url)
+                .await?,
+        ),
+        DatabaseType::Sqlite => {
+            // 桌面端离线使用, 数据库文件不存在时自动创建
+            let options = SqliteConnectOptions::from_str(&config.url)?.create_if_missing(true);
+            Database::Sqlite(
+                SqlitePoolOptions::new()
+                    .max_connections(config.max_connections)
+                    .min_connections(config.min_connections)
+                    .connect_with(options)
+                    .await?,
+            )
+        }
+    };
+    Ok(database)
+}
+
+// 连接数据库并执行尚未应用的迁移
+pub async fn init_db(config: &DatabaseConfig) -> anyhow::Result<Database> {
+    let database = connect_db(config).await?;
+    run_migrations(&database).await?;
+    Ok(database)
 }
 
-pub static DB: Lazy<Mutex<Option<Database>>> = Lazy::new(|| Mutex::new(None));
+pub async fn run_migrations(database: &Database) -> Result<(), MigrateError> {
+    match database {
+        Database::MySQL(pool) => MYSQL_MIGRATOR.run(pool).await,
+        Database::PgSQL(pool) => POSTGRES_MIGRATOR.run(pool).await,
+        Database::Sqlite(pool) => SQLITE_MIGRATOR.run(pool).await,
+    }
+}
 
-pub async fn init_db() {
-    dotenv::dotenv().ok();
-    let db_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    let db_type = std::env::var("DATABASE_TYPE").expect("DATABASE_TYPE must be set");
-    let database = match db_type.as_str() {
-        "mysql" => {
-            let pool = MySqlPool::connect(&db_url).await.unwrap();
-            Database::MySQL(pool)
+// 回滚最近一次迁移, 返回被回滚的版本号
+pub async fn revert_migration(database: &Database) -> Result<Option<i64>, MigrateError> {
+    match database {
+        Database::MySQL(pool) => {
+            let applied = applied_versions(&mut *pool.acquire().await?).await?;
+            revert_last(&MYSQL_MIGRATOR, pool, applied).await
         }
-        "postgresql" => {
-            let pool = PgPool::connect(&db_url).await.unwrap();
-            Database::PgSQL(pool)
+        Database::PgSQL(pool) => {
+            let applied = applied_versions(&mut *pool.acquire().await?).await?;
+            revert_last(&POSTGRES_MIGRATOR, pool, applied).await
         }
-        _ => panic!("Invalid database type"),
+        Database::Sqlite(pool) => {
+            let applied = applied_versions(&mut *pool.acquire().await?).await?;
+            revert_last(&SQLITE_MIGRATOR, pool, applied).await
+        }
+    }
+}
+
+async fn applied_versions<C: Migrate>(conn: &mut C) -> Result<Vec<i64>, MigrateError> {
+    conn.ensure_migrations_table().await?;
+    let mut versions: Vec<i64> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect();
+    versions.sort_unstable();
+    Ok(versions)
+}
+
+async fn revert_last<'a, A>(
+    migrator: &Migrator,
+    pool: A,
+    mut applied: Vec<i64>,
+) -> Result<Option<i64>, MigrateError>
+where
+    A: sqlx::Acquire<'a>,
+    <A::Connection as std::ops::Deref>::Target: Migrate,
+{
+    let Some(latest) = applied.pop() else {
+        return Ok(None);
     };
-    let mut db = DB.lock().unwrap();
-    *db = Some(database);
+    let target = applied.last().copied().unwrap_or(0);
+    migrator.undo(pool, target).await?;
+    Ok(Some(latest))
 }
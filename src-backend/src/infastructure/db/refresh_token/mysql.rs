@@ -0,0 +1,87 @@
+use chrono::{DateTime, Local};
+use sqlx::MySqlPool;
+
+use crate::domain::{
+    entities::refresh_token::RefreshToken, error::AppResult,
+    repository::refresh_token::RefreshTokenRepository,
+};
+
+pub struct MySqlRefreshTokenRepository {
+    pool: MySqlPool,
+}
+
+impl MySqlRefreshTokenRepository {
+    pub fn new(pool: MySqlPool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait::async_trait]
+impl RefreshTokenRepository for MySqlRefreshTokenRepository {
+    async fn create(&self, token: &RefreshToken) -> AppResult<RefreshToken> {
+        let query = "INSERT INTO refresh_tokens (user_id, family_id, token_hash, created_at, expires_at) VALUES (?, ?, ?, ?, ?)";
+        let res = sqlx::query(query)
+            .bind(token.user_id)
+            .bind(token.family_id.clone())
+            .bind(token.token_hash.clone())
+            .bind(token.created_at)
+            .bind(token.expires_at)
+            .execute(&self.pool)
+            .await?;
+        Ok(RefreshToken {
+            id: res.last_insert_id() as i32,
+            ..token.clone()
+        })
+    }
+
+    async fn get_by_hash(&self, token_hash: &str) -> AppResult<Option<RefreshToken>> {
+        let query = "SELECT * FROM refresh_tokens WHERE token_hash = ?";
+        let token = sqlx::query_as::<_, RefreshToken>(query)
+            .bind(token_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(token)
+    }
+
+    async fn mark_used(&self, id: i32, used_at: DateTime<Local>) -> AppResult<bool> {
+        let query = "UPDATE refresh_tokens SET used_at = ? WHERE id = ? AND used_at IS NULL AND revoked_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(used_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn revoke_family(&self, family_id: &str, revoked_at: DateTime<Local>) -> AppResult<u64> {
+        let query =
+            "UPDATE refresh_tokens SET revoked_at = ? WHERE family_id = ? AND revoked_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(revoked_at)
+            .bind(family_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    async fn revoke_all_by_user_id(
+        &self,
+        user_id: i32,
+        revoked_at: DateTime<Local>,
+    ) -> AppResult<u64> {
+        let query =
+            "UPDATE refresh_tokens SET revoked_at = ? WHERE user_id = ? AND revoked_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(revoked_at)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let query = "DELETE FROM refresh_tokens WHERE expires_at < ?";
+        let res = sqlx::query(query).bind(cutoff).execute(&self.pool).await?;
+        Ok(res.rows_affected())
+    }
+}
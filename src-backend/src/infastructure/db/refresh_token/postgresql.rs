@@ -0,0 +1,84 @@
+use chrono::{DateTime, Local};
+use sqlx::PgPool;
+
+use crate::domain::{
+    entities::refresh_token::RefreshToken, error::AppResult,
+    repository::refresh_token::RefreshTokenRepository,
+};
+
+pub struct PgRefreshTokenRepository {
+    pool: PgPool,
+}
+
+impl PgRefreshTokenRepository {
+    pub fn new(pool: PgPool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait::async_trait]
+impl RefreshTokenRepository for PgRefreshTokenRepository {
+    async fn create(&self, token: &RefreshToken) -> AppResult<RefreshToken> {
+        let query = "INSERT INTO refresh_tokens (user_id, family_id, token_hash, created_at, expires_at) VALUES ($1, $2, $3, $4, $5) RETURNING *";
+        let token = sqlx::query_as::<_, RefreshToken>(query)
+            .bind(token.user_id)
+            .bind(token.family_id.clone())
+            .bind(token.token_hash.clone())
+            .bind(token.created_at)
+            .bind(token.expires_at)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(token)
+    }
+
+    async fn get_by_hash(&self, token_hash: &str) -> AppResult<Option<RefreshToken>> {
+        let query = "SELECT * FROM refresh_tokens WHERE token_hash = $1";
+        let token = sqlx::query_as::<_, RefreshToken>(query)
+            .bind(token_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(token)
+    }
+
+    async fn mark_used(&self, id: i32, used_at: DateTime<Local>) -> AppResult<bool> {
+        let query = "UPDATE refresh_tokens SET used_at = $1 WHERE id = $2 AND used_at IS NULL AND revoked_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(used_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn revoke_family(&self, family_id: &str, revoked_at: DateTime<Local>) -> AppResult<u64> {
+        let query =
+            "UPDATE refresh_tokens SET revoked_at = $1 WHERE family_id = $2 AND revoked_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(revoked_at)
+            .bind(family_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    async fn revoke_all_by_user_id(
+        &self,
+        user_id: i32,
+        revoked_at: DateTime<Local>,
+    ) -> AppResult<u64> {
+        let query =
+            "UPDATE refresh_tokens SET revoked_at = $1 WHERE user_id = $2 AND revoked_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(revoked_at)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let query = "DELETE FROM refresh_tokens WHERE expires_at < $1";
+        let res = sqlx::query(query).bind(cutoff).execute(&self.pool).await?;
+        Ok(res.rows_affected())
+    }
+}
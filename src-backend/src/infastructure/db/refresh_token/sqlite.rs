@@ -0,0 +1,161 @@
+use chrono::{DateTime, Local};
+use sqlx::SqlitePool;
+
+use crate::domain::{
+    entities::refresh_token::RefreshToken, error::AppResult,
+    repository::refresh_token::RefreshTokenRepository,
+};
+
+pub struct SqliteRefreshTokenRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteRefreshTokenRepository {
+    pub fn new(pool: SqlitePool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait::async_trait]
+impl RefreshTokenRepository for SqliteRefreshTokenRepository {
+    async fn create(&self, token: &RefreshToken) -> AppResult<RefreshToken> {
+        let query = "INSERT INTO refresh_tokens (user_id, family_id, token_hash, created_at, expires_at) VALUES (?, ?, ?, ?, ?) RETURNING *";
+        let token = sqlx::query_as::<_, RefreshToken>(query)
+            .bind(token.user_id)
+            .bind(token.family_id.clone())
+            .bind(token.token_hash.clone())
+            .bind(token.created_at)
+            .bind(token.expires_at)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(token)
+    }
+
+    async fn get_by_hash(&self, token_hash: &str) -> AppResult<Option<RefreshToken>> {
+        let query = "SELECT * FROM refresh_tokens WHERE token_hash = ?";
+        let token = sqlx::query_as::<_, RefreshToken>(query)
+            .bind(token_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(token)
+    }
+
+    async fn mark_used(&self, id: i32, used_at: DateTime<Local>) -> AppResult<bool> {
+        let query = "UPDATE refresh_tokens SET used_at = ? WHERE id = ? AND used_at IS NULL AND revoked_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(used_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn revoke_family(&self, family_id: &str, revoked_at: DateTime<Local>) -> AppResult<u64> {
+        let query =
+            "UPDATE refresh_tokens SET revoked_at = ? WHERE family_id = ? AND revoked_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(revoked_at)
+            .bind(family_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    async fn revoke_all_by_user_id(
+        &self,
+        user_id: i32,
+        revoked_at: DateTime<Local>,
+    ) -> AppResult<u64> {
+        let query =
+            "UPDATE refresh_tokens SET revoked_at = ? WHERE user_id = ? AND revoked_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(revoked_at)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let query = "DELETE FROM refresh_tokens WHERE expires_at < ?";
+        let res = sqlx::query(query).bind(cutoff).execute(&self.pool).await?;
+        Ok(res.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::infastructure::db::SQLITE_MIGRATOR;
+
+    async fn setup() -> SqliteRefreshTokenRepository {
+        // 内存数据库只在单个连接内可见
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        SQLITE_MIGRATOR.run(&pool).await.unwrap();
+        SqliteRefreshTokenRepository::new(pool).unwrap()
+    }
+
+    fn new_token(user_id: i32, family_id: &str, token_hash: &str) -> RefreshToken {
+        RefreshToken::new(
+            user_id,
+            family_id.to_string(),
+            token_hash.to_string(),
+            Local::now(),
+            Local::now() + Duration::days(1),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_mark_used_only_once() {
+        let repo = setup().await;
+        let token = repo.create(&new_token(1, "family", "hash")).await.unwrap();
+        assert!(token.id > 0);
+
+        assert!(repo.mark_used(token.id, Local::now()).await.unwrap());
+        assert!(!repo.mark_used(token.id, Local::now()).await.unwrap());
+        let found = repo.get_by_hash("hash").await.unwrap().unwrap();
+        assert!(found.used_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_revoke_family_and_user() {
+        let repo = setup().await;
+        repo.create(&new_token(1, "a", "a1")).await.unwrap();
+        repo.create(&new_token(1, "a", "a2")).await.unwrap();
+        repo.create(&new_token(1, "b", "b1")).await.unwrap();
+        repo.create(&new_token(2, "c", "c1")).await.unwrap();
+
+        assert_eq!(repo.revoke_family("a", Local::now()).await.unwrap(), 2);
+        assert!(repo
+            .get_by_hash("b1")
+            .await
+            .unwrap()
+            .unwrap()
+            .revoked_at
+            .is_none());
+        assert_eq!(
+            repo.revoke_all_by_user_id(1, Local::now()).await.unwrap(),
+            1
+        );
+        assert!(repo
+            .get_by_hash("c1")
+            .await
+            .unwrap()
+            .unwrap()
+            .revoked_at
+            .is_none());
+
+        let purged = repo
+            .purge_expired_before(Local::now() + Duration::days(2))
+            .await
+            .unwrap();
+        assert_eq!(purged, 4);
+    }
+}
@@ -0,0 +1,158 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Local};
+
+use crate::{
+    domain::{
+        entities::session::Session,
+        error::{AppError, AppResult},
+        repository::session::SessionRepository,
+    },
+    infastructure::db::user::memory::UserOwnedStore,
+};
+
+#[derive(Default)]
+struct Store {
+    sessions: BTreeMap<i32, Session>,
+    next_id: i32,
+}
+
+// 基于内存的实现, 用于单元测试和接口测试, 克隆后共享同一份数据
+#[derive(Default, Clone)]
+pub struct InMemorySessionRepository {
+    store: Arc<Mutex<Store>>,
+}
+
+impl InMemorySessionRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl SessionRepository for InMemorySessionRepository {
+    async fn create(&self, session: &Session) -> AppResult<Session> {
+        let mut store = self.store.lock().unwrap();
+        if store
+            .sessions
+            .values()
+            .any(|existing| existing.family_id == session.family_id)
+        {
+            return Err(AppError::Conflict("Record already exists".to_string()));
+        }
+        store.next_id += 1;
+        let session = Session {
+            id: store.next_id,
+            ..session.clone()
+        };
+        store.sessions.insert(session.id, session.clone());
+        Ok(session)
+    }
+
+    async fn get(&self, id: i32) -> AppResult<Option<Session>> {
+        let store = self.store.lock().unwrap();
+        Ok(store.sessions.get(&id).cloned())
+    }
+
+    async fn get_by_family_id(&self, family_id: &str) -> AppResult<Option<Session>> {
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .sessions
+            .values()
+            .find(|session| session.family_id == family_id)
+            .cloned())
+    }
+
+    async fn list_active_by_user_id(
+        &self,
+        user_id: i32,
+        now: DateTime<Local>,
+    ) -> AppResult<Vec<Session>> {
+        let store = self.store.lock().unwrap();
+        let mut sessions: Vec<Session> = store
+            .sessions
+            .values()
+            .filter(|session| session.user_id == user_id && session.is_active(now))
+            .cloned()
+            .collect();
+        sessions.sort_by(|a, b| {
+            b.last_active_at
+                .cmp(&a.last_active_at)
+                .then(b.id.cmp(&a.id))
+        });
+        Ok(sessions)
+    }
+
+    async fn touch(
+        &self,
+        id: i32,
+        ip_address: &str,
+        last_active_at: DateTime<Local>,
+        expires_at: DateTime<Local>,
+    ) -> AppResult<()> {
+        let mut store = self.store.lock().unwrap();
+        if let Some(session) = store.sessions.get_mut(&id) {
+            session.ip_address = ip_address.to_string();
+            session.last_active_at = last_active_at;
+            session.expires_at = expires_at;
+        }
+        Ok(())
+    }
+
+    async fn revoke(&self, id: i32, revoked_at: DateTime<Local>) -> AppResult<bool> {
+        let mut store = self.store.lock().unwrap();
+        match store.sessions.get_mut(&id) {
+            Some(session) if session.revoked_at.is_none() => {
+                session.revoked_at = Some(revoked_at);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn revoke_all_by_user_id(
+        &self,
+        user_id: i32,
+        revoked_at: DateTime<Local>,
+    ) -> AppResult<u64> {
+        let mut store = self.store.lock().unwrap();
+        let mut count = 0;
+        for session in store.sessions.values_mut() {
+            if session.user_id == user_id && session.revoked_at.is_none() {
+                session.revoked_at = Some(revoked_at);
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let mut store = self.store.lock().unwrap();
+        let before = store.sessions.len();
+        store
+            .sessions
+            .retain(|_, session| session.expires_at >= cutoff);
+        Ok((before - store.sessions.len()) as u64)
+    }
+}
+
+impl UserOwnedStore for InMemorySessionRepository {
+    fn delete_user(&self, user_id: i32, at: DateTime<Local>) {
+        let mut store = self.store.lock().unwrap();
+        for session in store.sessions.values_mut() {
+            if session.user_id == user_id && session.revoked_at.is_none() {
+                session.revoked_at = Some(at);
+            }
+        }
+    }
+
+    fn purge_users(&self, user_ids: &[i32]) {
+        let mut store = self.store.lock().unwrap();
+        store
+            .sessions
+            .retain(|_, session| !user_ids.contains(&session.user_id));
+    }
+}
@@ -0,0 +1,116 @@
+use chrono::{DateTime, Local};
+use sqlx::PgPool;
+
+use crate::domain::{
+    entities::session::Session, error::AppResult, repository::session::SessionRepository,
+};
+
+pub struct PgSessionRepository {
+    pool: PgPool,
+}
+
+impl PgSessionRepository {
+    pub fn new(pool: PgPool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait::async_trait]
+impl SessionRepository for PgSessionRepository {
+    async fn create(&self, session: &Session) -> AppResult<Session> {
+        let query = "INSERT INTO sessions (user_id, family_id, device_name, user_agent, ip_address, created_at, last_active_at, expires_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *";
+        let session = sqlx::query_as::<_, Session>(query)
+            .bind(session.user_id)
+            .bind(session.family_id.clone())
+            .bind(session.device_name.clone())
+            .bind(session.user_agent.clone())
+            .bind(session.ip_address.clone())
+            .bind(session.created_at)
+            .bind(session.last_active_at)
+            .bind(session.expires_at)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(session)
+    }
+
+    async fn get(&self, id: i32) -> AppResult<Option<Session>> {
+        let query = "SELECT * FROM sessions WHERE id = $1";
+        let session = sqlx::query_as::<_, Session>(query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(session)
+    }
+
+    async fn get_by_family_id(&self, family_id: &str) -> AppResult<Option<Session>> {
+        let query = "SELECT * FROM sessions WHERE family_id = $1";
+        let session = sqlx::query_as::<_, Session>(query)
+            .bind(family_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(session)
+    }
+
+    async fn list_active_by_user_id(
+        &self,
+        user_id: i32,
+        now: DateTime<Local>,
+    ) -> AppResult<Vec<Session>> {
+        let query = "SELECT * FROM sessions WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > $2 ORDER BY last_active_at DESC, id DESC";
+        let sessions = sqlx::query_as::<_, Session>(query)
+            .bind(user_id)
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(sessions)
+    }
+
+    async fn touch(
+        &self,
+        id: i32,
+        ip_address: &str,
+        last_active_at: DateTime<Local>,
+        expires_at: DateTime<Local>,
+    ) -> AppResult<()> {
+        let query =
+            "UPDATE sessions SET ip_address = $1, last_active_at = $2, expires_at = $3 WHERE id = $4";
+        sqlx::query(query)
+            .bind(ip_address)
+            .bind(last_active_at)
+            .bind(expires_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn revoke(&self, id: i32, revoked_at: DateTime<Local>) -> AppResult<bool> {
+        let query = "UPDATE sessions SET revoked_at = $1 WHERE id = $2 AND revoked_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(revoked_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn revoke_all_by_user_id(
+        &self,
+        user_id: i32,
+        revoked_at: DateTime<Local>,
+    ) -> AppResult<u64> {
+        let query = "UPDATE sessions SET revoked_at = $1 WHERE user_id = $2 AND revoked_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(revoked_at)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let query = "DELETE FROM sessions WHERE expires_at < $1";
+        let res = sqlx::query(query).bind(cutoff).execute(&self.pool).await?;
+        Ok(res.rows_affected())
+    }
+}
@@ -0,0 +1,217 @@
+use chrono::{DateTime, Local};
+use sqlx::SqlitePool;
+
+use crate::domain::{
+    entities::session::Session, error::AppResult, repository::session::SessionRepository,
+};
+
+pub struct SqliteSessionRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteSessionRepository {
+    pub fn new(pool: SqlitePool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait::async_trait]
+impl SessionRepository for SqliteSessionRepository {
+    async fn create(&self, session: &Session) -> AppResult<Session> {
+        let query = "INSERT INTO sessions (user_id, family_id, device_name, user_agent, ip_address, created_at, last_active_at, expires_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?) RETURNING *";
+        let session = sqlx::query_as::<_, Session>(query)
+            .bind(session.user_id)
+            .bind(session.family_id.clone())
+            .bind(session.device_name.clone())
+            .bind(session.user_agent.clone())
+            .bind(session.ip_address.clone())
+            .bind(session.created_at)
+            .bind(session.last_active_at)
+            .bind(session.expires_at)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(session)
+    }
+
+    async fn get(&self, id: i32) -> AppResult<Option<Session>> {
+        let query = "SELECT * FROM sessions WHERE id = ?";
+        let session = sqlx::query_as::<_, Session>(query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(session)
+    }
+
+    async fn get_by_family_id(&self, family_id: &str) -> AppResult<Option<Session>> {
+        let query = "SELECT * FROM sessions WHERE family_id = ?";
+        let session = sqlx::query_as::<_, Session>(query)
+            .bind(family_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(session)
+    }
+
+    async fn list_active_by_user_id(
+        &self,
+        user_id: i32,
+        now: DateTime<Local>,
+    ) -> AppResult<Vec<Session>> {
+        let query = "SELECT * FROM sessions WHERE user_id = ? AND revoked_at IS NULL AND expires_at > ? ORDER BY last_active_at DESC, id DESC";
+        let sessions = sqlx::query_as::<_, Session>(query)
+            .bind(user_id)
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(sessions)
+    }
+
+    async fn touch(
+        &self,
+        id: i32,
+        ip_address: &str,
+        last_active_at: DateTime<Local>,
+        expires_at: DateTime<Local>,
+    ) -> AppResult<()> {
+        let query =
+            "UPDATE sessions SET ip_address = ?, last_active_at = ?, expires_at = ? WHERE id = ?";
+        sqlx::query(query)
+            .bind(ip_address)
+            .bind(last_active_at)
+            .bind(expires_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn revoke(&self, id: i32, revoked_at: DateTime<Local>) -> AppResult<bool> {
+        let query = "UPDATE sessions SET revoked_at = ? WHERE id = ? AND revoked_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(revoked_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn revoke_all_by_user_id(
+        &self,
+        user_id: i32,
+        revoked_at: DateTime<Local>,
+    ) -> AppResult<u64> {
+        let query = "UPDATE sessions SET revoked_at = ? WHERE user_id = ? AND revoked_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(revoked_at)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let query = "DELETE FROM sessions WHERE expires_at < ?";
+        let res = sqlx::query(query).bind(cutoff).execute(&self.pool).await?;
+        Ok(res.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::infastructure::db::SQLITE_MIGRATOR;
+
+    async fn setup() -> SqliteSessionRepository {
+        // 内存数据库只在单个连接内可见
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        SQLITE_MIGRATOR.run(&pool).await.unwrap();
+        SqliteSessionRepository::new(pool).unwrap()
+    }
+
+    fn new_session(user_id: i32, family_id: &str, expires_in: Duration) -> Session {
+        Session::new(
+            user_id,
+            family_id.to_string(),
+            Some("Laptop".to_string()),
+            Some("curl/8.0".to_string()),
+            "127.0.0.1".to_string(),
+            Local::now(),
+            Local::now() + expires_in,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_create_touch_and_revoke() {
+        let repo = setup().await;
+        let session = repo
+            .create(&new_session(1, "family", Duration::days(1)))
+            .await
+            .unwrap();
+        assert!(session.id > 0);
+        assert_eq!(session.device_name.as_deref(), Some("Laptop"));
+
+        let later = Local::now() + Duration::minutes(5);
+        repo.touch(session.id, "10.0.0.1", later, later + Duration::days(1))
+            .await
+            .unwrap();
+        let found = repo.get_by_family_id("family").await.unwrap().unwrap();
+        assert_eq!(found.id, session.id);
+        assert_eq!(found.ip_address, "10.0.0.1");
+        assert_eq!(found.last_active_at.timestamp(), later.timestamp());
+
+        assert!(repo.revoke(session.id, Local::now()).await.unwrap());
+        assert!(!repo.revoke(session.id, Local::now()).await.unwrap());
+        assert!(repo
+            .get(session.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .revoked_at
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_list_active_and_revoke_all() {
+        let repo = setup().await;
+        repo.create(&new_session(1, "a", Duration::days(1)))
+            .await
+            .unwrap();
+        repo.create(&new_session(1, "b", Duration::days(1)))
+            .await
+            .unwrap();
+        repo.create(&new_session(1, "expired", Duration::days(-1)))
+            .await
+            .unwrap();
+        repo.create(&new_session(2, "c", Duration::days(1)))
+            .await
+            .unwrap();
+
+        let active = repo.list_active_by_user_id(1, Local::now()).await.unwrap();
+        assert_eq!(active.len(), 2);
+        assert_eq!(
+            repo.revoke_all_by_user_id(1, Local::now()).await.unwrap(),
+            3
+        );
+        assert!(repo
+            .list_active_by_user_id(1, Local::now())
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            repo.list_active_by_user_id(2, Local::now())
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let purged = repo.purge_expired_before(Local::now()).await.unwrap();
+        assert_eq!(purged, 1);
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    domain::{
+        entities::tag::Tag,
+        error::{AppError, AppResult},
+        repository::tag::{TagFilter, TagMatch, TagRepository},
+    },
+    infastructure::db::user::memory::UserOwnedStore,
+};
+
+#[derive(Default)]
+struct Store {
+    tags: BTreeMap<i32, Tag>,
+    // (todo_id, tag_id)
+    todo_tags: BTreeSet<(i32, i32)>,
+    next_id: i32,
+}
+
+// 基于内存的实现, 用于单元测试和接口测试, 克隆后共享同一份数据
+#[derive(Default, Clone)]
+pub struct InMemoryTagRepository {
+    store: Arc<Mutex<Store>>,
+}
+
+impl InMemoryTagRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 带有 filter 中任意一个或全部标签的任务 id, 供内存中的 todo 仓储过滤使用
+    pub fn todo_ids_by_tags(&self, user_id: i32, filter: &TagFilter) -> Vec<i32> {
+        let store = self.store.lock().unwrap();
+        let mut counts: BTreeMap<i32, usize> = BTreeMap::new();
+        for (todo_id, tag_id) in &store.todo_tags {
+            let owned = store
+                .tags
+                .get(tag_id)
+                .is_some_and(|tag| tag.user_id == user_id);
+            if owned && filter.tag_ids.contains(tag_id) {
+                *counts.entry(*todo_id).or_default() += 1;
+            }
+        }
+        counts
+            .into_iter()
+            .filter(|(_, count)| filter.mode == TagMatch::Any || *count == filter.tag_ids.len())
+            .map(|(todo_id, _)| todo_id)
+            .collect()
+    }
+
+    // 任务被物理删除时解除其所有标签
+    pub fn detach_todos(&self, todo_ids: &[i32]) {
+        let mut store = self.store.lock().unwrap();
+        store
+            .todo_tags
+            .retain(|(todo_id, _)| !todo_ids.contains(todo_id));
+    }
+}
+
+fn sort_by_name(tags: &mut [Tag]) {
+    tags.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+}
+
+fn name_taken(store: &Store, tag: &Tag) -> bool {
+    store.tags.values().any(|existing| {
+        existing.id != tag.id && existing.user_id == tag.user_id && existing.name == tag.name
+    })
+}
+
+#[async_trait::async_trait]
+impl TagRepository for InMemoryTagRepository {
+    async fn list_by_user_id(&self, user_id: i32) -> AppResult<Vec<Tag>> {
+        let store = self.store.lock().unwrap();
+        let mut tags: Vec<Tag> = store
+            .tags
+            .values()
+            .filter(|tag| tag.user_id == user_id)
+            .cloned()
+            .collect();
+        sort_by_name(&mut tags);
+        Ok(tags)
+    }
+
+    async fn get_by_id(&self, user_id: i32, id: i32) -> AppResult<Option<Tag>> {
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .tags
+            .get(&id)
+            .filter(|tag| tag.user_id == user_id)
+            .cloned())
+    }
+
+    async fn create(&self, tag: &Tag) -> AppResult<Tag> {
+        let mut store = self.store.lock().unwrap();
+        if name_taken(&store, tag) {
+            return Err(AppError::Conflict("Record already exists".to_string()));
+        }
+        store.next_id += 1;
+        let tag = Tag {
+            id: store.next_id,
+            ..tag.clone()
+        };
+        store.tags.insert(tag.id, tag.clone());
+        Ok(tag)
+    }
+
+    async fn save(&self, tag: Tag) -> AppResult<()> {
+        let mut store = self.store.lock().unwrap();
+        if name_taken(&store, &tag) {
+            return Err(AppError::Conflict("Record already exists".to_string()));
+        }
+        if let Some(existing) = store.tags.get_mut(&tag.id) {
+            if existing.user_id == tag.user_id {
+                *existing = tag;
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, user_id: i32, id: i32) -> AppResult<bool> {
+        let mut store = self.store.lock().unwrap();
+        if store.tags.get(&id).is_none_or(|tag| tag.user_id != user_id) {
+            return Ok(false);
+        }
+        store.tags.remove(&id);
+        store.todo_tags.retain(|(_, tag_id)| *tag_id != id);
+        Ok(true)
+    }
+
+    async fn attach(&self, todo_id: i32, tag_id: i32) -> AppResult<bool> {
+        let mut store = self.store.lock().unwrap();
+        Ok(store.todo_tags.insert((todo_id, tag_id)))
+    }
+
+    async fn detach(&self, todo_id: i32, tag_id: i32) -> AppResult<bool> {
+        let mut store = self.store.lock().unwrap();
+        Ok(store.todo_tags.remove(&(todo_id, tag_id)))
+    }
+
+    async fn list_by_todo_id(&self, todo_id: i32) -> AppResult<Vec<Tag>> {
+        let store = self.store.lock().unwrap();
+        let mut tags: Vec<Tag> = store
+            .todo_tags
+            .iter()
+            .filter(|(id, _)| *id == todo_id)
+            .filter_map(|(_, tag_id)| store.tags.get(tag_id).cloned())
+            .collect();
+        sort_by_name(&mut tags);
+        Ok(tags)
+    }
+}
+
+impl UserOwnedStore for InMemoryTagRepository {
+    fn purge_users(&self, user_ids: &[i32]) {
+        let mut store = self.store.lock().unwrap();
+        let removed: Vec<i32> = store
+            .tags
+            .values()
+            .filter(|tag| user_ids.contains(&tag.user_id))
+            .map(|tag| tag.id)
+            .collect();
+        store.tags.retain(|id, _| !removed.contains(id));
+        store
+            .todo_tags
+            .retain(|(_, tag_id)| !removed.contains(tag_id));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod memory;
+pub mod mysql;
+pub mod postgresql;
+pub mod sqlite;
@@ -0,0 +1,110 @@
+use sqlx::PgPool;
+
+use crate::domain::{entities::tag::Tag, error::AppResult, repository::tag::TagRepository};
+
+pub struct PgTagRepository {
+    pool: PgPool,
+}
+
+impl PgTagRepository {
+    pub fn new(pool: PgPool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait::async_trait]
+impl TagRepository for PgTagRepository {
+    async fn list_by_user_id(&self, user_id: i32) -> AppResult<Vec<Tag>> {
+        let query = "SELECT * FROM tags WHERE user_id = $1 ORDER BY name, id";
+        let tags = sqlx::query_as::<_, Tag>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(tags)
+    }
+
+    async fn get_by_id(&self, user_id: i32, id: i32) -> AppResult<Option<Tag>> {
+        let query = "SELECT * FROM tags WHERE id = $1 AND user_id = $2";
+        let tag = sqlx::query_as::<_, Tag>(query)
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(tag)
+    }
+
+    async fn create(&self, tag: &Tag) -> AppResult<Tag> {
+        let query = "INSERT INTO tags (user_id, name, color, created_at, updated_at) VALUES ($1, $2, $3, $4, $5) RETURNING *";
+        let tag = sqlx::query_as::<_, Tag>(query)
+            .bind(tag.user_id)
+            .bind(tag.name.clone())
+            .bind(tag.color.clone())
+            .bind(tag.created_at)
+            .bind(tag.updated_at)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(tag)
+    }
+
+    async fn save(&self, tag: Tag) -> AppResult<()> {
+        let query =
+            "UPDATE tags SET name = $1, color = $2, updated_at = $3 WHERE id = $4 AND user_id = $5";
+        sqlx::query(query)
+            .bind(tag.name)
+            .bind(tag.color)
+            .bind(tag.updated_at)
+            .bind(tag.id)
+            .bind(tag.user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, user_id: i32, id: i32) -> AppResult<bool> {
+        let mut tx = self.pool.begin().await?;
+        let res = sqlx::query("DELETE FROM tags WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Ok(false);
+        }
+        sqlx::query("DELETE FROM todo_tags WHERE tag_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn attach(&self, todo_id: i32, tag_id: i32) -> AppResult<bool> {
+        let query =
+            "INSERT INTO todo_tags (todo_id, tag_id) VALUES ($1, $2) ON CONFLICT DO NOTHING";
+        let res = sqlx::query(query)
+            .bind(todo_id)
+            .bind(tag_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn detach(&self, todo_id: i32, tag_id: i32) -> AppResult<bool> {
+        let query = "DELETE FROM todo_tags WHERE todo_id = $1 AND tag_id = $2";
+        let res = sqlx::query(query)
+            .bind(todo_id)
+            .bind(tag_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn list_by_todo_id(&self, todo_id: i32) -> AppResult<Vec<Tag>> {
+        let query = "SELECT tags.* FROM tags JOIN todo_tags ON todo_tags.tag_id = tags.id WHERE todo_tags.todo_id = $1 ORDER BY tags.name, tags.id";
+        let tags = sqlx::query_as::<_, Tag>(query)
+            .bind(todo_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(tags)
+    }
+}
@@ -0,0 +1,191 @@
+use sqlx::SqlitePool;
+
+use crate::domain::{entities::tag::Tag, error::AppResult, repository::tag::TagRepository};
+
+pub struct SqliteTagRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteTagRepository {
+    pub fn new(pool: SqlitePool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait::async_trait]
+impl TagRepository for SqliteTagRepository {
+    async fn list_by_user_id(&self, user_id: i32) -> AppResult<Vec<Tag>> {
+        let query = "SELECT * FROM tags WHERE user_id = ? ORDER BY name, id";
+        let tags = sqlx::query_as::<_, Tag>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(tags)
+    }
+
+    async fn get_by_id(&self, user_id: i32, id: i32) -> AppResult<Option<Tag>> {
+        let query = "SELECT * FROM tags WHERE id = ? AND user_id = ?";
+        let tag = sqlx::query_as::<_, Tag>(query)
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(tag)
+    }
+
+    async fn create(&self, tag: &Tag) -> AppResult<Tag> {
+        let query = "INSERT INTO tags (user_id, name, color, created_at, updated_at) VALUES (?, ?, ?, ?, ?) RETURNING *";
+        let tag = sqlx::query_as::<_, Tag>(query)
+            .bind(tag.user_id)
+            .bind(tag.name.clone())
+            .bind(tag.color.clone())
+            .bind(tag.created_at)
+            .bind(tag.updated_at)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(tag)
+    }
+
+    async fn save(&self, tag: Tag) -> AppResult<()> {
+        let query =
+            "UPDATE tags SET name = ?, color = ?, updated_at = ? WHERE id = ? AND user_id = ?";
+        sqlx::query(query)
+            .bind(tag.name)
+            .bind(tag.color)
+            .bind(tag.updated_at)
+            .bind(tag.id)
+            .bind(tag.user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, user_id: i32, id: i32) -> AppResult<bool> {
+        let mut tx = self.pool.begin().await?;
+        let res = sqlx::query("DELETE FROM tags WHERE id = ? AND user_id = ?")
+            .bind(id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Ok(false);
+        }
+        sqlx::query("DELETE FROM todo_tags WHERE tag_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn attach(&self, todo_id: i32, tag_id: i32) -> AppResult<bool> {
+        let query = "INSERT OR IGNORE INTO todo_tags (todo_id, tag_id) VALUES (?, ?)";
+        let res = sqlx::query(query)
+            .bind(todo_id)
+            .bind(tag_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn detach(&self, todo_id: i32, tag_id: i32) -> AppResult<bool> {
+        let query = "DELETE FROM todo_tags WHERE todo_id = ? AND tag_id = ?";
+        let res = sqlx::query(query)
+            .bind(todo_id)
+            .bind(tag_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn list_by_todo_id(&self, todo_id: i32) -> AppResult<Vec<Tag>> {
+        let query = "SELECT tags.* FROM tags JOIN todo_tags ON todo_tags.tag_id = tags.id WHERE todo_tags.todo_id = ? ORDER BY tags.name, tags.id";
+        let tags = sqlx::query_as::<_, Tag>(query)
+            .bind(todo_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(tags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::{domain::error::AppError, infastructure::db::SQLITE_MIGRATOR};
+
+    async fn setup() -> SqliteTagRepository {
+        // 内存数据库只在单个连接内可见
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        SQLITE_MIGRATOR.run(&pool).await.unwrap();
+        SqliteTagRepository::new(pool).unwrap()
+    }
+
+    fn new_tag(user_id: i32, name: &str) -> Tag {
+        Tag::new(
+            user_id,
+            name.to_string(),
+            "#ff0000".to_string(),
+            Local::now(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_create_and_rename() {
+        let repo = setup().await;
+        let work = repo.create(&new_tag(1, "work")).await.unwrap();
+        repo.create(&new_tag(1, "home")).await.unwrap();
+        // 不同用户可以使用相同的名称
+        repo.create(&new_tag(2, "work")).await.unwrap();
+        assert!(matches!(
+            repo.create(&new_tag(1, "work")).await,
+            Err(AppError::Conflict(_))
+        ));
+
+        let names: Vec<String> = repo
+            .list_by_user_id(1)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect();
+        assert_eq!(names, vec!["home", "work"]);
+
+        repo.save(Tag {
+            name: "office".to_string(),
+            ..work.clone()
+        })
+        .await
+        .unwrap();
+        let found = repo.get_by_id(1, work.id).await.unwrap().unwrap();
+        assert_eq!(found.name, "office");
+        assert!(repo.get_by_id(2, work.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_attach_and_delete() {
+        let repo = setup().await;
+        let work = repo.create(&new_tag(1, "work")).await.unwrap();
+        let urgent = repo.create(&new_tag(1, "urgent")).await.unwrap();
+        let foreign = repo.create(&new_tag(2, "work")).await.unwrap();
+
+        assert!(repo.attach(10, work.id).await.unwrap());
+        assert!(!repo.attach(10, work.id).await.unwrap());
+        repo.attach(10, urgent.id).await.unwrap();
+        repo.attach(11, work.id).await.unwrap();
+        repo.attach(12, foreign.id).await.unwrap();
+        assert_eq!(repo.list_by_todo_id(10).await.unwrap().len(), 2);
+
+        assert!(repo.detach(10, urgent.id).await.unwrap());
+        assert!(!repo.delete(2, work.id).await.unwrap());
+        assert!(repo.delete(1, work.id).await.unwrap());
+        assert!(repo.list_by_todo_id(10).await.unwrap().is_empty());
+        assert!(repo.list_by_todo_id(11).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,232 @@
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Local};
+
+use crate::{
+    domain::{
+        entities::todo::Todo,
+        error::AppResult,
+        repository::{
+            todo::{TodoFilter, TodoQuery, TodoRepository, TodoSortField},
+            Page, SortOrder,
+        },
+    },
+    infastructure::db::{tag::memory::InMemoryTagRepository, user::memory::UserOwnedStore},
+};
+
+#[derive(Default)]
+struct Store {
+    todos: BTreeMap<i32, Todo>,
+    next_id: i32,
+}
+
+// 基于内存的实现, 用于单元测试和接口测试, 克隆后共享同一份数据
+#[derive(Default, Clone)]
+pub struct InMemoryTodoRepository {
+    store: Arc<Mutex<Store>>,
+    tags: InMemoryTagRepository,
+}
+
+impl InMemoryTodoRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 共享标签仓储的数据, 用于按标签过滤和清理关联
+    pub fn with_tags(mut self, tags: InMemoryTagRepository) -> Self {
+        self.tags = tags;
+        self
+    }
+}
+
+fn matches_filter(todo: &Todo, filter: &TodoFilter, tagged: Option<&[i32]>) -> bool {
+    filter
+        .status
+        .is_none_or(|status| todo.status as i16 == status as i16)
+        && filter
+            .priority
+            .is_none_or(|priority| todo.priority as i16 == priority as i16)
+        && filter.done.is_none_or(|done| todo.done == done)
+        && filter
+            .deadline_from
+            .is_none_or(|from| todo.deadline.is_some_and(|deadline| deadline >= from))
+        && filter
+            .deadline_to
+            .is_none_or(|to| todo.deadline.is_some_and(|deadline| deadline <= to))
+        && tagged.is_none_or(|ids| ids.contains(&todo.id))
+}
+
+fn compare(a: &Todo, b: &Todo, field: TodoSortField) -> Ordering {
+    match field {
+        TodoSortField::CreatedAt => a.created_at.cmp(&b.created_at),
+        TodoSortField::Deadline => a.deadline.cmp(&b.deadline),
+        TodoSortField::Priority => (a.priority as i16).cmp(&(b.priority as i16)),
+        TodoSortField::Status => (a.status as i16).cmp(&(b.status as i16)),
+        TodoSortField::Title => a.title.cmp(&b.title),
+    }
+    .then(a.id.cmp(&b.id))
+}
+
+#[async_trait::async_trait]
+impl TodoRepository for InMemoryTodoRepository {
+    async fn get_all_by_user_id(&self, user_id: i32) -> AppResult<Vec<Todo>> {
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .todos
+            .values()
+            .filter(|todo| todo.user_id == user_id && todo.deleted_at.is_none())
+            .cloned()
+            .collect())
+    }
+    async fn list_by_user_id(&self, user_id: i32, query: &TodoQuery) -> AppResult<Page<Todo>> {
+        let tagged = query
+            .filter
+            .tags
+            .as_ref()
+            .map(|tags| self.tags.todo_ids_by_tags(user_id, tags));
+        let mut todos: Vec<Todo> = self
+            .get_all_by_user_id(user_id)
+            .await?
+            .into_iter()
+            .filter(|todo| matches_filter(todo, &query.filter, tagged.as_deref()))
+            .collect();
+        todos.sort_by(|a, b| match query.order {
+            SortOrder::Asc => compare(a, b, query.sort),
+            SortOrder::Desc => compare(b, a, query.sort),
+        });
+
+        let total = todos.len() as i64;
+        let items = todos
+            .into_iter()
+            .skip(query.offset() as usize)
+            .take(query.page_size as usize)
+            .collect();
+        Ok(Page {
+            items,
+            total,
+            page: query.page,
+            page_size: query.page_size,
+        })
+    }
+    async fn get_by_id(&self, user_id: i32, id: i32) -> AppResult<Option<Todo>> {
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .todos
+            .get(&id)
+            .filter(|todo| todo.user_id == user_id && todo.deleted_at.is_none())
+            .cloned())
+    }
+    async fn create(&self, todo: &Todo) -> AppResult<Todo> {
+        let mut store = self.store.lock().unwrap();
+        store.next_id += 1;
+        let todo = Todo {
+            id: store.next_id,
+            ..todo.clone()
+        };
+        store.todos.insert(todo.id, todo.clone());
+        Ok(todo)
+    }
+    async fn save(&self, todo: Todo) -> AppResult<()> {
+        let mut store = self.store.lock().unwrap();
+        if let Some(existing) = store.todos.get_mut(&todo.id) {
+            if existing.user_id == todo.user_id {
+                *existing = todo;
+            }
+        }
+        Ok(())
+    }
+    async fn delete(&self, user_id: i32, id: i32, descendants: &[i32]) -> AppResult<bool> {
+        let mut store = self.store.lock().unwrap();
+        if !store
+            .todos
+            .get(&id)
+            .is_some_and(|todo| todo.user_id == user_id && todo.deleted_at.is_none())
+        {
+            return Ok(false);
+        }
+        let now = Local::now();
+        for id in std::iter::once(&id).chain(descendants) {
+            if let Some(todo) = store.todos.get_mut(id) {
+                if todo.user_id == user_id && todo.deleted_at.is_none() {
+                    todo.deleted_at = Some(now);
+                }
+            }
+        }
+        Ok(true)
+    }
+    async fn get_trashed_by_user_id(&self, user_id: i32) -> AppResult<Vec<Todo>> {
+        let store = self.store.lock().unwrap();
+        let mut todos: Vec<Todo> = store
+            .todos
+            .values()
+            .filter(|todo| todo.user_id == user_id && todo.deleted_at.is_some())
+            .cloned()
+            .collect();
+        todos.sort_by_key(|todo| std::cmp::Reverse(todo.deleted_at));
+        Ok(todos)
+    }
+    async fn restore(
+        &self,
+        user_id: i32,
+        id: i32,
+        descendants: &[i32],
+        detach: bool,
+    ) -> AppResult<bool> {
+        let mut store = self.store.lock().unwrap();
+        match store.todos.get_mut(&id) {
+            Some(todo) if todo.user_id == user_id && todo.deleted_at.is_some() => {
+                if detach {
+                    todo.parent_id = None;
+                }
+            }
+            _ => return Ok(false),
+        }
+        let now = Local::now();
+        for id in std::iter::once(&id).chain(descendants) {
+            if let Some(todo) = store.todos.get_mut(id) {
+                if todo.user_id == user_id && todo.deleted_at.is_some() {
+                    todo.deleted_at = None;
+                    todo.updated_at = now;
+                }
+            }
+        }
+        Ok(true)
+    }
+    async fn purge_deleted_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let mut store = self.store.lock().unwrap();
+        let purged: Vec<i32> = store
+            .todos
+            .values()
+            .filter(|todo| {
+                todo.deleted_at
+                    .is_some_and(|deleted_at| deleted_at < cutoff)
+            })
+            .map(|todo| todo.id)
+            .collect();
+        store.todos.retain(|id, _| !purged.contains(id));
+        self.tags.detach_todos(&purged);
+        Ok(purged.len() as u64)
+    }
+}
+
+impl UserOwnedStore for InMemoryTodoRepository {
+    fn delete_user(&self, user_id: i32, at: DateTime<Local>) {
+        let mut store = self.store.lock().unwrap();
+        for todo in store.todos.values_mut() {
+            if todo.user_id == user_id && todo.deleted_at.is_none() {
+                todo.deleted_at = Some(at);
+            }
+        }
+    }
+
+    fn purge_users(&self, user_ids: &[i32]) {
+        let mut store = self.store.lock().unwrap();
+        store
+            .todos
+            .retain(|_, todo| !user_ids.contains(&todo.user_id));
+    }
+}
@@ -7,5 +7,7 @@
  * @FilePath: /mithril-rust/src-backend/src/infastructure/db/todo/mod.rs
  */
 
+pub mod memory;
 pub mod mysql;
 pub mod postgresql;
+pub mod sqlite;
@@ -1,9 +1,15 @@
-use anyhow::Result;
-use std::str::FromStr;
+use chrono::{DateTime, Local};
+use sqlx::{MySql, MySqlPool, QueryBuilder};
 
-use sqlx::{mysql::MySqlConnectOptions, ConnectOptions, MySqlPool};
-
-use crate::domain::{entities::todo::Todo, repository::todo::TodoRepository};
+use crate::domain::{
+    entities::todo::Todo,
+    error::AppResult,
+    repository::{
+        tag::TagMatch,
+        todo::{TodoFilter, TodoQuery, TodoRepository},
+        Page,
+    },
+};
 
 pub struct MySqlTodoRepository {
     pool: MySqlPool,
@@ -15,35 +21,102 @@ impl MySqlTodoRepository {
     }
 }
 
+fn push_conditions(builder: &mut QueryBuilder<'_, MySql>, user_id: i32, filter: &TodoFilter) {
+    builder
+        .push(" WHERE deleted_at IS NULL AND user_id = ")
+        .push_bind(user_id);
+    if let Some(status) = filter.status {
+        builder.push(" AND status = ").push_bind(status);
+    }
+    if let Some(priority) = filter.priority {
+        builder.push(" AND priority = ").push_bind(priority);
+    }
+    if let Some(done) = filter.done {
+        builder.push(" AND done = ").push_bind(done);
+    }
+    if let Some(deadline_from) = filter.deadline_from {
+        builder.push(" AND deadline >= ").push_bind(deadline_from);
+    }
+    if let Some(deadline_to) = filter.deadline_to {
+        builder.push(" AND deadline <= ").push_bind(deadline_to);
+    }
+    // 标签条件作为子查询, 只考虑属于该用户的标签
+    match filter.tags.as_ref() {
+        Some(tags) if tags.tag_ids.is_empty() => {
+            builder.push(" AND 1 = 0");
+        }
+        Some(tags) => {
+            builder
+                .push(" AND EXISTS (SELECT 1 FROM todo_tags JOIN tags ON tags.id = todo_tags.tag_id WHERE todo_tags.todo_id = todos.id AND tags.user_id = ")
+                .push_bind(user_id)
+                .push(" AND todo_tags.tag_id IN (");
+            let mut separated = builder.separated(", ");
+            for tag_id in &tags.tag_ids {
+                separated.push_bind(*tag_id);
+            }
+            builder.push(")");
+            if tags.mode == TagMatch::All {
+                builder
+                    .push(" GROUP BY todo_tags.todo_id HAVING COUNT(DISTINCT todo_tags.tag_id) = ")
+                    .push_bind(tags.tag_ids.len() as i64);
+            }
+            builder.push(")");
+        }
+        None => {}
+    }
+}
+
 #[async_trait::async_trait]
 impl TodoRepository for MySqlTodoRepository {
-    async fn get_all_by_user_id(&self, user_id: i32) -> Vec<Todo> {
-        let query = "SELECT * FROM todos WHERE user_id = ?";
-        if let Ok(todos) = sqlx::query_as::<_, Todo>(query)
+    async fn get_all_by_user_id(&self, user_id: i32) -> AppResult<Vec<Todo>> {
+        let query = "SELECT * FROM todos WHERE user_id = ? AND deleted_at IS NULL";
+        let todos = sqlx::query_as::<_, Todo>(query)
             .bind(user_id)
             .fetch_all(&self.pool)
-            .await
-        {
-            todos
-        } else {
-            Vec::new()
-        }
+            .await?;
+        Ok(todos)
     }
-    async fn get_by_id(&self, id: i32) -> Option<Todo> {
-        let query = "SELECT * FROM todos WHERE id = ?";
-        if let Ok(todo) = sqlx::query_as::<_, Todo>(query)
+    async fn list_by_user_id(&self, user_id: i32, query: &TodoQuery) -> AppResult<Page<Todo>> {
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM todos");
+        push_conditions(&mut count, user_id, &query.filter);
+        let total: i64 = count.build_query_scalar().fetch_one(&self.pool).await?;
+
+        let mut select = QueryBuilder::new("SELECT * FROM todos");
+        push_conditions(&mut select, user_id, &query.filter);
+        select.push(format!(
+            " ORDER BY {column} {order}, id {order}",
+            column = query.sort.column(),
+            order = query.order.as_sql()
+        ));
+        select
+            .push(" LIMIT ")
+            .push_bind(i64::from(query.page_size))
+            .push(" OFFSET ")
+            .push_bind(query.offset());
+        let items = select
+            .build_query_as::<Todo>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Page {
+            items,
+            total,
+            page: query.page,
+            page_size: query.page_size,
+        })
+    }
+    async fn get_by_id(&self, user_id: i32, id: i32) -> AppResult<Option<Todo>> {
+        let query = "SELECT * FROM todos WHERE id = ? AND user_id = ? AND deleted_at IS NULL";
+        let todo = sqlx::query_as::<_, Todo>(query)
             .bind(id)
-            .fetch_one(&self.pool)
-            .await
-        {
-            Some(todo)
-        } else {
-            None
-        }
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(todo)
     }
-    async fn create(&self, todo: &Todo) -> Result<Todo> {
-        let query = "INSERT INTO todos (user_id, title, description, status, priority, created_at, updated_at, deleted_at, deadline, done) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
-        if let Ok(res) = sqlx::query(query)
+    async fn create(&self, todo: &Todo) -> AppResult<Todo> {
+        let query = "INSERT INTO todos (user_id, title, description, status, priority, created_at, updated_at, deleted_at, deadline, done, parent_id, auto_complete) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
+        let res = sqlx::query(query)
             .bind(todo.user_id)
             .bind(todo.title.clone())
             .bind(todo.description.clone())
@@ -54,21 +127,18 @@ impl TodoRepository for MySqlTodoRepository {
             .bind(todo.deleted_at)
             .bind(todo.deadline)
             .bind(todo.done)
+            .bind(todo.parent_id)
+            .bind(todo.auto_complete)
             .execute(&self.pool)
-            .await
-        {
-            Ok(Todo {
-                id: res.last_insert_id() as i32,
-                ..todo.clone()
-            })
-        } else {
-            Err(anyhow::anyhow!("Failed to create todo"))
-        }
+            .await?;
+        Ok(Todo {
+            id: res.last_insert_id() as i32,
+            ..todo.clone()
+        })
     }
-    async fn save(&self, todo: Todo) -> Result<bool, sqlx::Error> {
-        let query = "UPDATE todos SET user_id = ?, title = ?, description = ?, status = ?, priority = ?, created_at = ?, updated_at = ?, deleted_at = ?, deadline = ?, done = ? WHERE id = ?";
-        if let Ok(_) = sqlx::query(query)
-            .bind(todo.user_id)
+    async fn save(&self, todo: Todo) -> AppResult<()> {
+        let query = "UPDATE todos SET title = ?, description = ?, status = ?, priority = ?, created_at = ?, updated_at = ?, deleted_at = ?, deadline = ?, done = ?, parent_id = ?, auto_complete = ? WHERE id = ? AND user_id = ?";
+        sqlx::query(query)
             .bind(todo.title)
             .bind(todo.description)
             .bind(todo.status)
@@ -78,22 +148,94 @@ impl TodoRepository for MySqlTodoRepository {
             .bind(todo.deleted_at)
             .bind(todo.deadline)
             .bind(todo.done)
+            .bind(todo.parent_id)
+            .bind(todo.auto_complete)
             .bind(todo.id)
+            .bind(todo.user_id)
             .execute(&self.pool)
-            .await
-        {
-            Ok(true)
-        } else {
-            Err(sqlx::Error::RowNotFound)
+            .await?;
+        Ok(())
+    }
+    async fn delete(&self, user_id: i32, id: i32, descendants: &[i32]) -> AppResult<bool> {
+        let now = Local::now();
+        let query =
+            "UPDATE todos SET deleted_at = ? WHERE id = ? AND user_id = ? AND deleted_at IS NULL";
+        let mut tx = self.pool.begin().await?;
+        let res = sqlx::query(query)
+            .bind(now)
+            .bind(id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Ok(false);
         }
+        for descendant in descendants {
+            sqlx::query(query)
+                .bind(now)
+                .bind(descendant)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(true)
     }
-    async fn delete(&self, id: i32) -> bool {
-        let query = "DELETE FROM todos WHERE id = ?";
-        if let Ok(_) = sqlx::query(query).bind(id).execute(&self.pool).await {
-            true
-        } else {
-            false
+    async fn get_trashed_by_user_id(&self, user_id: i32) -> AppResult<Vec<Todo>> {
+        let query =
+            "SELECT * FROM todos WHERE user_id = ? AND deleted_at IS NOT NULL ORDER BY deleted_at DESC";
+        let todos = sqlx::query_as::<_, Todo>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(todos)
+    }
+    async fn restore(
+        &self,
+        user_id: i32,
+        id: i32,
+        descendants: &[i32],
+        detach: bool,
+    ) -> AppResult<bool> {
+        let now = Local::now();
+        let query = "UPDATE todos SET deleted_at = NULL, updated_at = ? WHERE id = ? AND user_id = ? AND deleted_at IS NOT NULL";
+        let mut tx = self.pool.begin().await?;
+        let res = sqlx::query(query)
+            .bind(now)
+            .bind(id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Ok(false);
+        }
+        if detach {
+            sqlx::query("UPDATE todos SET parent_id = NULL WHERE id = ? AND user_id = ?")
+                .bind(id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
         }
+        for descendant in descendants {
+            sqlx::query(query)
+                .bind(now)
+                .bind(descendant)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+    async fn purge_deleted_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let mut tx = self.pool.begin().await?;
+        // 先删除标签关联, 避免留下指向不存在任务的记录
+        let query = "DELETE FROM todo_tags WHERE todo_id IN (SELECT id FROM todos WHERE deleted_at IS NOT NULL AND deleted_at < ?)";
+        sqlx::query(query).bind(cutoff).execute(&mut *tx).await?;
+        let query = "DELETE FROM todos WHERE deleted_at IS NOT NULL AND deleted_at < ?";
+        let res = sqlx::query(query).bind(cutoff).execute(&mut *tx).await?;
+        tx.commit().await?;
+        Ok(res.rows_affected())
     }
 }
 #[cfg(test)]
@@ -101,7 +243,8 @@ mod tests {
 
     use super::*;
     use crate::domain::entities::todo::{Priority, Status, Todo};
-    use std::env;
+    use sqlx::mysql::MySqlConnectOptions;
+    use std::{env, str::FromStr};
 
     async fn setup() -> MySqlTodoRepository {
         dotenv::dotenv().ok();
@@ -113,23 +256,26 @@ mod tests {
     }
 
     #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a MySQL database"]
     async fn test_get_all_by_user_id() {
         let repo = setup().await;
         let user_id = 1;
-        let todos = repo.get_all_by_user_id(user_id).await;
-        assert!(todos.len() >= 0);
+        let todos = repo.get_all_by_user_id(user_id).await.unwrap();
+        assert!(todos.iter().all(|todo| todo.user_id == user_id));
     }
 
     #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a MySQL database"]
     async fn test_get_by_id() {
         let repo = setup().await;
         let id = 1;
-        let todo = repo.get_by_id(id).await.unwrap();
+        let todo = repo.get_by_id(1, id).await.unwrap();
         print!("{:?}", todo)
         // assert!(todo.is_some());
     }
 
     #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a MySQL database"]
     async fn test_create() {
         let repo = setup().await;
         let todo = Todo {
@@ -144,6 +290,8 @@ mod tests {
             deleted_at: None,
             deadline: None,
             done: false,
+            parent_id: None,
+            auto_complete: false,
         };
         let result = repo.create(&todo).await;
         print!("{:?}", result);
@@ -162,10 +310,11 @@ mod tests {
     // }
 
     #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a MySQL database"]
     async fn test_delete() {
         let repo = setup().await;
         let id = 1;
-        let result = repo.delete(id).await;
+        let result = repo.delete(1, id, &[]).await.unwrap();
         assert!(result);
     }
 }
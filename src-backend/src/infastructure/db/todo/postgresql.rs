@@ -1,8 +1,16 @@
-use anyhow::Result;
-use sqlx::postgres::{PgPool, PgPoolOptions};
-use sqlx::{prelude::*, query};
+use chrono::{DateTime, Local};
+use sqlx::postgres::PgPool;
+use sqlx::{Postgres, QueryBuilder};
 
-use crate::domain::{entities::todo::Todo, repository::todo::TodoRepository};
+use crate::domain::{
+    entities::todo::Todo,
+    error::AppResult,
+    repository::{
+        tag::TagMatch,
+        todo::{TodoFilter, TodoQuery, TodoRepository},
+        Page,
+    },
+};
 pub struct PgSqlTodoRepository {
     pool: PgPool,
 }
@@ -13,37 +21,104 @@ impl PgSqlTodoRepository {
     }
 }
 
+fn push_conditions(builder: &mut QueryBuilder<'_, Postgres>, user_id: i32, filter: &TodoFilter) {
+    builder
+        .push(" WHERE deleted_at IS NULL AND user_id = ")
+        .push_bind(user_id);
+    if let Some(status) = filter.status {
+        builder.push(" AND status = ").push_bind(status);
+    }
+    if let Some(priority) = filter.priority {
+        builder.push(" AND priority = ").push_bind(priority);
+    }
+    if let Some(done) = filter.done {
+        builder.push(" AND done = ").push_bind(done);
+    }
+    if let Some(deadline_from) = filter.deadline_from {
+        builder.push(" AND deadline >= ").push_bind(deadline_from);
+    }
+    if let Some(deadline_to) = filter.deadline_to {
+        builder.push(" AND deadline <= ").push_bind(deadline_to);
+    }
+    // 标签条件作为子查询, 只考虑属于该用户的标签
+    match filter.tags.as_ref() {
+        Some(tags) if tags.tag_ids.is_empty() => {
+            builder.push(" AND 1 = 0");
+        }
+        Some(tags) => {
+            builder
+                .push(" AND EXISTS (SELECT 1 FROM todo_tags JOIN tags ON tags.id = todo_tags.tag_id WHERE todo_tags.todo_id = todos.id AND tags.user_id = ")
+                .push_bind(user_id)
+                .push(" AND todo_tags.tag_id IN (");
+            let mut separated = builder.separated(", ");
+            for tag_id in &tags.tag_ids {
+                separated.push_bind(*tag_id);
+            }
+            builder.push(")");
+            if tags.mode == TagMatch::All {
+                builder
+                    .push(" GROUP BY todo_tags.todo_id HAVING COUNT(DISTINCT todo_tags.tag_id) = ")
+                    .push_bind(tags.tag_ids.len() as i64);
+            }
+            builder.push(")");
+        }
+        None => {}
+    }
+}
+
 #[async_trait::async_trait]
 impl TodoRepository for PgSqlTodoRepository {
-    async fn get_all_by_user_id(&self, user_id: i32) -> Vec<Todo> {
-        let query = "SELECT * FROM todos WHERE user_id = $1 RETURNING *";
-        if let Ok(todos) = sqlx::query_as::<_, Todo>(query)
+    async fn get_all_by_user_id(&self, user_id: i32) -> AppResult<Vec<Todo>> {
+        let query = "SELECT * FROM todos WHERE user_id = $1 AND deleted_at IS NULL";
+        let todos = sqlx::query_as::<_, Todo>(query)
             .bind(user_id)
             .fetch_all(&self.pool)
-            .await
-        {
-            todos
-        } else {
-            Vec::new()
-        }
+            .await?;
+        Ok(todos)
     }
-    async fn get_by_id(&self, id: i32) -> Option<Todo> {
-        let query = "SELECT * FROM todos WHERE id = $1 RETURNING *";
-        if let Ok(todo) = sqlx::query_as::<_, Todo>(query)
+    async fn list_by_user_id(&self, user_id: i32, query: &TodoQuery) -> AppResult<Page<Todo>> {
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM todos");
+        push_conditions(&mut count, user_id, &query.filter);
+        let total: i64 = count.build_query_scalar().fetch_one(&self.pool).await?;
+
+        let mut select = QueryBuilder::new("SELECT * FROM todos");
+        push_conditions(&mut select, user_id, &query.filter);
+        select.push(format!(
+            " ORDER BY {column} {order}, id {order}",
+            column = query.sort.column(),
+            order = query.order.as_sql()
+        ));
+        select
+            .push(" LIMIT ")
+            .push_bind(i64::from(query.page_size))
+            .push(" OFFSET ")
+            .push_bind(query.offset());
+        let items = select
+            .build_query_as::<Todo>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Page {
+            items,
+            total,
+            page: query.page,
+            page_size: query.page_size,
+        })
+    }
+    async fn get_by_id(&self, user_id: i32, id: i32) -> AppResult<Option<Todo>> {
+        let query = "SELECT * FROM todos WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL";
+        let todo = sqlx::query_as::<_, Todo>(query)
             .bind(id)
-            .fetch_one(&self.pool)
-            .await
-        {
-            Some(todo)
-        } else {
-            None
-        }
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(todo)
     }
 
-    async fn create(&self, todo: &Todo) -> Result<Todo> {
-        let query = "INSERT INTO todos (user_id, title, description, status, priority, created_at, updated_at, deleted_at, deadline, done) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING *";
+    async fn create(&self, todo: &Todo) -> AppResult<Todo> {
+        let query = "INSERT INTO todos (user_id, title, description, status, priority, created_at, updated_at, deleted_at, deadline, done, parent_id, auto_complete) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) RETURNING *";
 
-        if let Ok(res) = sqlx::query(query)
+        let todo = sqlx::query_as::<_, Todo>(query)
             .bind(todo.user_id)
             .bind(todo.title.clone())
             .bind(todo.description.clone())
@@ -54,30 +129,15 @@ impl TodoRepository for PgSqlTodoRepository {
             .bind(todo.deleted_at)
             .bind(todo.deadline)
             .bind(todo.done)
+            .bind(todo.parent_id)
+            .bind(todo.auto_complete)
             .fetch_one(&self.pool)
-            .await
-        {
-            Ok(Todo {
-                id: res.try_get("id")?,
-                user_id: res.try_get("user_id")?,
-                title: res.try_get("title")?,
-                description: res.try_get("description")?,
-                status: res.try_get("status")?,
-                priority: res.try_get("priority")?,
-                created_at: res.try_get("created_at")?,
-                updated_at: res.try_get("updated_at")?,
-                deleted_at: res.try_get("deleted_at")?,
-                deadline: res.try_get("deadline")?,
-                done: res.try_get("done")?,
-            })
-        } else {
-            Err(anyhow::anyhow!("Failed to create todo"))
-        }
+            .await?;
+        Ok(todo)
     }
-    async fn save(&self, todo: Todo) -> Result<bool, sqlx::Error> {
-        let query = "UPDATE todos SET user_id = $1, title = $2, description = $3, status = $4, priority = $5, created_at = $6, updated_at = $7, deleted_at = $8, deadline = $9, done = $10 WHERE id = $11";
-        if let Ok(_) = sqlx::query(query)
-            .bind(todo.user_id)
+    async fn save(&self, todo: Todo) -> AppResult<()> {
+        let query = "UPDATE todos SET title = $1, description = $2, status = $3, priority = $4, created_at = $5, updated_at = $6, deleted_at = $7, deadline = $8, done = $9, parent_id = $10, auto_complete = $11 WHERE id = $12 AND user_id = $13";
+        sqlx::query(query)
             .bind(todo.title)
             .bind(todo.description)
             .bind(todo.status)
@@ -87,22 +147,93 @@ impl TodoRepository for PgSqlTodoRepository {
             .bind(todo.deleted_at)
             .bind(todo.deadline)
             .bind(todo.done)
+            .bind(todo.parent_id)
+            .bind(todo.auto_complete)
             .bind(todo.id)
+            .bind(todo.user_id)
             .execute(&self.pool)
-            .await
-        {
-            Ok(true)
-        } else {
-            Err(sqlx::Error::RowNotFound)
+            .await?;
+        Ok(())
+    }
+    async fn delete(&self, user_id: i32, id: i32, descendants: &[i32]) -> AppResult<bool> {
+        let now = Local::now();
+        let query = "UPDATE todos SET deleted_at = $1 WHERE id = $2 AND user_id = $3 AND deleted_at IS NULL";
+        let mut tx = self.pool.begin().await?;
+        let res = sqlx::query(query)
+            .bind(now)
+            .bind(id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Ok(false);
         }
+        for descendant in descendants {
+            sqlx::query(query)
+                .bind(now)
+                .bind(descendant)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(true)
     }
-    async fn delete(&self, id: i32) -> bool {
-        let query = "DELETE FROM todos WHERE id = $1";
-        if let Ok(_) = sqlx::query(query).bind(id).execute(&self.pool).await {
-            true
-        } else {
-            false
+    async fn get_trashed_by_user_id(&self, user_id: i32) -> AppResult<Vec<Todo>> {
+        let query =
+            "SELECT * FROM todos WHERE user_id = $1 AND deleted_at IS NOT NULL ORDER BY deleted_at DESC";
+        let todos = sqlx::query_as::<_, Todo>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(todos)
+    }
+    async fn restore(
+        &self,
+        user_id: i32,
+        id: i32,
+        descendants: &[i32],
+        detach: bool,
+    ) -> AppResult<bool> {
+        let now = Local::now();
+        let query = "UPDATE todos SET deleted_at = NULL, updated_at = $1 WHERE id = $2 AND user_id = $3 AND deleted_at IS NOT NULL";
+        let mut tx = self.pool.begin().await?;
+        let res = sqlx::query(query)
+            .bind(now)
+            .bind(id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Ok(false);
+        }
+        if detach {
+            sqlx::query("UPDATE todos SET parent_id = NULL WHERE id = $1 AND user_id = $2")
+                .bind(id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
         }
+        for descendant in descendants {
+            sqlx::query(query)
+                .bind(now)
+                .bind(descendant)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+    async fn purge_deleted_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let mut tx = self.pool.begin().await?;
+        // 先删除标签关联, 避免留下指向不存在任务的记录
+        let query = "DELETE FROM todo_tags WHERE todo_id IN (SELECT id FROM todos WHERE deleted_at IS NOT NULL AND deleted_at < $1)";
+        sqlx::query(query).bind(cutoff).execute(&mut *tx).await?;
+        let query = "DELETE FROM todos WHERE deleted_at IS NOT NULL AND deleted_at < $1";
+        let res = sqlx::query(query).bind(cutoff).execute(&mut *tx).await?;
+        tx.commit().await?;
+        Ok(res.rows_affected())
     }
 }
 
@@ -114,6 +245,7 @@ mod tests {
 
     use super::*;
     use chrono::Local;
+    use sqlx::postgres::PgPoolOptions;
 
     async fn setup() -> PgSqlTodoRepository {
         dotenv::dotenv().ok();
@@ -127,21 +259,24 @@ mod tests {
     }
 
     #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a PostgreSQL database"]
     async fn test_get_all_by_user_id() {
         let repo = setup().await;
-        let todos = repo.get_all_by_user_id(1).await;
+        let todos = repo.get_all_by_user_id(1).await.unwrap();
         assert_eq!(todos.len(), 1);
     }
 
     #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a PostgreSQL database"]
     async fn test_get_by_id() {
         let repo = setup().await;
-        let todo = repo.get_by_id(11).await;
+        let todo = repo.get_by_id(1, 11).await.unwrap();
         assert!(todo.is_some());
     }
 
     // #[tokio::test]
     #[sqlx::test]
+    #[ignore = "requires DATABASE_URL pointing at a PostgreSQL database"]
     async fn test_create() {
         let repo = setup().await;
         let todo = Todo::new(
@@ -0,0 +1,439 @@
+use chrono::{DateTime, Local};
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+
+use crate::domain::{
+    entities::todo::Todo,
+    error::AppResult,
+    repository::{
+        tag::TagMatch,
+        todo::{TodoFilter, TodoQuery, TodoRepository},
+        Page,
+    },
+};
+
+pub struct SqliteTodoRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteTodoRepository {
+    pub fn new(pool: SqlitePool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+fn push_conditions(builder: &mut QueryBuilder<'_, Sqlite>, user_id: i32, filter: &TodoFilter) {
+    builder
+        .push(" WHERE deleted_at IS NULL AND user_id = ")
+        .push_bind(user_id);
+    if let Some(status) = filter.status {
+        builder.push(" AND status = ").push_bind(status);
+    }
+    if let Some(priority) = filter.priority {
+        builder.push(" AND priority = ").push_bind(priority);
+    }
+    if let Some(done) = filter.done {
+        builder.push(" AND done = ").push_bind(done);
+    }
+    if let Some(deadline_from) = filter.deadline_from {
+        builder.push(" AND deadline >= ").push_bind(deadline_from);
+    }
+    if let Some(deadline_to) = filter.deadline_to {
+        builder.push(" AND deadline <= ").push_bind(deadline_to);
+    }
+    // 标签条件作为子查询, 只考虑属于该用户的标签
+    match filter.tags.as_ref() {
+        Some(tags) if tags.tag_ids.is_empty() => {
+            builder.push(" AND 1 = 0");
+        }
+        Some(tags) => {
+            builder
+                .push(" AND EXISTS (SELECT 1 FROM todo_tags JOIN tags ON tags.id = todo_tags.tag_id WHERE todo_tags.todo_id = todos.id AND tags.user_id = ")
+                .push_bind(user_id)
+                .push(" AND todo_tags.tag_id IN (");
+            let mut separated = builder.separated(", ");
+            for tag_id in &tags.tag_ids {
+                separated.push_bind(*tag_id);
+            }
+            builder.push(")");
+            if tags.mode == TagMatch::All {
+                builder
+                    .push(" GROUP BY todo_tags.todo_id HAVING COUNT(DISTINCT todo_tags.tag_id) = ")
+                    .push_bind(tags.tag_ids.len() as i64);
+            }
+            builder.push(")");
+        }
+        None => {}
+    }
+}
+
+#[async_trait::async_trait]
+impl TodoRepository for SqliteTodoRepository {
+    async fn get_all_by_user_id(&self, user_id: i32) -> AppResult<Vec<Todo>> {
+        let query = "SELECT * FROM todos WHERE user_id = ? AND deleted_at IS NULL";
+        let todos = sqlx::query_as::<_, Todo>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(todos)
+    }
+    async fn list_by_user_id(&self, user_id: i32, query: &TodoQuery) -> AppResult<Page<Todo>> {
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM todos");
+        push_conditions(&mut count, user_id, &query.filter);
+        let total: i64 = count.build_query_scalar().fetch_one(&self.pool).await?;
+
+        let mut select = QueryBuilder::new("SELECT * FROM todos");
+        push_conditions(&mut select, user_id, &query.filter);
+        select.push(format!(
+            " ORDER BY {column} {order}, id {order}",
+            column = query.sort.column(),
+            order = query.order.as_sql()
+        ));
+        select
+            .push(" LIMIT ")
+            .push_bind(i64::from(query.page_size))
+            .push(" OFFSET ")
+            .push_bind(query.offset());
+        let items = select
+            .build_query_as::<Todo>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Page {
+            items,
+            total,
+            page: query.page,
+            page_size: query.page_size,
+        })
+    }
+    async fn get_by_id(&self, user_id: i32, id: i32) -> AppResult<Option<Todo>> {
+        let query = "SELECT * FROM todos WHERE id = ? AND user_id = ? AND deleted_at IS NULL";
+        let todo = sqlx::query_as::<_, Todo>(query)
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(todo)
+    }
+
+    async fn create(&self, todo: &Todo) -> AppResult<Todo> {
+        let query = "INSERT INTO todos (user_id, title, description, status, priority, created_at, updated_at, deleted_at, deadline, done, parent_id, auto_complete) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) RETURNING *";
+
+        let todo = sqlx::query_as::<_, Todo>(query)
+            .bind(todo.user_id)
+            .bind(todo.title.clone())
+            .bind(todo.description.clone())
+            .bind(todo.status)
+            .bind(todo.priority)
+            .bind(todo.created_at)
+            .bind(todo.updated_at)
+            .bind(todo.deleted_at)
+            .bind(todo.deadline)
+            .bind(todo.done)
+            .bind(todo.parent_id)
+            .bind(todo.auto_complete)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(todo)
+    }
+    async fn save(&self, todo: Todo) -> AppResult<()> {
+        let query = "UPDATE todos SET title = ?, description = ?, status = ?, priority = ?, created_at = ?, updated_at = ?, deleted_at = ?, deadline = ?, done = ?, parent_id = ?, auto_complete = ? WHERE id = ? AND user_id = ?";
+        sqlx::query(query)
+            .bind(todo.title)
+            .bind(todo.description)
+            .bind(todo.status)
+            .bind(todo.priority)
+            .bind(todo.created_at)
+            .bind(todo.updated_at)
+            .bind(todo.deleted_at)
+            .bind(todo.deadline)
+            .bind(todo.done)
+            .bind(todo.parent_id)
+            .bind(todo.auto_complete)
+            .bind(todo.id)
+            .bind(todo.user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+    async fn delete(&self, user_id: i32, id: i32, descendants: &[i32]) -> AppResult<bool> {
+        let now = Local::now();
+        let query =
+            "UPDATE todos SET deleted_at = ? WHERE id = ? AND user_id = ? AND deleted_at IS NULL";
+        let mut tx = self.pool.begin().await?;
+        let res = sqlx::query(query)
+            .bind(now)
+            .bind(id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Ok(false);
+        }
+        for descendant in descendants {
+            sqlx::query(query)
+                .bind(now)
+                .bind(descendant)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+    async fn get_trashed_by_user_id(&self, user_id: i32) -> AppResult<Vec<Todo>> {
+        let query =
+            "SELECT * FROM todos WHERE user_id = ? AND deleted_at IS NOT NULL ORDER BY deleted_at DESC";
+        let todos = sqlx::query_as::<_, Todo>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(todos)
+    }
+    async fn restore(
+        &self,
+        user_id: i32,
+        id: i32,
+        descendants: &[i32],
+        detach: bool,
+    ) -> AppResult<bool> {
+        let now = Local::now();
+        let query = "UPDATE todos SET deleted_at = NULL, updated_at = ? WHERE id = ? AND user_id = ? AND deleted_at IS NOT NULL";
+        let mut tx = self.pool.begin().await?;
+        let res = sqlx::query(query)
+            .bind(now)
+            .bind(id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Ok(false);
+        }
+        if detach {
+            sqlx::query("UPDATE todos SET parent_id = NULL WHERE id = ? AND user_id = ?")
+                .bind(id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        for descendant in descendants {
+            sqlx::query(query)
+                .bind(now)
+                .bind(descendant)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+    async fn purge_deleted_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let mut tx = self.pool.begin().await?;
+        // 先删除标签关联, 避免留下指向不存在任务的记录
+        let query = "DELETE FROM todo_tags WHERE todo_id IN (SELECT id FROM todos WHERE deleted_at IS NOT NULL AND deleted_at < ?)";
+        sqlx::query(query).bind(cutoff).execute(&mut *tx).await?;
+        let query = "DELETE FROM todos WHERE deleted_at IS NOT NULL AND deleted_at < ?";
+        let res = sqlx::query(query).bind(cutoff).execute(&mut *tx).await?;
+        tx.commit().await?;
+        Ok(res.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::{
+        domain::{
+            entities::todo::{Priority, Status},
+            repository::{
+                tag::{TagFilter, TagMatch},
+                todo::TodoSortField,
+                SortOrder,
+            },
+        },
+        infastructure::db::SQLITE_MIGRATOR,
+    };
+
+    async fn setup() -> SqliteTodoRepository {
+        // 内存数据库只在单个连接内可见
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        SQLITE_MIGRATOR.run(&pool).await.unwrap();
+        SqliteTodoRepository::new(pool).unwrap()
+    }
+
+    fn new_todo(user_id: i32, title: &str) -> Todo {
+        Todo::new(
+            user_id,
+            title.to_string(),
+            "Test".to_string(),
+            Status::Open,
+            Priority::Low,
+            Local::now(),
+            Local::now(),
+            None,
+            None,
+            false,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_create_and_get_by_id() {
+        let repo = setup().await;
+        let todo = repo.create(&new_todo(1, "Test")).await.unwrap();
+        assert!(todo.id > 0);
+
+        let found = repo.get_by_id(1, todo.id).await.unwrap().unwrap();
+        assert_eq!(found.title, "Test");
+        assert!(repo.get_by_id(2, todo.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_save_parent_and_auto_complete() {
+        let repo = setup().await;
+        let parent = repo.create(&new_todo(1, "Parent")).await.unwrap();
+        let mut child = new_todo(1, "Child");
+        child.parent_id = Some(parent.id);
+        let mut child = repo.create(&child).await.unwrap();
+        assert_eq!(child.parent_id, Some(parent.id));
+        assert!(!child.auto_complete);
+
+        child.parent_id = None;
+        child.auto_complete = true;
+        repo.save(child.clone()).await.unwrap();
+        let found = repo.get_by_id(1, child.id).await.unwrap().unwrap();
+        assert_eq!(found.parent_id, None);
+        assert!(found.auto_complete);
+    }
+
+    #[tokio::test]
+    async fn test_list_by_user_id() {
+        let repo = setup().await;
+        for title in ["b", "a", "c"] {
+            repo.create(&new_todo(1, title)).await.unwrap();
+        }
+        repo.create(&new_todo(2, "other")).await.unwrap();
+
+        let query = TodoQuery {
+            sort: TodoSortField::Title,
+            order: SortOrder::Asc,
+            page_size: 2,
+            ..Default::default()
+        };
+        let page = repo.list_by_user_id(1, &query).await.unwrap();
+        assert_eq!(page.total, 3);
+        let titles: Vec<_> = page.items.iter().map(|todo| todo.title.as_str()).collect();
+        assert_eq!(titles, ["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_soft_delete_and_restore() {
+        let repo = setup().await;
+        let todo = repo.create(&new_todo(1, "Test")).await.unwrap();
+
+        assert!(repo.delete(1, todo.id, &[]).await.unwrap());
+        assert!(repo.get_by_id(1, todo.id).await.unwrap().is_none());
+        assert_eq!(repo.get_trashed_by_user_id(1).await.unwrap().len(), 1);
+
+        assert!(repo.restore(1, todo.id, &[], false).await.unwrap());
+        assert!(repo.get_by_id(1, todo.id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_purge_deleted_before() {
+        let repo = setup().await;
+        let todo = repo.create(&new_todo(1, "Test")).await.unwrap();
+        repo.delete(1, todo.id, &[]).await.unwrap();
+
+        let purged = repo
+            .purge_deleted_before(Local::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(purged, 1);
+        assert!(repo.get_trashed_by_user_id(1).await.unwrap().is_empty());
+    }
+
+    async fn tag(repo: &SqliteTodoRepository, user_id: i32, name: &str, todo_ids: &[i32]) -> i32 {
+        let id: i32 = sqlx::query_scalar(
+            "INSERT INTO tags (user_id, name, color) VALUES (?, ?, '#ff0000') RETURNING id",
+        )
+        .bind(user_id)
+        .bind(name)
+        .fetch_one(&repo.pool)
+        .await
+        .unwrap();
+        for todo_id in todo_ids {
+            sqlx::query("INSERT INTO todo_tags (todo_id, tag_id) VALUES (?, ?)")
+                .bind(todo_id)
+                .bind(id)
+                .execute(&repo.pool)
+                .await
+                .unwrap();
+        }
+        id
+    }
+
+    #[tokio::test]
+    async fn test_filter_by_tags() {
+        let repo = setup().await;
+        let both = repo.create(&new_todo(1, "Both")).await.unwrap();
+        let work_only = repo.create(&new_todo(1, "Work")).await.unwrap();
+        repo.create(&new_todo(1, "Untagged")).await.unwrap();
+        let work = tag(&repo, 1, "work", &[both.id, work_only.id]).await;
+        let urgent = tag(&repo, 1, "urgent", &[both.id]).await;
+        // 其他用户的标签不参与过滤
+        let foreign = tag(&repo, 2, "work", &[work_only.id]).await;
+
+        let mut query = TodoQuery {
+            filter: TodoFilter {
+                tags: Some(TagFilter {
+                    tag_ids: vec![work, urgent],
+                    mode: TagMatch::Any,
+                }),
+                ..TodoFilter::default()
+            },
+            sort: TodoSortField::Title,
+            order: SortOrder::Asc,
+            ..TodoQuery::default()
+        };
+        let page = repo.list_by_user_id(1, &query).await.unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.items[0].id, both.id);
+
+        query.filter.tags = Some(TagFilter {
+            tag_ids: vec![work, urgent],
+            mode: TagMatch::All,
+        });
+        let page = repo.list_by_user_id(1, &query).await.unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].id, both.id);
+
+        query.filter.tags = Some(TagFilter {
+            tag_ids: vec![foreign],
+            mode: TagMatch::Any,
+        });
+        assert_eq!(repo.list_by_user_id(1, &query).await.unwrap().total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_purge_removes_tag_links() {
+        let repo = setup().await;
+        let todo = repo.create(&new_todo(1, "Test")).await.unwrap();
+        tag(&repo, 1, "work", &[todo.id]).await;
+        repo.delete(1, todo.id, &[]).await.unwrap();
+
+        repo.purge_deleted_before(Local::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        let links: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM todo_tags")
+            .fetch_one(&repo.pool)
+            .await
+            .unwrap();
+        assert_eq!(links, 0);
+    }
+}
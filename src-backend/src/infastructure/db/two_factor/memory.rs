@@ -0,0 +1,137 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Local};
+
+use crate::{
+    domain::{
+        entities::two_factor::{RecoveryCode, TotpCredential},
+        error::AppResult,
+        repository::two_factor::TwoFactorRepository,
+    },
+    infastructure::db::user::memory::UserOwnedStore,
+};
+
+#[derive(Default)]
+struct Store {
+    credentials: HashMap<i32, TotpCredential>,
+    recovery_codes: Vec<RecoveryCode>,
+    next_id: i32,
+}
+
+// 基于内存的实现, 用于单元测试和接口测试, 克隆后共享同一份数据
+#[derive(Default, Clone)]
+pub struct InMemoryTwoFactorRepository {
+    store: Arc<Mutex<Store>>,
+}
+
+impl InMemoryTwoFactorRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl TwoFactorRepository for InMemoryTwoFactorRepository {
+    async fn get_totp(&self, user_id: i32) -> AppResult<Option<TotpCredential>> {
+        let store = self.store.lock().unwrap();
+        Ok(store.credentials.get(&user_id).cloned())
+    }
+
+    async fn save_totp(&self, credential: &TotpCredential) -> AppResult<()> {
+        let mut store = self.store.lock().unwrap();
+        store
+            .credentials
+            .insert(credential.user_id, credential.clone());
+        Ok(())
+    }
+
+    async fn enable_totp(&self, user_id: i32, enabled_at: DateTime<Local>) -> AppResult<()> {
+        let mut store = self.store.lock().unwrap();
+        if let Some(credential) = store.credentials.get_mut(&user_id) {
+            credential.enabled_at = Some(enabled_at);
+        }
+        Ok(())
+    }
+
+    async fn record_totp_step(&self, user_id: i32, step: i64) -> AppResult<bool> {
+        let mut store = self.store.lock().unwrap();
+        match store.credentials.get_mut(&user_id) {
+            Some(credential) if credential.last_used_step.is_none_or(|last| last < step) => {
+                credential.last_used_step = Some(step);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn delete(&self, user_id: i32) -> AppResult<()> {
+        let mut store = self.store.lock().unwrap();
+        store.credentials.remove(&user_id);
+        store.recovery_codes.retain(|code| code.user_id != user_id);
+        Ok(())
+    }
+
+    async fn replace_recovery_codes(
+        &self,
+        user_id: i32,
+        code_hashes: &[String],
+        created_at: DateTime<Local>,
+    ) -> AppResult<()> {
+        let mut store = self.store.lock().unwrap();
+        store.recovery_codes.retain(|code| code.user_id != user_id);
+        for code_hash in code_hashes {
+            store.next_id += 1;
+            let id = store.next_id;
+            store.recovery_codes.push(RecoveryCode {
+                id,
+                user_id,
+                code_hash: code_hash.clone(),
+                created_at,
+                used_at: None,
+            });
+        }
+        Ok(())
+    }
+
+    async fn use_recovery_code(
+        &self,
+        user_id: i32,
+        code_hash: &str,
+        used_at: DateTime<Local>,
+    ) -> AppResult<bool> {
+        let mut store = self.store.lock().unwrap();
+        match store.recovery_codes.iter_mut().find(|code| {
+            code.user_id == user_id && code.code_hash == code_hash && code.used_at.is_none()
+        }) {
+            Some(code) => {
+                code.used_at = Some(used_at);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn count_unused_recovery_codes(&self, user_id: i32) -> AppResult<i64> {
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .recovery_codes
+            .iter()
+            .filter(|code| code.user_id == user_id && code.used_at.is_none())
+            .count() as i64)
+    }
+}
+
+impl UserOwnedStore for InMemoryTwoFactorRepository {
+    fn purge_users(&self, user_ids: &[i32]) {
+        let mut store = self.store.lock().unwrap();
+        store
+            .credentials
+            .retain(|user_id, _| !user_ids.contains(user_id));
+        store
+            .recovery_codes
+            .retain(|code| !user_ids.contains(&code.user_id));
+    }
+}
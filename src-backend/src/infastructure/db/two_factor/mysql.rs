@@ -0,0 +1,127 @@
+use chrono::{DateTime, Local};
+use sqlx::MySqlPool;
+
+use crate::domain::{
+    entities::two_factor::TotpCredential, error::AppResult,
+    repository::two_factor::TwoFactorRepository,
+};
+
+pub struct MySqlTwoFactorRepository {
+    pool: MySqlPool,
+}
+
+impl MySqlTwoFactorRepository {
+    pub fn new(pool: MySqlPool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait::async_trait]
+impl TwoFactorRepository for MySqlTwoFactorRepository {
+    async fn get_totp(&self, user_id: i32) -> AppResult<Option<TotpCredential>> {
+        let query = "SELECT * FROM totp_credentials WHERE user_id = ?";
+        let credential = sqlx::query_as::<_, TotpCredential>(query)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(credential)
+    }
+
+    async fn save_totp(&self, credential: &TotpCredential) -> AppResult<()> {
+        let query = "INSERT INTO totp_credentials (user_id, secret, created_at, enabled_at, last_used_step) VALUES (?, ?, ?, ?, ?) ON DUPLICATE KEY UPDATE secret = VALUES(secret), created_at = VALUES(created_at), enabled_at = VALUES(enabled_at), last_used_step = VALUES(last_used_step)";
+        sqlx::query(query)
+            .bind(credential.user_id)
+            .bind(credential.secret.clone())
+            .bind(credential.created_at)
+            .bind(credential.enabled_at)
+            .bind(credential.last_used_step)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn enable_totp(&self, user_id: i32, enabled_at: DateTime<Local>) -> AppResult<()> {
+        let query = "UPDATE totp_credentials SET enabled_at = ? WHERE user_id = ?";
+        sqlx::query(query)
+            .bind(enabled_at)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn record_totp_step(&self, user_id: i32, step: i64) -> AppResult<bool> {
+        let query = "UPDATE totp_credentials SET last_used_step = ? WHERE user_id = ? AND (last_used_step IS NULL OR last_used_step < ?)";
+        let res = sqlx::query(query)
+            .bind(step)
+            .bind(user_id)
+            .bind(step)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn delete(&self, user_id: i32) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM recovery_codes WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM totp_credentials WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn replace_recovery_codes(
+        &self,
+        user_id: i32,
+        code_hashes: &[String],
+        created_at: DateTime<Local>,
+    ) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM recovery_codes WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        for code_hash in code_hashes {
+            sqlx::query(
+                "INSERT INTO recovery_codes (user_id, code_hash, created_at) VALUES (?, ?, ?)",
+            )
+            .bind(user_id)
+            .bind(code_hash)
+            .bind(created_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn use_recovery_code(
+        &self,
+        user_id: i32,
+        code_hash: &str,
+        used_at: DateTime<Local>,
+    ) -> AppResult<bool> {
+        let query = "UPDATE recovery_codes SET used_at = ? WHERE user_id = ? AND code_hash = ? AND used_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(used_at)
+            .bind(user_id)
+            .bind(code_hash)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn count_unused_recovery_codes(&self, user_id: i32) -> AppResult<i64> {
+        let query = "SELECT COUNT(*) FROM recovery_codes WHERE user_id = ? AND used_at IS NULL";
+        let count: i64 = sqlx::query_scalar(query)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count)
+    }
+}
@@ -0,0 +1,193 @@
+use chrono::{DateTime, Local};
+use sqlx::SqlitePool;
+
+use crate::domain::{
+    entities::two_factor::TotpCredential, error::AppResult,
+    repository::two_factor::TwoFactorRepository,
+};
+
+pub struct SqliteTwoFactorRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteTwoFactorRepository {
+    pub fn new(pool: SqlitePool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait::async_trait]
+impl TwoFactorRepository for SqliteTwoFactorRepository {
+    async fn get_totp(&self, user_id: i32) -> AppResult<Option<TotpCredential>> {
+        let query = "SELECT * FROM totp_credentials WHERE user_id = ?";
+        let credential = sqlx::query_as::<_, TotpCredential>(query)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(credential)
+    }
+
+    async fn save_totp(&self, credential: &TotpCredential) -> AppResult<()> {
+        let query = "INSERT INTO totp_credentials (user_id, secret, created_at, enabled_at, last_used_step) VALUES (?, ?, ?, ?, ?) ON CONFLICT (user_id) DO UPDATE SET secret = excluded.secret, created_at = excluded.created_at, enabled_at = excluded.enabled_at, last_used_step = excluded.last_used_step";
+        sqlx::query(query)
+            .bind(credential.user_id)
+            .bind(credential.secret.clone())
+            .bind(credential.created_at)
+            .bind(credential.enabled_at)
+            .bind(credential.last_used_step)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn enable_totp(&self, user_id: i32, enabled_at: DateTime<Local>) -> AppResult<()> {
+        let query = "UPDATE totp_credentials SET enabled_at = ? WHERE user_id = ?";
+        sqlx::query(query)
+            .bind(enabled_at)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn record_totp_step(&self, user_id: i32, step: i64) -> AppResult<bool> {
+        let query = "UPDATE totp_credentials SET last_used_step = ? WHERE user_id = ? AND (last_used_step IS NULL OR last_used_step < ?)";
+        let res = sqlx::query(query)
+            .bind(step)
+            .bind(user_id)
+            .bind(step)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn delete(&self, user_id: i32) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM recovery_codes WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM totp_credentials WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn replace_recovery_codes(
+        &self,
+        user_id: i32,
+        code_hashes: &[String],
+        created_at: DateTime<Local>,
+    ) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM recovery_codes WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        for code_hash in code_hashes {
+            sqlx::query(
+                "INSERT INTO recovery_codes (user_id, code_hash, created_at) VALUES (?, ?, ?)",
+            )
+            .bind(user_id)
+            .bind(code_hash)
+            .bind(created_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn use_recovery_code(
+        &self,
+        user_id: i32,
+        code_hash: &str,
+        used_at: DateTime<Local>,
+    ) -> AppResult<bool> {
+        let query = "UPDATE recovery_codes SET used_at = ? WHERE user_id = ? AND code_hash = ? AND used_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(used_at)
+            .bind(user_id)
+            .bind(code_hash)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn count_unused_recovery_codes(&self, user_id: i32) -> AppResult<i64> {
+        let query = "SELECT COUNT(*) FROM recovery_codes WHERE user_id = ? AND used_at IS NULL";
+        let count: i64 = sqlx::query_scalar(query)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::infastructure::db::SQLITE_MIGRATOR;
+
+    async fn setup() -> SqliteTwoFactorRepository {
+        // 内存数据库只在单个连接内可见
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        SQLITE_MIGRATOR.run(&pool).await.unwrap();
+        SqliteTwoFactorRepository::new(pool).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_totp_upsert_and_step_replay() {
+        let repo = setup().await;
+        repo.save_totp(&TotpCredential::new(1, "FIRST".to_string(), Local::now()))
+            .await
+            .unwrap();
+        repo.save_totp(&TotpCredential::new(1, "SECOND".to_string(), Local::now()))
+            .await
+            .unwrap();
+        let credential = repo.get_totp(1).await.unwrap().unwrap();
+        assert_eq!(credential.secret, "SECOND");
+        assert!(!credential.is_enabled());
+
+        repo.enable_totp(1, Local::now()).await.unwrap();
+        assert!(repo.get_totp(1).await.unwrap().unwrap().is_enabled());
+        assert!(repo.record_totp_step(1, 100).await.unwrap());
+        assert!(!repo.record_totp_step(1, 100).await.unwrap());
+        assert!(!repo.record_totp_step(1, 99).await.unwrap());
+        assert!(repo.record_totp_step(1, 101).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_recovery_codes_are_single_use_and_replaced() {
+        let repo = setup().await;
+        repo.save_totp(&TotpCredential::new(1, "SECRET".to_string(), Local::now()))
+            .await
+            .unwrap();
+        let hashes = vec!["a".to_string(), "b".to_string()];
+        repo.replace_recovery_codes(1, &hashes, Local::now())
+            .await
+            .unwrap();
+        assert!(!repo.use_recovery_code(2, "a", Local::now()).await.unwrap());
+        assert!(repo.use_recovery_code(1, "a", Local::now()).await.unwrap());
+        assert!(!repo.use_recovery_code(1, "a", Local::now()).await.unwrap());
+        assert_eq!(repo.count_unused_recovery_codes(1).await.unwrap(), 1);
+
+        repo.replace_recovery_codes(1, &["c".to_string()], Local::now())
+            .await
+            .unwrap();
+        assert!(!repo.use_recovery_code(1, "b", Local::now()).await.unwrap());
+        assert_eq!(repo.count_unused_recovery_codes(1).await.unwrap(), 1);
+
+        repo.delete(1).await.unwrap();
+        assert!(repo.get_totp(1).await.unwrap().is_none());
+        assert_eq!(repo.count_unused_recovery_codes(1).await.unwrap(), 0);
+    }
+}
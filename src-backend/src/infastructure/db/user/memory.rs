@@ -0,0 +1,201 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Local};
+
+use crate::domain::{
+    entities::user::User,
+    error::{AppError, AppResult},
+    repository::{
+        user::{UserFilter, UserQuery, UserRepository},
+        Page,
+    },
+};
+
+#[derive(Default)]
+struct Store {
+    users: BTreeMap<i32, User>,
+    next_id: i32,
+}
+
+// 属于用户的内存数据, 内存实现没有事务, 由用户仓储在删除和清理时级联处理
+pub trait UserOwnedStore: Send + Sync {
+    // 账户软删除时调用, 与数据库实现一致地删除 todo 并使凭据失效
+    fn delete_user(&self, _user_id: i32, _at: DateTime<Local>) {}
+    fn purge_users(&self, user_ids: &[i32]);
+}
+
+// 基于内存的实现, 用于单元测试和接口测试
+#[derive(Default)]
+pub struct InMemoryUserRepository {
+    store: Mutex<Store>,
+    dependents: Vec<Arc<dyn UserOwnedStore>>,
+}
+
+impl InMemoryUserRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 传入其他仓储的克隆, 与其共享数据
+    pub fn with_dependent(mut self, store: impl UserOwnedStore + 'static) -> Self {
+        self.dependents.push(Arc::new(store));
+        self
+    }
+}
+
+fn matches_filter(user: &User, filter: &UserFilter) -> bool {
+    if user.deleted_at.is_some() {
+        return false;
+    }
+    if let Some(search) = filter.search.as_deref() {
+        let search = search.to_lowercase();
+        if !user.username.to_lowercase().contains(&search)
+            && !user.email.to_lowercase().contains(&search)
+        {
+            return false;
+        }
+    }
+    filter.role.is_none_or(|role| user.role == role)
+        && filter.status.is_none_or(|status| user.status == status)
+}
+
+#[async_trait::async_trait]
+impl UserRepository for InMemoryUserRepository {
+    async fn get_by_id(&self, id: i32) -> AppResult<Option<User>> {
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .users
+            .get(&id)
+            .filter(|user| user.deleted_at.is_none())
+            .cloned())
+    }
+
+    async fn get_by_email(&self, email: String) -> AppResult<Option<User>> {
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .users
+            .values()
+            .find(|user| user.email == email && user.deleted_at.is_none())
+            .cloned())
+    }
+
+    async fn search(&self, query: &UserQuery) -> AppResult<Page<User>> {
+        let store = self.store.lock().unwrap();
+        let users: Vec<&User> = store
+            .users
+            .values()
+            .filter(|user| matches_filter(user, &query.filter))
+            .collect();
+        Ok(Page {
+            total: users.len() as i64,
+            items: users
+                .into_iter()
+                .skip(query.offset() as usize)
+                .take(query.page_size as usize)
+                .cloned()
+                .collect(),
+            page: query.page,
+            page_size: query.page_size,
+        })
+    }
+
+    async fn create(&self, user: &User) -> AppResult<User> {
+        let mut store = self.store.lock().unwrap();
+        // 与数据库的唯一索引保持一致, 软删除的用户同样占用邮箱
+        if store
+            .users
+            .values()
+            .any(|existing| existing.email == user.email)
+        {
+            return Err(AppError::Conflict("Record already exists".to_string()));
+        }
+        store.next_id += 1;
+        let user = User {
+            id: store.next_id,
+            ..user.clone()
+        };
+        store.users.insert(user.id, user.clone());
+        Ok(user)
+    }
+
+    async fn save(&self, user: User) -> AppResult<()> {
+        let mut store = self.store.lock().unwrap();
+        if store
+            .users
+            .values()
+            .any(|existing| existing.id != user.id && existing.email == user.email)
+        {
+            return Err(AppError::Conflict("Record already exists".to_string()));
+        }
+        // 与数据库实现一致, 锁定状态只通过 record_login_failure/reset_login_failures 修改
+        if let Some(existing) = store.users.get_mut(&user.id) {
+            *existing = User {
+                failed_login_attempts: existing.failed_login_attempts,
+                locked_until: existing.locked_until,
+                ..user
+            };
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, id: i32) -> AppResult<bool> {
+        let mut store = self.store.lock().unwrap();
+        let now = Local::now();
+        match store.users.get_mut(&id) {
+            Some(user) if user.deleted_at.is_none() => user.deleted_at = Some(now),
+            _ => return Ok(false),
+        }
+        for dependent in &self.dependents {
+            dependent.delete_user(id, now);
+        }
+        Ok(true)
+    }
+
+    async fn record_login_failure(
+        &self,
+        id: i32,
+        max_attempts: u32,
+        locked_until: DateTime<Local>,
+    ) -> AppResult<()> {
+        let mut store = self.store.lock().unwrap();
+        if let Some(user) = store.users.get_mut(&id) {
+            if user.failed_login_attempts + 1 >= max_attempts as i32 {
+                user.failed_login_attempts = 0;
+                user.locked_until = Some(locked_until);
+            } else {
+                user.failed_login_attempts += 1;
+            }
+        }
+        Ok(())
+    }
+
+    async fn reset_login_failures(&self, id: i32) -> AppResult<()> {
+        let mut store = self.store.lock().unwrap();
+        if let Some(user) = store.users.get_mut(&id) {
+            user.failed_login_attempts = 0;
+            user.locked_until = None;
+        }
+        Ok(())
+    }
+
+    async fn purge_deleted_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let mut store = self.store.lock().unwrap();
+        let purged: Vec<i32> = store
+            .users
+            .values()
+            .filter(|user| {
+                user.deleted_at
+                    .is_some_and(|deleted_at| deleted_at < cutoff)
+            })
+            .map(|user| user.id)
+            .collect();
+        store.users.retain(|id, _| !purged.contains(id));
+        for dependent in &self.dependents {
+            dependent.purge_users(&purged);
+        }
+        Ok(purged.len() as u64)
+    }
+}
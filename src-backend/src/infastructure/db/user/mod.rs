@@ -1,2 +1,56 @@
+pub mod memory;
 pub mod mysql;
 pub mod postgresql;
+pub mod sqlite;
+
+// 属于用户的数据表, 审计日志不在其中, 清理用户后仍然保留
+pub(crate) const USER_OWNED_TABLES: [&str; 9] = [
+    "tags",
+    "todos",
+    "sessions",
+    "refresh_tokens",
+    "api_tokens",
+    "user_tokens",
+    "totp_credentials",
+    "recovery_codes",
+    "identities",
+];
+
+// 软删除账户时在同一事务中执行: todo 进入回收站, 会话和各类令牌全部失效
+pub(crate) fn delete_owned_queries(at: &str, user_id: &str) -> [String; 5] {
+    [
+        format!("UPDATE todos SET deleted_at = {at} WHERE user_id = {user_id} AND deleted_at IS NULL"),
+        format!("UPDATE sessions SET revoked_at = {at} WHERE user_id = {user_id} AND revoked_at IS NULL"),
+        format!("UPDATE refresh_tokens SET revoked_at = {at} WHERE user_id = {user_id} AND revoked_at IS NULL"),
+        format!("UPDATE api_tokens SET revoked_at = {at} WHERE user_id = {user_id} AND revoked_at IS NULL"),
+        format!("UPDATE user_tokens SET used_at = {at} WHERE user_id = {user_id} AND used_at IS NULL"),
+    ]
+}
+
+// 清理用户前删除其数据的语句, users 为待清理用户 id 的子查询, 关联表需要先删
+pub(crate) fn purge_owned_queries(users: &str) -> Vec<String> {
+    let mut queries = vec![
+        format!("DELETE FROM todo_tags WHERE tag_id IN (SELECT id FROM tags WHERE user_id IN ({users}))"),
+        format!("DELETE FROM todo_tags WHERE todo_id IN (SELECT id FROM todos WHERE user_id IN ({users}))"),
+    ];
+    queries.extend(
+        USER_OWNED_TABLES
+            .iter()
+            .map(|table| format!("DELETE FROM {table} WHERE user_id IN ({users})")),
+    );
+    queries
+}
+
+// 转义 LIKE 通配符, 查询时配合 ESCAPE '!' 使用
+pub(crate) fn like_pattern(search: &str) -> String {
+    let mut pattern = String::with_capacity(search.len() + 2);
+    pattern.push('%');
+    for c in search.to_lowercase().chars() {
+        if matches!(c, '!' | '%' | '_') {
+            pattern.push('!');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
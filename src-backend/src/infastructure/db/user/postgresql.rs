@@ -1,7 +1,16 @@
-use anyhow::Result;
-use sqlx::{PgPool, Row};
+use chrono::{DateTime, Local};
+use sqlx::{PgPool, Postgres, QueryBuilder, Row};
 
-use crate::domain::{entities::user::User, repository::user::UserRepository};
+use crate::domain::{
+    entities::user::User,
+    error::AppResult,
+    repository::{
+        user::{UserFilter, UserQuery, UserRepository},
+        Page,
+    },
+};
+
+use super::{delete_owned_queries, like_pattern, purge_owned_queries};
 
 pub struct PgUserRepository {
     pool: PgPool,
@@ -13,79 +22,165 @@ impl PgUserRepository {
     }
 }
 
+fn push_conditions(builder: &mut QueryBuilder<'_, Postgres>, filter: &UserFilter) {
+    builder.push(" WHERE deleted_at IS NULL");
+    if let Some(search) = filter.search.as_deref() {
+        let pattern = like_pattern(search);
+        builder
+            .push(" AND (LOWER(username) LIKE ")
+            .push_bind(pattern.clone())
+            .push(" ESCAPE '!' OR LOWER(email) LIKE ")
+            .push_bind(pattern)
+            .push(" ESCAPE '!')");
+    }
+    if let Some(role) = filter.role {
+        builder.push(" AND role = ").push_bind(role);
+    }
+    if let Some(status) = filter.status {
+        builder.push(" AND status = ").push_bind(status);
+    }
+}
+
 #[async_trait::async_trait]
 impl UserRepository for PgUserRepository {
-    async fn get_by_id(&self, id: i32) -> Option<User> {
-        let query = "SELECT * FROM user WHERE id = $1";
-        if let Ok(user) = sqlx::query_as::<_, User>(query)
+    async fn get_by_id(&self, id: i32) -> AppResult<Option<User>> {
+        let query = "SELECT * FROM users WHERE id = $1 AND deleted_at IS NULL";
+        let user = sqlx::query_as::<_, User>(query)
             .bind(id)
-            .fetch_one(&self.pool)
-            .await
-        {
-            Some(user)
-        } else {
-            None
-        }
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(user)
     }
 
-    async fn get_by_email(&self, email: String) -> Option<User> {
-        let query = "SELECT * FROM user WHERE email = $1";
-        if let Ok(user) = sqlx::query_as::<_, User>(query)
+    async fn get_by_email(&self, email: String) -> AppResult<Option<User>> {
+        let query = "SELECT * FROM users WHERE email = $1 AND deleted_at IS NULL";
+        let user = sqlx::query_as::<_, User>(query)
             .bind(email)
-            .fetch_one(&self.pool)
-            .await
-        {
-            Some(user)
-        } else {
-            None
-        }
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(user)
+    }
+
+    async fn search(&self, query: &UserQuery) -> AppResult<Page<User>> {
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM users");
+        push_conditions(&mut count, &query.filter);
+        let total: i64 = count.build_query_scalar().fetch_one(&self.pool).await?;
+
+        let mut select = QueryBuilder::new("SELECT * FROM users");
+        push_conditions(&mut select, &query.filter);
+        select
+            .push(" ORDER BY id ASC LIMIT ")
+            .push_bind(i64::from(query.page_size))
+            .push(" OFFSET ")
+            .push_bind(query.offset());
+        let items = select
+            .build_query_as::<User>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Page {
+            items,
+            total,
+            page: query.page,
+            page_size: query.page_size,
+        })
     }
 
-    async fn create(&self, user: &User) -> Result<User> {
-        let query = "INSERT INTO user (email, password, salt, created_at, updated_at, deleted_at) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *";
-        if let Ok(res) = sqlx::query(query)
+    async fn create(&self, user: &User) -> AppResult<User> {
+        let query = "INSERT INTO users (username, email, password, role, status, created_at, updated_at, deleted_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *";
+        let res = sqlx::query(query)
+            .bind(user.username.clone())
             .bind(user.email.clone())
             .bind(user.password.clone())
-            .bind(user.salt.clone())
+            .bind(user.role)
+            .bind(user.status)
             .bind(user.created_at)
             .bind(user.updated_at)
             .bind(user.deleted_at)
             .fetch_one(&self.pool)
-            .await
-        {
-            Ok(User {
-                id: res.try_get("id")?,
-                ..user.clone()
-            })
-        } else {
-            Err(anyhow::anyhow!("Error creating user"))
-        }
+            .await?;
+        Ok(User {
+            id: res.try_get("id")?,
+            ..user.clone()
+        })
     }
 
-    async fn save(&self, user: User) -> bool {
-        let query = "UPDATE user SET email = $1, password = $2, salt = $3, updated_at = $4, deleted_at = $5 WHERE id = $6";
-        if let Ok(_) = sqlx::query(query)
+    async fn save(&self, user: User) -> AppResult<()> {
+        let query = "UPDATE users SET username = $1, email = $2, password = $3, email_verified_at = $4, role = $5, status = $6, updated_at = $7, deleted_at = $8 WHERE id = $9";
+        sqlx::query(query)
+            .bind(user.username.clone())
             .bind(user.email)
             .bind(user.password)
-            .bind(user.salt.clone())
+            .bind(user.email_verified_at)
+            .bind(user.role)
+            .bind(user.status)
             .bind(user.updated_at)
             .bind(user.deleted_at)
             .bind(user.id)
             .execute(&self.pool)
-            .await
-        {
-            true
-        } else {
-            false
+            .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, id: i32) -> AppResult<bool> {
+        let now = Local::now();
+        let mut tx = self.pool.begin().await?;
+        let query = "UPDATE users SET deleted_at = $1 WHERE id = $2 AND deleted_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(now)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Ok(false);
+        }
+        for query in delete_owned_queries("$1", "$2") {
+            sqlx::query(&query)
+                .bind(now)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
         }
+        // 外部身份不再指向已删除的账户, 同一身份之后可以重新注册
+        sqlx::query("DELETE FROM identities WHERE user_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn record_login_failure(
+        &self,
+        id: i32,
+        max_attempts: u32,
+        locked_until: DateTime<Local>,
+    ) -> AppResult<()> {
+        let query = "UPDATE users SET locked_until = CASE WHEN failed_login_attempts + 1 >= $1 THEN $2 ELSE locked_until END, failed_login_attempts = CASE WHEN failed_login_attempts + 1 >= $1 THEN 0 ELSE failed_login_attempts + 1 END WHERE id = $3";
+        sqlx::query(query)
+            .bind(max_attempts as i32)
+            .bind(locked_until)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn reset_login_failures(&self, id: i32) -> AppResult<()> {
+        let query = "UPDATE users SET failed_login_attempts = 0, locked_until = NULL WHERE id = $1";
+        sqlx::query(query).bind(id).execute(&self.pool).await?;
+        Ok(())
     }
 
-    async fn delete(&self, id: i32) -> bool {
-        let query = "DELETE FROM user WHERE id = $1";
-        if let Ok(_) = sqlx::query(query).bind(id).execute(&self.pool).await {
-            true
-        } else {
-            false
+    async fn purge_deleted_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let users = "SELECT id FROM users WHERE deleted_at IS NOT NULL AND deleted_at < $1";
+        let mut tx = self.pool.begin().await?;
+        for query in purge_owned_queries(users) {
+            sqlx::query(&query).bind(cutoff).execute(&mut *tx).await?;
         }
+        let query = "DELETE FROM users WHERE deleted_at IS NOT NULL AND deleted_at < $1";
+        let res = sqlx::query(query).bind(cutoff).execute(&mut *tx).await?;
+        tx.commit().await?;
+        Ok(res.rows_affected())
     }
 }
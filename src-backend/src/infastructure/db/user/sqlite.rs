@@ -0,0 +1,404 @@
+use chrono::{DateTime, Local};
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+
+use crate::domain::{
+    entities::user::User,
+    error::AppResult,
+    repository::{
+        user::{UserFilter, UserQuery, UserRepository},
+        Page,
+    },
+};
+
+use super::{delete_owned_queries, like_pattern, purge_owned_queries};
+
+pub struct SqliteUserRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteUserRepository {
+    pub fn new(pool: SqlitePool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+fn push_conditions(builder: &mut QueryBuilder<'_, Sqlite>, filter: &UserFilter) {
+    builder.push(" WHERE deleted_at IS NULL");
+    if let Some(search) = filter.search.as_deref() {
+        let pattern = like_pattern(search);
+        builder
+            .push(" AND (LOWER(username) LIKE ")
+            .push_bind(pattern.clone())
+            .push(" ESCAPE '!' OR LOWER(email) LIKE ")
+            .push_bind(pattern)
+            .push(" ESCAPE '!')");
+    }
+    if let Some(role) = filter.role {
+        builder.push(" AND role = ").push_bind(role);
+    }
+    if let Some(status) = filter.status {
+        builder.push(" AND status = ").push_bind(status);
+    }
+}
+
+#[async_trait::async_trait]
+impl UserRepository for SqliteUserRepository {
+    async fn get_by_id(&self, id: i32) -> AppResult<Option<User>> {
+        let query = "SELECT * FROM users WHERE id = ? AND deleted_at IS NULL";
+        let user = sqlx::query_as::<_, User>(query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(user)
+    }
+
+    async fn get_by_email(&self, email: String) -> AppResult<Option<User>> {
+        let query = "SELECT * FROM users WHERE email = ? AND deleted_at IS NULL";
+        let user = sqlx::query_as::<_, User>(query)
+            .bind(email)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(user)
+    }
+
+    async fn search(&self, query: &UserQuery) -> AppResult<Page<User>> {
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM users");
+        push_conditions(&mut count, &query.filter);
+        let total: i64 = count.build_query_scalar().fetch_one(&self.pool).await?;
+
+        let mut select = QueryBuilder::new("SELECT * FROM users");
+        push_conditions(&mut select, &query.filter);
+        select
+            .push(" ORDER BY id ASC LIMIT ")
+            .push_bind(i64::from(query.page_size))
+            .push(" OFFSET ")
+            .push_bind(query.offset());
+        let items = select
+            .build_query_as::<User>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Page {
+            items,
+            total,
+            page: query.page,
+            page_size: query.page_size,
+        })
+    }
+
+    async fn create(&self, user: &User) -> AppResult<User> {
+        let query = "INSERT INTO users (username, email, password, role, status, created_at, updated_at, deleted_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?) RETURNING *";
+        let user = sqlx::query_as::<_, User>(query)
+            .bind(user.username.clone())
+            .bind(user.email.clone())
+            .bind(user.password.clone())
+            .bind(user.role)
+            .bind(user.status)
+            .bind(user.created_at)
+            .bind(user.updated_at)
+            .bind(user.deleted_at)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(user)
+    }
+
+    async fn save(&self, user: User) -> AppResult<()> {
+        let query =
+            "UPDATE users SET username = ?, email = ?, password = ?, email_verified_at = ?, role = ?, status = ?, updated_at = ?, deleted_at = ? WHERE id = ?";
+        sqlx::query(query)
+            .bind(user.username.clone())
+            .bind(user.email)
+            .bind(user.password)
+            .bind(user.email_verified_at)
+            .bind(user.role)
+            .bind(user.status)
+            .bind(user.updated_at)
+            .bind(user.deleted_at)
+            .bind(user.id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, id: i32) -> AppResult<bool> {
+        let now = Local::now();
+        let mut tx = self.pool.begin().await?;
+        let query = "UPDATE users SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(now)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Ok(false);
+        }
+        for query in delete_owned_queries("?", "?") {
+            sqlx::query(&query)
+                .bind(now)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        // 外部身份不再指向已删除的账户, 同一身份之后可以重新注册
+        sqlx::query("DELETE FROM identities WHERE user_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn record_login_failure(
+        &self,
+        id: i32,
+        max_attempts: u32,
+        locked_until: DateTime<Local>,
+    ) -> AppResult<()> {
+        let query = "UPDATE users SET locked_until = CASE WHEN failed_login_attempts + 1 >= ? THEN ? ELSE locked_until END, failed_login_attempts = CASE WHEN failed_login_attempts + 1 >= ? THEN 0 ELSE failed_login_attempts + 1 END WHERE id = ?";
+        sqlx::query(query)
+            .bind(max_attempts as i32)
+            .bind(locked_until)
+            .bind(max_attempts as i32)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn reset_login_failures(&self, id: i32) -> AppResult<()> {
+        let query = "UPDATE users SET failed_login_attempts = 0, locked_until = NULL WHERE id = ?";
+        sqlx::query(query).bind(id).execute(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn purge_deleted_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let users = "SELECT id FROM users WHERE deleted_at IS NOT NULL AND deleted_at < ?";
+        let mut tx = self.pool.begin().await?;
+        for query in purge_owned_queries(users) {
+            sqlx::query(&query).bind(cutoff).execute(&mut *tx).await?;
+        }
+        let query = "DELETE FROM users WHERE deleted_at IS NOT NULL AND deleted_at < ?";
+        let res = sqlx::query(query).bind(cutoff).execute(&mut *tx).await?;
+        tx.commit().await?;
+        Ok(res.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::{
+        domain::entities::user::{UserRole, UserStatus},
+        infastructure::db::{user::USER_OWNED_TABLES, SQLITE_MIGRATOR},
+    };
+
+    async fn setup() -> SqliteUserRepository {
+        // 内存数据库只在单个连接内可见
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        SQLITE_MIGRATOR.run(&pool).await.unwrap();
+        SqliteUserRepository::new(pool).unwrap()
+    }
+
+    async fn create_user(repo: &SqliteUserRepository, username: &str, email: &str) -> User {
+        repo.create(&User::new(
+            username.to_string(),
+            "hash".to_string(),
+            email.to_string(),
+            Local::now(),
+            Local::now(),
+            None,
+        ))
+        .await
+        .unwrap()
+    }
+
+    fn query(filter: UserFilter) -> UserQuery {
+        UserQuery {
+            filter,
+            page: 1,
+            page_size: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_role_and_status_roundtrip() {
+        let repo = setup().await;
+        let mut user = create_user(&repo, "alice", "alice@example.com").await;
+        assert_eq!(user.role, UserRole::User);
+        assert_eq!(user.status, UserStatus::Active);
+
+        user.role = UserRole::Admin;
+        user.status = UserStatus::Suspended;
+        repo.save(user.clone()).await.unwrap();
+        let stored = repo.get_by_id(user.id).await.unwrap().unwrap();
+        assert_eq!(stored.role, UserRole::Admin);
+        assert_eq!(stored.status, UserStatus::Suspended);
+    }
+
+    #[tokio::test]
+    async fn test_search_filters_and_escapes_wildcards() {
+        let repo = setup().await;
+        let alice = create_user(&repo, "Alice", "alice@example.com").await;
+        create_user(&repo, "bob", "bob_smith@example.com").await;
+        create_user(&repo, "carol", "carol@test.org").await;
+        let deleted = create_user(&repo, "alina", "alina@example.com").await;
+        repo.delete(deleted.id).await.unwrap();
+
+        let page = repo
+            .search(&query(UserFilter {
+                search: Some("ALI".to_string()),
+                ..UserFilter::default()
+            }))
+            .await
+            .unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].id, alice.id);
+
+        // _ 按字面匹配, 不作为通配符
+        let page = repo
+            .search(&query(UserFilter {
+                search: Some("b_s".to_string()),
+                ..UserFilter::default()
+            }))
+            .await
+            .unwrap();
+        assert_eq!(page.total, 1);
+        let page = repo
+            .search(&query(UserFilter {
+                search: Some("a_i".to_string()),
+                ..UserFilter::default()
+            }))
+            .await
+            .unwrap();
+        assert_eq!(page.total, 0);
+
+        let mut admin = alice.clone();
+        admin.role = UserRole::Admin;
+        repo.save(admin).await.unwrap();
+        let page = repo
+            .search(&query(UserFilter {
+                role: Some(UserRole::Admin),
+                ..UserFilter::default()
+            }))
+            .await
+            .unwrap();
+        assert_eq!(page.total, 1);
+
+        let page = repo
+            .search(&UserQuery {
+                filter: UserFilter::default(),
+                page: 2,
+                page_size: 2,
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].username, "carol");
+    }
+
+    // 直接写入各个表, 模拟用户拥有的数据
+    async fn seed_owned(repo: &SqliteUserRepository, user_id: i32) {
+        let key = format!("u{user_id}");
+        let statements = [
+            "INSERT INTO todos (user_id, title) VALUES (?, ?)",
+            "INSERT INTO tags (user_id, name, color) VALUES (?, ?, '#000000')",
+            "INSERT INTO sessions (user_id, family_id, ip_address, expires_at) VALUES (?, ?, '', CURRENT_TIMESTAMP)",
+            "INSERT INTO refresh_tokens (user_id, family_id, token_hash, expires_at) VALUES (?, ?, ?2, CURRENT_TIMESTAMP)",
+            "INSERT INTO api_tokens (user_id, name, token_prefix, token_hash, scopes) VALUES (?, ?, ?2, ?2, '')",
+            "INSERT INTO user_tokens (user_id, purpose, token_hash, expires_at) VALUES (?, ?, ?2, CURRENT_TIMESTAMP)",
+            "INSERT INTO totp_credentials (user_id, secret) VALUES (?, ?)",
+            "INSERT INTO recovery_codes (user_id, code_hash) VALUES (?, ?)",
+            "INSERT INTO identities (user_id, issuer, subject) VALUES (?, ?, ?2)",
+        ];
+        for statement in statements {
+            sqlx::query(statement)
+                .bind(user_id)
+                .bind(&key)
+                .execute(&repo.pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO todo_tags (todo_id, tag_id) SELECT todos.id, tags.id FROM todos, tags WHERE todos.user_id = ? AND tags.user_id = todos.user_id",
+        )
+        .bind(user_id)
+        .execute(&repo.pool)
+        .await
+        .unwrap();
+    }
+
+    async fn count_owned(repo: &SqliteUserRepository, user_id: i32) -> i64 {
+        let mut total = 0;
+        for table in USER_OWNED_TABLES {
+            let query = format!("SELECT COUNT(*) FROM {table} WHERE user_id = ?");
+            let count: i64 = sqlx::query_scalar(&query)
+                .bind(user_id)
+                .fetch_one(&repo.pool)
+                .await
+                .unwrap();
+            total += count;
+        }
+        let query = "SELECT COUNT(*) FROM todo_tags WHERE tag_id IN (SELECT id FROM tags WHERE user_id = ?) OR todo_id IN (SELECT id FROM todos WHERE user_id = ?)";
+        let count: i64 = sqlx::query_scalar(query)
+            .bind(user_id)
+            .bind(user_id)
+            .fetch_one(&repo.pool)
+            .await
+            .unwrap();
+        total + count
+    }
+
+    #[tokio::test]
+    async fn test_purge_removes_owned_rows() {
+        let repo = setup().await;
+        let gone = create_user(&repo, "gone", "gone@example.com").await;
+        let kept = create_user(&repo, "kept", "kept@example.com").await;
+        seed_owned(&repo, gone.id).await;
+        seed_owned(&repo, kept.id).await;
+        let before = count_owned(&repo, kept.id).await;
+        repo.delete(gone.id).await.unwrap();
+
+        let purged = repo
+            .purge_deleted_before(Local::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(purged, 1);
+        assert_eq!(count_owned(&repo, gone.id).await, 0);
+        assert_eq!(count_owned(&repo, kept.id).await, before);
+        // 删除后邮箱可以重新注册
+        create_user(&repo, "gone", "gone@example.com").await;
+    }
+
+    #[tokio::test]
+    async fn test_delete_trashes_todos_and_revokes_credentials() {
+        let repo = setup().await;
+        let user = create_user(&repo, "alice", "alice@example.com").await;
+        seed_owned(&repo, user.id).await;
+
+        assert!(repo.delete(user.id).await.unwrap());
+        let checks = [
+            "SELECT COUNT(*) FROM todos WHERE user_id = ? AND deleted_at IS NULL",
+            "SELECT COUNT(*) FROM sessions WHERE user_id = ? AND revoked_at IS NULL",
+            "SELECT COUNT(*) FROM refresh_tokens WHERE user_id = ? AND revoked_at IS NULL",
+            "SELECT COUNT(*) FROM api_tokens WHERE user_id = ? AND revoked_at IS NULL",
+            "SELECT COUNT(*) FROM user_tokens WHERE user_id = ? AND used_at IS NULL",
+        ];
+        for check in checks {
+            let count: i64 = sqlx::query_scalar(check)
+                .bind(user.id)
+                .fetch_one(&repo.pool)
+                .await
+                .unwrap();
+            assert_eq!(count, 0, "{check}");
+        }
+        // 重复删除不再生效
+        assert!(!repo.delete(user.id).await.unwrap());
+    }
+}
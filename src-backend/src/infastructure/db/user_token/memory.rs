@@ -0,0 +1,119 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Local};
+
+use crate::{
+    domain::{
+        entities::user_token::{TokenPurpose, UserToken},
+        error::{AppError, AppResult},
+        repository::user_token::UserTokenRepository,
+    },
+    infastructure::db::user::memory::UserOwnedStore,
+};
+
+#[derive(Default)]
+struct Store {
+    tokens: BTreeMap<i32, UserToken>,
+    next_id: i32,
+}
+
+// 基于内存的实现, 用于单元测试和接口测试, 克隆后共享同一份数据
+#[derive(Default, Clone)]
+pub struct InMemoryUserTokenRepository {
+    store: Arc<Mutex<Store>>,
+}
+
+impl InMemoryUserTokenRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl UserTokenRepository for InMemoryUserTokenRepository {
+    async fn create(&self, token: &UserToken) -> AppResult<UserToken> {
+        let mut store = self.store.lock().unwrap();
+        if store
+            .tokens
+            .values()
+            .any(|existing| existing.token_hash == token.token_hash)
+        {
+            return Err(AppError::Conflict("Record already exists".to_string()));
+        }
+        store.next_id += 1;
+        let token = UserToken {
+            id: store.next_id,
+            ..token.clone()
+        };
+        store.tokens.insert(token.id, token.clone());
+        Ok(token)
+    }
+
+    async fn get_by_hash(&self, token_hash: &str) -> AppResult<Option<UserToken>> {
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .tokens
+            .values()
+            .find(|token| token.token_hash == token_hash)
+            .cloned())
+    }
+
+    async fn mark_used(&self, id: i32, used_at: DateTime<Local>) -> AppResult<bool> {
+        let mut store = self.store.lock().unwrap();
+        match store.tokens.get_mut(&id) {
+            Some(token) if token.used_at.is_none() => {
+                token.used_at = Some(used_at);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn invalidate_by_user_id(
+        &self,
+        user_id: i32,
+        purpose: TokenPurpose,
+        used_at: DateTime<Local>,
+    ) -> AppResult<u64> {
+        let mut store = self.store.lock().unwrap();
+        let mut count = 0;
+        for token in store.tokens.values_mut() {
+            if token.user_id == user_id
+                && token.purpose == purpose.as_str()
+                && token.used_at.is_none()
+            {
+                token.used_at = Some(used_at);
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let mut store = self.store.lock().unwrap();
+        let before = store.tokens.len();
+        store.tokens.retain(|_, token| token.expires_at >= cutoff);
+        Ok((before - store.tokens.len()) as u64)
+    }
+}
+
+impl UserOwnedStore for InMemoryUserTokenRepository {
+    fn delete_user(&self, user_id: i32, at: DateTime<Local>) {
+        let mut store = self.store.lock().unwrap();
+        for token in store.tokens.values_mut() {
+            if token.user_id == user_id && token.used_at.is_none() {
+                token.used_at = Some(at);
+            }
+        }
+    }
+
+    fn purge_users(&self, user_ids: &[i32]) {
+        let mut store = self.store.lock().unwrap();
+        store
+            .tokens
+            .retain(|_, token| !user_ids.contains(&token.user_id));
+    }
+}
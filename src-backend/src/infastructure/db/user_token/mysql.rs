@@ -0,0 +1,78 @@
+use chrono::{DateTime, Local};
+use sqlx::MySqlPool;
+
+use crate::domain::{
+    entities::user_token::{TokenPurpose, UserToken},
+    error::AppResult,
+    repository::user_token::UserTokenRepository,
+};
+
+pub struct MySqlUserTokenRepository {
+    pool: MySqlPool,
+}
+
+impl MySqlUserTokenRepository {
+    pub fn new(pool: MySqlPool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait::async_trait]
+impl UserTokenRepository for MySqlUserTokenRepository {
+    async fn create(&self, token: &UserToken) -> AppResult<UserToken> {
+        let query = "INSERT INTO user_tokens (user_id, purpose, token_hash, created_at, expires_at) VALUES (?, ?, ?, ?, ?)";
+        let res = sqlx::query(query)
+            .bind(token.user_id)
+            .bind(token.purpose.clone())
+            .bind(token.token_hash.clone())
+            .bind(token.created_at)
+            .bind(token.expires_at)
+            .execute(&self.pool)
+            .await?;
+        Ok(UserToken {
+            id: res.last_insert_id() as i32,
+            ..token.clone()
+        })
+    }
+
+    async fn get_by_hash(&self, token_hash: &str) -> AppResult<Option<UserToken>> {
+        let query = "SELECT * FROM user_tokens WHERE token_hash = ?";
+        let token = sqlx::query_as::<_, UserToken>(query)
+            .bind(token_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(token)
+    }
+
+    async fn mark_used(&self, id: i32, used_at: DateTime<Local>) -> AppResult<bool> {
+        let query = "UPDATE user_tokens SET used_at = ? WHERE id = ? AND used_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(used_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn invalidate_by_user_id(
+        &self,
+        user_id: i32,
+        purpose: TokenPurpose,
+        used_at: DateTime<Local>,
+    ) -> AppResult<u64> {
+        let query = "UPDATE user_tokens SET used_at = ? WHERE user_id = ? AND purpose = ? AND used_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(used_at)
+            .bind(user_id)
+            .bind(purpose.as_str())
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let query = "DELETE FROM user_tokens WHERE expires_at < ?";
+        let res = sqlx::query(query).bind(cutoff).execute(&self.pool).await?;
+        Ok(res.rows_affected())
+    }
+}
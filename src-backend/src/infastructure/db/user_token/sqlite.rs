@@ -0,0 +1,154 @@
+use chrono::{DateTime, Local};
+use sqlx::SqlitePool;
+
+use crate::domain::{
+    entities::user_token::{TokenPurpose, UserToken},
+    error::AppResult,
+    repository::user_token::UserTokenRepository,
+};
+
+pub struct SqliteUserTokenRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteUserTokenRepository {
+    pub fn new(pool: SqlitePool) -> Result<Self, sqlx::Error> {
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait::async_trait]
+impl UserTokenRepository for SqliteUserTokenRepository {
+    async fn create(&self, token: &UserToken) -> AppResult<UserToken> {
+        let query = "INSERT INTO user_tokens (user_id, purpose, token_hash, created_at, expires_at) VALUES (?, ?, ?, ?, ?) RETURNING *";
+        let token = sqlx::query_as::<_, UserToken>(query)
+            .bind(token.user_id)
+            .bind(token.purpose.clone())
+            .bind(token.token_hash.clone())
+            .bind(token.created_at)
+            .bind(token.expires_at)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(token)
+    }
+
+    async fn get_by_hash(&self, token_hash: &str) -> AppResult<Option<UserToken>> {
+        let query = "SELECT * FROM user_tokens WHERE token_hash = ?";
+        let token = sqlx::query_as::<_, UserToken>(query)
+            .bind(token_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(token)
+    }
+
+    async fn mark_used(&self, id: i32, used_at: DateTime<Local>) -> AppResult<bool> {
+        let query = "UPDATE user_tokens SET used_at = ? WHERE id = ? AND used_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(used_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn invalidate_by_user_id(
+        &self,
+        user_id: i32,
+        purpose: TokenPurpose,
+        used_at: DateTime<Local>,
+    ) -> AppResult<u64> {
+        let query = "UPDATE user_tokens SET used_at = ? WHERE user_id = ? AND purpose = ? AND used_at IS NULL";
+        let res = sqlx::query(query)
+            .bind(used_at)
+            .bind(user_id)
+            .bind(purpose.as_str())
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    async fn purge_expired_before(&self, cutoff: DateTime<Local>) -> AppResult<u64> {
+        let query = "DELETE FROM user_tokens WHERE expires_at < ?";
+        let res = sqlx::query(query).bind(cutoff).execute(&self.pool).await?;
+        Ok(res.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::infastructure::db::SQLITE_MIGRATOR;
+
+    async fn setup() -> SqliteUserTokenRepository {
+        // 内存数据库只在单个连接内可见
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        SQLITE_MIGRATOR.run(&pool).await.unwrap();
+        SqliteUserTokenRepository::new(pool).unwrap()
+    }
+
+    fn new_token(user_id: i32, purpose: TokenPurpose, token_hash: &str) -> UserToken {
+        UserToken::new(
+            user_id,
+            purpose,
+            token_hash.to_string(),
+            Local::now(),
+            Local::now() + Duration::hours(1),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_mark_used_only_once() {
+        let repo = setup().await;
+        let token = repo
+            .create(&new_token(1, TokenPurpose::PasswordReset, "hash"))
+            .await
+            .unwrap();
+        assert!(token.id > 0);
+        assert_eq!(token.purpose, "password_reset");
+
+        assert!(repo.mark_used(token.id, Local::now()).await.unwrap());
+        assert!(!repo.mark_used(token.id, Local::now()).await.unwrap());
+        let found = repo.get_by_hash("hash").await.unwrap().unwrap();
+        assert!(found.used_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_by_purpose() {
+        let repo = setup().await;
+        repo.create(&new_token(1, TokenPurpose::PasswordReset, "r1"))
+            .await
+            .unwrap();
+        repo.create(&new_token(1, TokenPurpose::PasswordReset, "r2"))
+            .await
+            .unwrap();
+        repo.create(&new_token(1, TokenPurpose::EmailVerification, "v1"))
+            .await
+            .unwrap();
+        repo.create(&new_token(2, TokenPurpose::PasswordReset, "o1"))
+            .await
+            .unwrap();
+
+        let count = repo
+            .invalidate_by_user_id(1, TokenPurpose::PasswordReset, Local::now())
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+        for (hash, used) in [("r1", true), ("r2", true), ("v1", false), ("o1", false)] {
+            let token = repo.get_by_hash(hash).await.unwrap().unwrap();
+            assert_eq!(token.used_at.is_some(), used);
+        }
+
+        let purged = repo
+            .purge_expired_before(Local::now() + Duration::hours(2))
+            .await
+            .unwrap();
+        assert_eq!(purged, 4);
+    }
+}
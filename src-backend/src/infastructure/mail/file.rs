@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+
+use chrono::Local;
+use tokio::io::AsyncWriteExt;
+
+use crate::utils::encryption::token::generate_token;
+
+use super::{Email, Mailer};
+
+// 每封邮件写入 dir 下的一个 .eml 文件, 用于没有邮件服务器的本地开发
+pub struct FileMailer {
+    dir: PathBuf,
+    from: String,
+}
+
+impl FileMailer {
+    pub fn new(dir: &str, from: &str) -> Self {
+        Self {
+            dir: PathBuf::from(dir),
+            from: from.to_string(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, email: Email) -> anyhow::Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let now = Local::now();
+        let path = self.dir.join(format!(
+            "{}-{}.eml",
+            now.format("%Y%m%d%H%M%S%.6f"),
+            &generate_token()[..8]
+        ));
+        let content = format!(
+            "Date: {}\r\nFrom: {}\r\nTo: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}\r\n",
+            now.to_rfc2822(),
+            self.from,
+            email.to,
+            email.subject,
+            email.body
+        );
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await?;
+        file.write_all(content.as_bytes()).await?;
+        // tokio 的文件写入在后台完成, flush 之后才保证内容已写入
+        file.flush().await?;
+        log::info!("wrote email to {} into {}", email.to, path.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_writes_one_file_per_email() {
+        let dir = std::env::temp_dir().join(format!("mithril-mail-{}", generate_token()));
+        let mailer = FileMailer::new(dir.to_str().unwrap(), "Mithril <no-reply@localhost>");
+        for subject in ["first", "second"] {
+            mailer
+                .send(Email {
+                    to: "user@example.com".to_string(),
+                    subject: subject.to_string(),
+                    body: "hello".to_string(),
+                })
+                .await
+                .unwrap();
+        }
+
+        let mut contents = Vec::new();
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            contents.push(std::fs::read_to_string(entry.unwrap().path()).unwrap());
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(contents.len(), 2);
+        assert!(contents
+            .iter()
+            .all(|content| content.contains("To: user@example.com\r\n")));
+    }
+}
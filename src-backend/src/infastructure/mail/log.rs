@@ -0,0 +1,65 @@
+use std::sync::Mutex;
+
+use tokio::sync::Notify;
+
+use super::{Email, Mailer};
+
+// 内存中最多保留的邮件数量, 避免长时间运行时无限增长
+const MAX_CAPTURED: usize = 100;
+
+#[derive(Default)]
+struct Captured {
+    emails: Vec<Email>,
+    // 累计发送的数量, 不受 MAX_CAPTURED 限制
+    total: usize,
+}
+
+// 只把邮件输出到日志并在内存中保留最近的邮件, 测试中通过 sent 读取
+#[derive(Default)]
+pub struct LogMailer {
+    captured: Mutex<Captured>,
+    notify: Notify,
+}
+
+impl LogMailer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sent(&self) -> Vec<Email> {
+        self.captured.lock().unwrap().emails.clone()
+    }
+
+    pub fn total(&self) -> usize {
+        self.captured.lock().unwrap().total
+    }
+
+    // 等待累计发送的邮件达到 count 封, 用于等待后台任务发送的邮件
+    pub async fn wait_for(&self, count: usize) {
+        loop {
+            // 先注册再检查, 检查之后发送的邮件同样能唤醒
+            let notified = self.notify.notified();
+            if self.total() >= count {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, email: Email) -> anyhow::Result<()> {
+        log::info!("email to {}: {}\n{}", email.to, email.subject, email.body);
+        {
+            let mut captured = self.captured.lock().unwrap();
+            if captured.emails.len() >= MAX_CAPTURED {
+                captured.emails.remove(0);
+            }
+            captured.emails.push(email);
+            captured.total += 1;
+        }
+        self.notify.notify_waiters();
+        Ok(())
+    }
+}
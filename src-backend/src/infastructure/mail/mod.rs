@@ -0,0 +1,34 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::config::{MailConfig, MailTransport};
+
+pub mod file;
+pub mod log;
+pub mod smtp;
+
+// 纯文本邮件
+#[derive(Debug, Clone, Serialize)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait::async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: Email) -> anyhow::Result<()>;
+}
+
+pub fn create_mailer(config: &MailConfig) -> anyhow::Result<Arc<dyn Mailer>> {
+    let mailer: Arc<dyn Mailer> = match config.transport {
+        MailTransport::Smtp => {
+            let url = config.smtp_url.as_deref().unwrap_or_default();
+            Arc::new(smtp::SmtpMailer::new(url, &config.from)?)
+        }
+        MailTransport::File => Arc::new(file::FileMailer::new(&config.dir, &config.from)),
+        MailTransport::Log => Arc::new(log::LogMailer::new()),
+    };
+    Ok(mailer)
+}
@@ -0,0 +1,223 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+    Form, Json, Router,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use reqwest::Url;
+use ring::{
+    rand::SystemRandom,
+    signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING},
+};
+use serde_json::{json, Value};
+
+use super::pkce_challenge;
+use crate::{config::OidcConfig, utils::encryption::token::generate_token};
+
+const CLIENT_ID: &str = "mithril-test";
+const REDIRECT_URL: &str = "http://localhost:1420/oidc/callback";
+const KID: &str = "mock-key";
+
+// 身份提供方中的用户, 由测试决定返回哪些声明
+#[derive(Debug, Clone, Default)]
+pub struct MockUser {
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub name: Option<String>,
+    pub preferred_username: Option<String>,
+}
+
+struct IssuedCode {
+    user: MockUser,
+    nonce: String,
+    code_challenge: String,
+    redirect_uri: String,
+}
+
+struct MockState {
+    issuer: String,
+    pkcs8: Vec<u8>,
+    public_key: Vec<u8>,
+    codes: Mutex<HashMap<String, IssuedCode>>,
+}
+
+impl MockState {
+    fn sign(&self, kid: &str, pkcs8: &[u8], claims: &Value) -> String {
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some(kid.to_string());
+        encode(&header, claims, &EncodingKey::from_ec_der(pkcs8)).unwrap()
+    }
+}
+
+fn generate_key() -> (Vec<u8>, Vec<u8>) {
+    let rng = SystemRandom::new();
+    let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+    let key_pair =
+        EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng).unwrap();
+    (
+        pkcs8.as_ref().to_vec(),
+        key_pair.public_key().as_ref().to_vec(),
+    )
+}
+
+// 本地运行的 OIDC 身份提供方, 提供发现文档, JWKS 和 token 接口, 授权页面由 authorize 模拟
+pub struct MockProvider {
+    state: Arc<MockState>,
+}
+
+impl MockProvider {
+    pub async fn start() -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let (pkcs8, public_key) = generate_key();
+        let state = Arc::new(MockState {
+            issuer,
+            pkcs8,
+            public_key,
+            codes: Mutex::new(HashMap::new()),
+        });
+        let app = Router::new()
+            .route("/.well-known/openid-configuration", get(discovery))
+            .route("/jwks", get(jwks))
+            .route("/token", post(token))
+            .with_state(state.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        Self { state }
+    }
+
+    pub fn issuer(&self) -> &str {
+        &self.state.issuer
+    }
+
+    pub fn client_id(&self) -> &str {
+        CLIENT_ID
+    }
+
+    pub fn config(&self) -> OidcConfig {
+        OidcConfig {
+            issuer: self.state.issuer.clone(),
+            client_id: CLIENT_ID.to_string(),
+            client_secret: None,
+            redirect_url: REDIRECT_URL.to_string(),
+            scopes: vec!["openid".to_string(), "email".to_string()],
+        }
+    }
+
+    // 模拟用户在授权页面同意登录, 返回回调地址中的 code 和 state
+    pub fn authorize(&self, authorization_url: &str, user: MockUser) -> (String, String) {
+        let url = Url::parse(authorization_url).unwrap();
+        let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        assert_eq!(params["response_type"], "code");
+        assert_eq!(params["client_id"], CLIENT_ID);
+        assert_eq!(params["code_challenge_method"], "S256");
+        let code = generate_token();
+        self.state.codes.lock().unwrap().insert(
+            code.clone(),
+            IssuedCode {
+                user,
+                nonce: params["nonce"].clone(),
+                code_challenge: params["code_challenge"].clone(),
+                redirect_uri: params["redirect_uri"].clone(),
+            },
+        );
+        (code, params["state"].clone())
+    }
+
+    pub fn sign(&self, claims: &Value) -> String {
+        self.state.sign(KID, &self.state.pkcs8, claims)
+    }
+
+    // 使用不在 JWKS 中的密钥签名
+    pub fn sign_with_unknown_key(&self, claims: &Value) -> String {
+        let (pkcs8, _) = generate_key();
+        self.state.sign("unknown-key", &pkcs8, claims)
+    }
+}
+
+async fn discovery(State(state): State<Arc<MockState>>) -> Json<Value> {
+    Json(json!({
+        "issuer": state.issuer,
+        "authorization_endpoint": format!("{}/authorize", state.issuer),
+        "token_endpoint": format!("{}/token", state.issuer),
+        "jwks_uri": format!("{}/jwks", state.issuer),
+        "response_types_supported": ["code"],
+        "id_token_signing_alg_values_supported": ["ES256"],
+    }))
+}
+
+async fn jwks(State(state): State<Arc<MockState>>) -> Json<Value> {
+    // 未压缩的 P-256 公钥: 0x04 || x || y
+    let (x, y) = state.public_key[1..].split_at(32);
+    Json(json!({
+        "keys": [{
+            "kty": "EC",
+            "crv": "P-256",
+            "use": "sig",
+            "alg": "ES256",
+            "kid": KID,
+            "x": URL_SAFE_NO_PAD.encode(x),
+            "y": URL_SAFE_NO_PAD.encode(y),
+        }]
+    }))
+}
+
+fn invalid_grant() -> axum::response::Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({ "error": "invalid_grant" })),
+    )
+        .into_response()
+}
+
+async fn token(
+    State(state): State<Arc<MockState>>,
+    Form(form): Form<HashMap<String, String>>,
+) -> axum::response::Response {
+    let field = |key: &str| form.get(key).map(String::as_str).unwrap_or_default();
+    if field("grant_type") != "authorization_code" || field("client_id") != CLIENT_ID {
+        return invalid_grant();
+    }
+    // 授权码只能使用一次
+    let Some(issued) = state.codes.lock().unwrap().remove(field("code")) else {
+        return invalid_grant();
+    };
+    if field("redirect_uri") != issued.redirect_uri
+        || pkce_challenge(field("code_verifier")) != issued.code_challenge
+    {
+        return invalid_grant();
+    }
+    let now = chrono::Local::now().timestamp();
+    let user = issued.user;
+    let id_token = state.sign(
+        KID,
+        &state.pkcs8,
+        &json!({
+            "iss": state.issuer,
+            "aud": CLIENT_ID,
+            "sub": user.subject,
+            "iat": now,
+            "exp": now + 300,
+            "nonce": issued.nonce,
+            "email": user.email,
+            "email_verified": user.email_verified,
+            "name": user.name,
+            "preferred_username": user.preferred_username,
+        }),
+    );
+    Json(json!({
+        "access_token": generate_token(),
+        "token_type": "Bearer",
+        "expires_in": 300,
+        "id_token": id_token,
+    }))
+    .into_response()
+}
@@ -0,0 +1,305 @@
+use std::{fmt, time::Duration};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use reqwest::Url;
+use ring::digest;
+use serde::Deserialize;
+use tokio::{
+    sync::{Mutex, OnceCell},
+    time::Instant,
+};
+
+use crate::{
+    config::OidcConfig,
+    domain::error::{AppError, AppResult},
+};
+
+#[cfg(test)]
+pub mod mock;
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+// 遇到未知 kid 时重新拉取 JWKS, 但限制频率, 避免伪造的 kid 触发大量请求
+const JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+// 校验 exp/iat 时允许的时钟偏差
+const LEEWAY_SECS: u64 = 60;
+
+// 发现文档中用到的字段
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+// id_token 中用到的声明, iss/aud/exp 由 jsonwebtoken 校验
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    pub name: Option<String>,
+    pub preferred_username: Option<String>,
+    pub nonce: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Deserialize)]
+struct TokenErrorResponse {
+    error: String,
+}
+
+struct CachedJwks {
+    keys: JwkSet,
+    fetched_at: Instant,
+}
+
+// PKCE S256: BASE64URL(SHA256(code_verifier))
+pub fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(digest::digest(&digest::SHA256, verifier.as_bytes()))
+}
+
+fn provider_error(err: impl fmt::Display) -> AppError {
+    AppError::Internal(format!("OIDC provider error: {err}"))
+}
+
+// 具体原因只记录在日志中, 不返回给客户端
+fn rejected(err: impl fmt::Display) -> AppError {
+    log::warn!("rejected OIDC login: {err}");
+    AppError::Unauthorized("OIDC login failed".to_string())
+}
+
+// 授权码 + PKCE 流程的客户端, 发现文档和 JWKS 在首次使用时拉取并缓存
+pub struct OidcClient {
+    config: OidcConfig,
+    http: reqwest::Client,
+    metadata: OnceCell<ProviderMetadata>,
+    jwks: Mutex<Option<CachedJwks>>,
+}
+
+impl OidcClient {
+    pub fn new(config: OidcConfig) -> anyhow::Result<Self> {
+        let http = reqwest::Client::builder().timeout(HTTP_TIMEOUT).build()?;
+        Ok(Self {
+            config,
+            http,
+            metadata: OnceCell::new(),
+            jwks: Mutex::new(None),
+        })
+    }
+
+    pub fn issuer(&self) -> &str {
+        &self.config.issuer
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> AppResult<T> {
+        self.http
+            .get(url)
+            .send()
+            .await
+            .and_then(|res| res.error_for_status())
+            .map_err(provider_error)?
+            .json()
+            .await
+            .map_err(provider_error)
+    }
+
+    pub async fn metadata(&self) -> AppResult<&ProviderMetadata> {
+        self.metadata
+            .get_or_try_init(|| async {
+                let url = format!("{}/.well-known/openid-configuration", self.config.issuer);
+                let metadata: ProviderMetadata = self.get_json(&url).await?;
+                // 防止发现文档被替换为其他身份提供方
+                if metadata.issuer.trim_end_matches('/') != self.config.issuer {
+                    return Err(provider_error(format!(
+                        "discovery document issuer `{}` does not match `{}`",
+                        metadata.issuer, self.config.issuer
+                    )));
+                }
+                Ok(metadata)
+            })
+            .await
+    }
+
+    pub async fn authorization_url(
+        &self,
+        state: &str,
+        nonce: &str,
+        code_verifier: &str,
+    ) -> AppResult<String> {
+        let metadata = self.metadata().await?;
+        let mut url = Url::parse(&metadata.authorization_endpoint).map_err(provider_error)?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.config.redirect_url)
+            .append_pair("scope", &self.config.scopes.join(" "))
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", &pkce_challenge(code_verifier))
+            .append_pair("code_challenge_method", "S256");
+        Ok(url.into())
+    }
+
+    // 用授权码换取 id_token 并校验签名, iss, aud, exp 和 nonce
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> AppResult<IdTokenClaims> {
+        let metadata = self.metadata().await?;
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_url.as_str()),
+            ("client_id", self.config.client_id.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+        let res = self
+            .http
+            .post(&metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(provider_error)?;
+        let status = res.status();
+        if status.is_client_error() {
+            let error = res
+                .json::<TokenErrorResponse>()
+                .await
+                .map(|body| body.error)
+                .unwrap_or_else(|_| status.to_string());
+            return Err(rejected(format!("token endpoint returned {error}")));
+        }
+        let res: TokenResponse = res
+            .error_for_status()
+            .map_err(provider_error)?
+            .json()
+            .await
+            .map_err(provider_error)?;
+        self.verify_id_token(&res.id_token, nonce).await
+    }
+
+    async fn verify_id_token(&self, token: &str, nonce: &str) -> AppResult<IdTokenClaims> {
+        let metadata = self.metadata().await?;
+        let header = decode_header(token).map_err(rejected)?;
+        // 对称算法会以 client secret 为密钥, 只接受身份提供方的公钥签名
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(rejected(format!("unsupported algorithm {:?}", header.alg)));
+        }
+        let kid = header.kid.ok_or_else(|| rejected("id_token has no kid"))?;
+        let key = self.decoding_key(&kid).await?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.leeway = LEEWAY_SECS;
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["sub", "iss", "aud", "exp"]);
+        let claims = decode::<IdTokenClaims>(token, &key, &validation)
+            .map_err(rejected)?
+            .claims;
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(rejected("nonce mismatch"));
+        }
+        Ok(claims)
+    }
+
+    async fn decoding_key(&self, kid: &str) -> AppResult<DecodingKey> {
+        let mut cache = self.jwks.lock().await;
+        let stale = cache
+            .as_ref()
+            .is_none_or(|jwks| jwks.fetched_at.elapsed() >= JWKS_REFRESH_INTERVAL);
+        let known = cache
+            .as_ref()
+            .is_some_and(|jwks| jwks.keys.find(kid).is_some());
+        if !known && stale {
+            let metadata = self.metadata().await?;
+            *cache = Some(CachedJwks {
+                keys: self.get_json(&metadata.jwks_uri).await?,
+                fetched_at: Instant::now(),
+            });
+        }
+        let jwk = cache
+            .as_ref()
+            .and_then(|jwks| jwks.keys.find(kid))
+            .ok_or_else(|| rejected(format!("unknown kid `{kid}`")))?;
+        DecodingKey::from_jwk(jwk).map_err(provider_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{mock::MockProvider, *};
+
+    #[test]
+    fn test_pkce_challenge() {
+        // RFC 7636 附录 B 中的示例
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_id_token() {
+        let provider = MockProvider::start().await;
+        let client = OidcClient::new(provider.config()).unwrap();
+        let now = chrono::Local::now().timestamp();
+        let claims = |overrides: serde_json::Value| {
+            let mut claims = json!({
+                "iss": provider.issuer(),
+                "aud": provider.client_id(),
+                "sub": "subject-1",
+                "iat": now,
+                "exp": now + 300,
+                "nonce": "nonce-1",
+            });
+            claims
+                .as_object_mut()
+                .unwrap()
+                .extend(overrides.as_object().unwrap().clone());
+            claims
+        };
+
+        let verified = client
+            .verify_id_token(&provider.sign(&claims(json!({}))), "nonce-1")
+            .await
+            .unwrap();
+        assert_eq!(verified.sub, "subject-1");
+        assert!(!verified.email_verified);
+
+        for (token, nonce) in [
+            (provider.sign(&claims(json!({}))), "nonce-2"),
+            (provider.sign(&claims(json!({"aud": "other"}))), "nonce-1"),
+            (
+                provider.sign(&claims(json!({"iss": "https://evil.example.com"}))),
+                "nonce-1",
+            ),
+            (provider.sign(&claims(json!({"exp": now - 600}))), "nonce-1"),
+            (
+                provider.sign_with_unknown_key(&claims(json!({}))),
+                "nonce-1",
+            ),
+        ] {
+            assert!(matches!(
+                client.verify_id_token(&token, nonce).await,
+                Err(AppError::Unauthorized(_))
+            ));
+        }
+    }
+}
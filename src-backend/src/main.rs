@@ -1,15 +1,118 @@
-use src_backend::{api::router::create_router, infastructure::db::DB};
+use std::net::SocketAddr;
+
+use src_backend::{
+    api::router::{create_router, create_user_service_for},
+    application::audit::service::AuditContext,
+    config::AppConfig,
+    domain::entities::user::UserRole,
+    infastructure::db::{connect_db, revert_migration, run_migrations},
+};
 use tokio::signal;
 
 #[tokio::main]
 async fn main() {
-    let app = create_router();
+    let config = match AppConfig::load() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("Invalid configuration: {err}");
+            std::process::exit(2);
+        }
+    };
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("migrate") {
+        migrate(&config, args.get(1).map(String::as_str).unwrap_or("run")).await;
+        return;
+    }
+    if args.first().map(String::as_str) == Some("admin") {
+        admin(
+            &config,
+            args.get(1).map(String::as_str).unwrap_or(""),
+            args.get(2).map(String::as_str),
+        )
+        .await;
+        return;
+    }
+
+    let bind_addr = config.server.bind_addr;
+    let app = match create_router(config).await {
+        Ok(app) => app,
+        Err(err) => {
+            eprintln!("Failed to start server: {err:#}");
+            std::process::exit(1);
+        }
+    };
+
+    let listener = tokio::net::TcpListener::bind(bind_addr).await.unwrap();
+    // 限流等功能需要客户端地址
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await
+    .unwrap();
+}
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
-    axum::serve(listener, app.await)
-        .with_graceful_shutdown(shutdown_signal())
+// 用法: src-backend migrate [run|revert]
+async fn migrate(config: &AppConfig, action: &str) {
+    let database = connect_db(&config.database)
+        .await
+        .expect("Failed to connect to database");
+    match action {
+        "run" => {
+            run_migrations(&database)
+                .await
+                .expect("Failed to run database migrations");
+            println!("Migrations applied");
+        }
+        "revert" => {
+            match revert_migration(&database)
+                .await
+                .expect("Failed to revert database migration")
+            {
+                Some(version) => println!("Reverted migration {version}"),
+                None => println!("No migrations to revert"),
+            }
+        }
+        _ => {
+            eprintln!("Unknown migrate action `{action}`, expected `run` or `revert`");
+            std::process::exit(2);
+        }
+    }
+}
+
+// 用法: src-backend admin [grant|revoke] <email>, 用于创建第一个管理员
+async fn admin(config: &AppConfig, action: &str, email: Option<&str>) {
+    let role = match action {
+        "grant" => UserRole::Admin,
+        "revoke" => UserRole::User,
+        _ => {
+            eprintln!("Unknown admin action `{action}`, expected `grant` or `revoke`");
+            std::process::exit(2);
+        }
+    };
+    let Some(email) = email else {
+        eprintln!("Usage: src-backend admin {action} <email>");
+        std::process::exit(2);
+    };
+    let database = connect_db(&config.database)
+        .await
+        .expect("Failed to connect to database");
+    let user_service =
+        create_user_service_for(database, &config.auth).expect("Failed to create user service");
+    let user = match user_service.get_user_by_email(email.to_string()).await {
+        Ok(user) => user,
+        Err(err) => {
+            eprintln!("Cannot find user `{email}`: {err}");
+            std::process::exit(1);
+        }
+    };
+    user_service
+        .set_role(user.id, role, &AuditContext::system())
         .await
-        .unwrap();
+        .expect("Failed to update user role");
+    println!("Updated role of `{email}` to {role:?}");
 }
 
 async fn shutdown_signal() {
@@ -1,52 +1,175 @@
-use hex;
-use ring::{pbkdf2, rand::SecureRandom};
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher as _, PasswordVerifier, SaltString},
+    Algorithm, Argon2, Params, Version,
+};
+use ring::{
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
 
-const PBKDF2_ITERATIONS: u32 = 101_101;
-const PBKDF2_KEY_LEN: usize = 32;
-const PBKDF2_SALT_LEN: usize = 16;
+const SALT_LEN: usize = 16;
 
-pub struct PasswordHasher {
-    rng: ring::rand::SystemRandom,
+// 旧版本使用 PBKDF2-SHA256 并把盐单独存放, 迁移时合并为
+// $pbkdf2-sha256-hex$i=<iterations>$<salt hex>$<hash hex>
+const LEGACY_PBKDF2_PREFIX: &str = "$pbkdf2-sha256-hex$";
+
+// 密码哈希算法, 哈希结果为自描述的 PHC 字符串
+pub trait PasswordHasher: Send + Sync {
+    fn hash_password(&self, password: &str) -> anyhow::Result<String>;
+    // 以常量时间比较, 无法识别的哈希格式视为校验失败
+    fn verify_password(&self, password: &str, password_hash: &str) -> bool;
+    // 哈希算法或参数已过时, 登录成功后需要重新哈希
+    fn needs_rehash(&self, password_hash: &str) -> bool;
+}
+
+pub struct Argon2Hasher {
+    params: Params,
+    rng: SystemRandom,
+}
+
+impl Default for Argon2Hasher {
+    fn default() -> Self {
+        Self::new(Params::default())
+    }
 }
 
-impl PasswordHasher {
-    pub fn new() -> Self {
+impl Argon2Hasher {
+    pub fn new(params: Params) -> Self {
         Self {
-            rng: ring::rand::SystemRandom::new(),
+            params,
+            rng: SystemRandom::new(),
         }
     }
 
-    pub fn hash_password(&self, password: &str) -> Result<(String, String), anyhow::Error> {
-        let mut salt = [0u8; PBKDF2_SALT_LEN];
-        self.rng.fill(&mut salt).unwrap();
+    fn argon2(&self) -> Argon2<'static> {
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, self.params.clone())
+    }
+}
 
-        let mut pbkdf2_hash = [0u8; PBKDF2_KEY_LEN];
-        pbkdf2::derive(
-            pbkdf2::PBKDF2_HMAC_SHA256,
-            std::num::NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
-            &salt,
-            password.as_bytes(),
-            &mut pbkdf2_hash,
-        );
+impl PasswordHasher for Argon2Hasher {
+    fn hash_password(&self, password: &str) -> anyhow::Result<String> {
+        let mut salt = [0u8; SALT_LEN];
+        self.rng
+            .fill(&mut salt)
+            .map_err(|_| anyhow::anyhow!("failed to generate salt"))?;
+        let salt = SaltString::encode_b64(&salt).map_err(|err| anyhow::anyhow!(err))?;
+        let hash = self
+            .argon2()
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|err| anyhow::anyhow!(err))?;
+        Ok(hash.to_string())
+    }
 
-        let derived_key = hex::encode(pbkdf2_hash);
-        let salt_hex = hex::encode(&salt);
+    fn verify_password(&self, password: &str, password_hash: &str) -> bool {
+        if let Some(legacy) = password_hash.strip_prefix(LEGACY_PBKDF2_PREFIX) {
+            return verify_legacy_pbkdf2(password, legacy);
+        }
+        match PasswordHash::new(password_hash) {
+            // 使用哈希中记录的参数校验, 参数调整后旧哈希仍然有效
+            Ok(hash) => Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok(),
+            Err(_) => false,
+        }
+    }
 
-        Ok((derived_key, salt_hex))
+    fn needs_rehash(&self, password_hash: &str) -> bool {
+        let Ok(hash) = PasswordHash::new(password_hash) else {
+            return true;
+        };
+        if hash.algorithm != Algorithm::Argon2id.ident() || hash.version != Some(0x13) {
+            return true;
+        }
+        match Params::try_from(&hash) {
+            Ok(params) => {
+                params.m_cost() != self.params.m_cost()
+                    || params.t_cost() != self.params.t_cost()
+                    || params.p_cost() != self.params.p_cost()
+            }
+            Err(_) => true,
+        }
     }
+}
+
+// 格式: i=<iterations>$<salt hex>$<hash hex>
+fn verify_legacy_pbkdf2(password: &str, legacy: &str) -> bool {
+    let mut parts = legacy.split('$');
+    let (Some(params), Some(salt), Some(hash), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+    let Some(iterations) = params
+        .strip_prefix("i=")
+        .and_then(|iterations| iterations.parse::<u32>().ok())
+        .and_then(std::num::NonZeroU32::new)
+    else {
+        return false;
+    };
+    let (Ok(salt), Ok(hash)) = (hex::decode(salt), hex::decode(hash)) else {
+        return false;
+    };
+    pbkdf2::verify(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        &salt,
+        password.as_bytes(),
+        &hash,
+    )
+    .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use super::*;
 
-    pub fn verify_password(&self, password: &str, salt: &str, derived_key: &str) -> bool {
-        let salt = hex::decode(salt).unwrap();
-        let derived_key = hex::decode(derived_key).unwrap();
-        let mut pbkdf2_hash = [0u8; PBKDF2_KEY_LEN];
+    fn legacy_hash(password: &str) -> String {
+        let salt = [7u8; 16];
+        let mut hash = [0u8; 32];
         pbkdf2::derive(
             pbkdf2::PBKDF2_HMAC_SHA256,
-            std::num::NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+            NonZeroU32::new(101_101).unwrap(),
             &salt,
             password.as_bytes(),
-            &mut pbkdf2_hash,
+            &mut hash,
         );
-        let derived_key_vec: Vec<u8> = derived_key.into();
-        pbkdf2_hash.to_vec() == derived_key_vec
+        format!(
+            "{LEGACY_PBKDF2_PREFIX}i=101101${}${}",
+            hex::encode(salt),
+            hex::encode(hash)
+        )
+    }
+
+    #[test]
+    fn test_argon2id_roundtrip() {
+        let hasher = Argon2Hasher::default();
+        let hash = hasher.hash_password("password123").unwrap();
+        assert!(hash.starts_with("$argon2id$v=19$"));
+        assert!(hasher.verify_password("password123", &hash));
+        assert!(!hasher.verify_password("password124", &hash));
+        assert!(!hasher.needs_rehash(&hash));
+        assert_ne!(hash, hasher.hash_password("password123").unwrap());
+    }
+
+    #[test]
+    fn test_legacy_pbkdf2_is_verifiable_and_needs_rehash() {
+        let hasher = Argon2Hasher::default();
+        let hash = legacy_hash("password123");
+        assert!(hasher.verify_password("password123", &hash));
+        assert!(!hasher.verify_password("password124", &hash));
+        assert!(hasher.needs_rehash(&hash));
+        assert!(!hasher.verify_password("password123", "not-a-hash"));
+    }
+
+    #[test]
+    fn test_outdated_params_need_rehash() {
+        let weak = Argon2Hasher::new(Params::new(8 * 1024, 1, 1, None).unwrap());
+        let hash = weak.hash_password("password123").unwrap();
+
+        let hasher = Argon2Hasher::default();
+        assert!(hasher.verify_password("password123", &hash));
+        assert!(hasher.needs_rehash(&hash));
     }
 }
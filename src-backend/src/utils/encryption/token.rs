@@ -0,0 +1,18 @@
+use ring::{
+    digest,
+    rand::{SecureRandom, SystemRandom},
+};
+
+const TOKEN_LEN: usize = 32;
+
+// 生成随机的不透明令牌, 以十六进制字符串返回
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; TOKEN_LEN];
+    SystemRandom::new().fill(&mut bytes).unwrap();
+    hex::encode(bytes)
+}
+
+// 令牌只以 SHA-256 哈希的形式落库
+pub fn hash_token(token: &str) -> String {
+    hex::encode(digest::digest(&digest::SHA256, token.as_bytes()))
+}
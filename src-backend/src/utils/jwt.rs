@@ -1,75 +1,317 @@
-use anyhow::Result;
-use axum::{extract::FromRequestParts, http::request::Parts};
-use chrono::{Duration, Local};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
-use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-use crate::api::{
-    self,
-    request::{error_response, Response},
+use anyhow::{anyhow, bail, Result};
+use chrono::Local;
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation,
 };
+use serde::{Deserialize, Serialize};
+
+use crate::config::{JwtAlgorithm, JwtConfig, JwtKeyConfig};
+
+// 校验 exp/nbf/iat 时允许的时钟偏差
+const LEEWAY_SECS: i64 = 60;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     pub sub: String,
-    pub exp: usize,
+    pub iss: String,
+    pub aud: String,
+    pub iat: i64,
+    pub nbf: i64,
+    pub exp: i64,
+    // 签发时所属的会话, 每次请求都会校验会话是否已被吊销
+    pub sid: i32,
+}
+
+struct VerifyingKey {
+    algorithm: Algorithm,
+    key: DecodingKey,
+}
+
+// 由配置构建的签名/校验密钥集合, 按 kid 查找校验密钥以支持密钥轮换
+pub struct JwtKeys {
+    issuer: String,
+    audience: String,
+    ttl: chrono::Duration,
+    active_kid: String,
+    algorithm: Algorithm,
+    encoding_key: EncodingKey,
+    verifying_keys: HashMap<String, VerifyingKey>,
+}
+
+fn algorithm(algorithm: JwtAlgorithm) -> Algorithm {
+    match algorithm {
+        JwtAlgorithm::HS256 => Algorithm::HS256,
+        JwtAlgorithm::RS256 => Algorithm::RS256,
+        JwtAlgorithm::EdDSA => Algorithm::EdDSA,
+    }
+}
+
+fn pem<'a>(pem: &'a Option<String>, key: &JwtKeyConfig, kind: &str) -> Result<&'a [u8]> {
+    pem.as_deref()
+        .map(str::as_bytes)
+        .ok_or_else(|| anyhow!("key `{}` has no {kind} key", key.kid))
 }
 
-pub fn generate_token(user_id: i32) -> Result<String> {
-    let expiration = Local::now() + Duration::hours(24);
-    let claims = Claims {
-        sub: user_id.to_string(),
-        exp: expiration.timestamp() as usize,
+fn encoding_key(key: &JwtKeyConfig) -> Result<EncodingKey> {
+    let encoding_key = match key.algorithm {
+        JwtAlgorithm::HS256 => EncodingKey::from_secret(pem(&key.secret, key, "secret")?),
+        JwtAlgorithm::RS256 => {
+            EncodingKey::from_rsa_pem(pem(&key.private_key_pem, key, "private")?)?
+        }
+        JwtAlgorithm::EdDSA => {
+            EncodingKey::from_ed_pem(pem(&key.private_key_pem, key, "private")?)?
+        }
     };
-    let token = encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret("secret".as_ref()),
-    )?;
-    Ok(token)
+    Ok(encoding_key)
 }
 
-pub fn verify_token(token: &str) -> Result<Claims> {
-    let token_data = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret("secret".as_ref()),
-        &Validation::default(),
-    )?;
-    Ok(token_data.claims)
+fn decoding_key(key: &JwtKeyConfig) -> Result<DecodingKey> {
+    let decoding_key = match key.algorithm {
+        JwtAlgorithm::HS256 => DecodingKey::from_secret(pem(&key.secret, key, "secret")?),
+        JwtAlgorithm::RS256 => DecodingKey::from_rsa_pem(pem(&key.public_key_pem, key, "public")?)?,
+        JwtAlgorithm::EdDSA => DecodingKey::from_ed_pem(pem(&key.public_key_pem, key, "public")?)?,
+    };
+    Ok(decoding_key)
 }
 
-// 包装成一个中间件
-pub struct JwtMiddleware;
-
-#[async_trait::async_trait]
-impl<B> FromRequestParts<B> for JwtMiddleware
-where
-    B: Send + Sync,
-{
-    type Rejection = Response;
-
-    async fn from_request_parts(parts: &mut Parts, _body: &B) -> Result<Self, Self::Rejection> {
-        let mut headers = parts.headers.clone();
-        let token = headers
-            .remove("Authorization")
-            .ok_or_else(|| error_response(4001, "Invalid token".to_string()))?;
-        let token = token
-            .to_str()
-            .map_err(|_| error_response(4001, "Invalid token".to_string()))?;
-        let token = token.replace("Bearer ", "");
-        let claims =
-            verify_token(&token).map_err(|_| error_response(4001, "Invalid token".to_string()))?;
-        let now = Local::now().timestamp() as usize;
-        if now > claims.exp {
-            return Err(error_response(4001, "Invalid token".to_string()));
+impl JwtKeys {
+    pub fn new(config: &JwtConfig) -> Result<Self> {
+        let active = config
+            .keys
+            .iter()
+            .find(|key| key.kid == config.active_kid)
+            .ok_or_else(|| anyhow!("no key with kid `{}`", config.active_kid))?;
+        let mut verifying_keys = HashMap::new();
+        for key in &config.keys {
+            let verifying_key = VerifyingKey {
+                algorithm: algorithm(key.algorithm),
+                key: decoding_key(key)
+                    .map_err(|err| anyhow!("invalid key `{}`: {err}", key.kid))?,
+            };
+            verifying_keys.insert(key.kid.clone(), verifying_key);
+        }
+        Ok(Self {
+            issuer: config.issuer.clone(),
+            audience: config.audience.clone(),
+            ttl: config.access_token_ttl,
+            active_kid: active.kid.clone(),
+            algorithm: algorithm(active.algorithm),
+            encoding_key: encoding_key(active)
+                .map_err(|err| anyhow!("invalid key `{}`: {err}", active.kid))?,
+            verifying_keys,
+        })
+    }
+
+    pub fn access_token_ttl(&self) -> chrono::Duration {
+        self.ttl
+    }
+
+    pub fn generate_token(&self, user_id: i32, session_id: i32) -> Result<String> {
+        let now = Local::now();
+        let claims = Claims {
+            sub: user_id.to_string(),
+            iss: self.issuer.clone(),
+            aud: self.audience.clone(),
+            iat: now.timestamp(),
+            nbf: now.timestamp(),
+            exp: (now + self.ttl).timestamp(),
+            sid: session_id,
+        };
+        let mut header = Header::new(self.algorithm);
+        header.kid = Some(self.active_kid.clone());
+        let token = encode(&header, &claims, &self.encoding_key)?;
+        Ok(token)
+    }
+
+    pub fn verify_token(&self, token: &str) -> Result<Claims> {
+        let header = decode_header(token)?;
+        let kid = header.kid.ok_or_else(|| anyhow!("token has no kid"))?;
+        let key = self
+            .verifying_keys
+            .get(&kid)
+            .ok_or_else(|| anyhow!("unknown kid `{kid}`"))?;
+
+        // 只接受该 kid 配置的算法, 防止算法混淆
+        let mut validation = Validation::new(key.algorithm);
+        validation.leeway = LEEWAY_SECS as u64;
+        validation.validate_nbf = true;
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&[&self.audience]);
+        validation.set_required_spec_claims(&["sub", "iss", "aud", "iat", "nbf", "exp"]);
+        let claims = decode::<Claims>(token, &key.key, &validation)?.claims;
+        if claims.iat > Local::now().timestamp() + LEEWAY_SECS {
+            bail!("token issued in the future");
         }
+        Ok(claims)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/jwt");
+
+    fn hs256_key(kid: &str, secret: &str) -> JwtKeyConfig {
+        JwtKeyConfig {
+            kid: kid.to_string(),
+            algorithm: JwtAlgorithm::HS256,
+            secret: Some(secret.to_string()),
+            private_key_pem: None,
+            public_key_pem: None,
+        }
+    }
+
+    fn pem_key(kid: &str, algorithm: JwtAlgorithm, name: &str, can_sign: bool) -> JwtKeyConfig {
+        let read = |suffix: &str| {
+            std::fs::read_to_string(format!("{FIXTURES}/{name}_{suffix}.pem")).unwrap()
+        };
+        JwtKeyConfig {
+            kid: kid.to_string(),
+            algorithm,
+            secret: None,
+            private_key_pem: can_sign.then(|| read("private")),
+            public_key_pem: Some(read("public")),
+        }
+    }
+
+    fn config(active_kid: &str, keys: Vec<JwtKeyConfig>) -> JwtConfig {
+        JwtConfig {
+            issuer: "mithril".to_string(),
+            audience: "mithril".to_string(),
+            access_token_ttl: chrono::Duration::minutes(15),
+            active_kid: active_kid.to_string(),
+            keys,
+        }
+    }
+
+    #[test]
+    fn test_generate_and_verify_each_algorithm() {
+        let keys = vec![
+            hs256_key("hs", "0123456789abcdef0123456789abcdef"),
+            pem_key("rs", JwtAlgorithm::RS256, "rsa", true),
+            pem_key("ed", JwtAlgorithm::EdDSA, "ed25519", true),
+        ];
+        for kid in ["hs", "rs", "ed"] {
+            let jwt = JwtKeys::new(&config(kid, keys.clone())).unwrap();
+            let token = jwt.generate_token(42, 1).unwrap();
+            assert_eq!(decode_header(&token).unwrap().kid.as_deref(), Some(kid));
+            let claims = jwt.verify_token(&token).unwrap();
+            assert_eq!(claims.sub, "42");
+            assert_eq!(claims.sid, 1);
+            assert_eq!(claims.iss, "mithril");
+            assert_eq!(claims.aud, "mithril");
+        }
+    }
+
+    #[test]
+    fn test_rotated_key_still_verifies() {
+        let old = JwtKeys::new(&config(
+            "2024-01",
+            vec![pem_key("2024-01", JwtAlgorithm::EdDSA, "ed25519", true)],
+        ))
+        .unwrap();
+        let token = old.generate_token(7, 1).unwrap();
+
+        // 新密钥签发, 旧密钥只保留公钥用于校验
+        let rotated = JwtKeys::new(&config(
+            "2024-02",
+            vec![
+                pem_key("2024-02", JwtAlgorithm::RS256, "rsa", true),
+                pem_key("2024-01", JwtAlgorithm::EdDSA, "ed25519", false),
+            ],
+        ))
+        .unwrap();
+        assert_eq!(rotated.verify_token(&token).unwrap().sub, "7");
+
+        let retired = JwtKeys::new(&config(
+            "2024-02",
+            vec![pem_key("2024-02", JwtAlgorithm::RS256, "rsa", true)],
+        ))
+        .unwrap();
+        assert!(retired.verify_token(&token).is_err());
+    }
+
+    #[test]
+    fn test_rejects_invalid_claims() {
+        let secret = "0123456789abcdef0123456789abcdef";
+        let jwt = JwtKeys::new(&config("hs", vec![hs256_key("hs", secret)])).unwrap();
+        let now = Local::now().timestamp();
+        let valid = Claims {
+            sub: "1".to_string(),
+            iss: "mithril".to_string(),
+            aud: "mithril".to_string(),
+            iat: now,
+            nbf: now,
+            exp: now + 600,
+            sid: 1,
+        };
+        let sign = |claims: &Claims, kid: Option<&str>| {
+            let mut header = Header::new(Algorithm::HS256);
+            header.kid = kid.map(str::to_string);
+            encode(
+                &header,
+                claims,
+                &EncodingKey::from_secret(secret.as_bytes()),
+            )
+            .unwrap()
+        };
+
+        assert!(jwt.verify_token(&sign(&valid, Some("hs"))).is_ok());
+        assert!(jwt.verify_token(&sign(&valid, None)).is_err());
+        assert!(jwt.verify_token(&sign(&valid, Some("other"))).is_err());
+
+        let invalid = [
+            Claims {
+                iss: "someone-else".to_string(),
+                ..valid.clone()
+            },
+            Claims {
+                aud: "another-api".to_string(),
+                ..valid.clone()
+            },
+            Claims {
+                nbf: now + 3600,
+                ..valid.clone()
+            },
+            Claims {
+                iat: now + 3600,
+                ..valid.clone()
+            },
+            Claims {
+                exp: now - 3600,
+                ..valid.clone()
+            },
+        ];
+        for claims in &invalid {
+            assert!(jwt.verify_token(&sign(claims, Some("hs"))).is_err());
+        }
+    }
 
-        claims
-            .sub
-            .parse::<i32>()
-            .map_err(|_| error_response(4001, "Invalid token".to_string()))?;
-        // 在上下文写入用户id
-        parts.extensions.insert(claims.sub.parse::<i32>().unwrap());
-        Ok(JwtMiddleware)
+    #[test]
+    fn test_rejects_algorithm_mismatch() {
+        let jwt = JwtKeys::new(&config(
+            "rs",
+            vec![pem_key("rs", JwtAlgorithm::RS256, "rsa", true)],
+        ))
+        .unwrap();
+        // 使用公钥作为 HMAC 密钥伪造的 token 不应通过校验
+        let public_key = std::fs::read(format!("{FIXTURES}/rsa_public.pem")).unwrap();
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some("rs".to_string());
+        let now = Local::now().timestamp();
+        let claims = Claims {
+            sub: "1".to_string(),
+            iss: "mithril".to_string(),
+            aud: "mithril".to_string(),
+            iat: now,
+            nbf: now,
+            exp: now + 600,
+            sid: 1,
+        };
+        let forged = encode(&header, &claims, &EncodingKey::from_secret(&public_key)).unwrap();
+        assert!(jwt.verify_token(&forged).is_err());
     }
 }
@@ -1,3 +1,5 @@
 pub mod encryption;
 pub mod jwt;
+pub mod password_policy;
+pub mod totp;
 pub mod verification;
@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+
+use lazy_static::lazy_static;
+
+// 常见及已泄露的密码列表, 编译期嵌入二进制
+const COMMON_PASSWORDS: &str = include_str!("../../assets/common-passwords.txt");
+
+lazy_static! {
+    static ref COMMON: HashSet<&'static str> = COMMON_PASSWORDS
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    pub reject_common: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            max_length: 128,
+            require_lowercase: false,
+            require_uppercase: false,
+            require_digit: false,
+            require_symbol: false,
+            reject_common: true,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    // 返回第一条不满足的规则
+    pub fn check(&self, password: &str) -> Result<(), String> {
+        let length = password.chars().count();
+        if length < self.min_length {
+            return Err(format!(
+                "Password must be at least {} characters",
+                self.min_length
+            ));
+        }
+        if length > self.max_length {
+            return Err(format!(
+                "Password must be at most {} characters",
+                self.max_length
+            ));
+        }
+        if self.require_lowercase && !password.chars().any(char::is_lowercase) {
+            return Err("Password must contain a lowercase letter".to_string());
+        }
+        if self.require_uppercase && !password.chars().any(char::is_uppercase) {
+            return Err("Password must contain an uppercase letter".to_string());
+        }
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            return Err("Password must contain a digit".to_string());
+        }
+        if self.require_symbol && password.chars().all(char::is_alphanumeric) {
+            return Err("Password must contain a symbol".to_string());
+        }
+        if self.reject_common && COMMON.contains(password.to_lowercase().as_str()) {
+            return Err("Password is too common".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_policy() {
+        let policy = PasswordPolicy::default();
+        assert!(policy.check("short").is_err());
+        assert!(policy.check("Password123").is_err());
+        assert!(policy.check("correct-horse-battery").is_ok());
+        assert!(policy.check(&"a".repeat(129)).is_err());
+    }
+
+    #[test]
+    fn test_character_classes() {
+        let policy = PasswordPolicy {
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: true,
+            ..PasswordPolicy::default()
+        };
+        assert!(policy.check("CORRECT-HORSE-1").is_err());
+        assert!(policy.check("correct-horse-1").is_err());
+        assert!(policy.check("Correct-Horse").is_err());
+        assert!(policy.check("CorrectHorse1").is_err());
+        assert!(policy.check("Correct-Horse-1").is_ok());
+    }
+}
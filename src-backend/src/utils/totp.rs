@@ -0,0 +1,173 @@
+use reqwest::Url;
+use ring::{
+    hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+
+// RFC 6238 的常用参数, 主流验证器应用只支持 SHA1 / 6 位 / 30 秒
+pub const DIGITS: u32 = 6;
+pub const PERIOD_SECS: i64 = 30;
+// 160 位密钥, 与 HMAC-SHA1 的输出长度一致
+const SECRET_LEN: usize = 20;
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+// RFC 4648 base32, 不带填充
+pub fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    for chunk in bytes.chunks(5) {
+        let mut buf = [0u8; 5];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        let value = buf
+            .iter()
+            .fold(0u64, |acc, byte| (acc << 8) | u64::from(*byte));
+        let chars = (chunk.len() * 8).div_ceil(5);
+        for i in 0..chars {
+            let index = (value >> (35 - i * 5)) & 0x1f;
+            out.push(BASE32_ALPHABET[index as usize] as char);
+        }
+    }
+    out
+}
+
+// 忽略大小写, 空格和填充, 遇到非法字符返回 None
+pub fn base32_decode(input: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() * 5 / 8);
+    let mut buffer = 0u64;
+    let mut bits = 0;
+    for c in input.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let c = c.to_ascii_uppercase() as u8;
+        let value = BASE32_ALPHABET.iter().position(|a| *a == c)? as u64;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+// 生成随机密钥, 以 base32 返回
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; SECRET_LEN];
+    SystemRandom::new().fill(&mut bytes).unwrap();
+    base32_encode(&bytes)
+}
+
+pub fn step_at(timestamp: i64) -> i64 {
+    timestamp.div_euclid(PERIOD_SECS)
+}
+
+// RFC 4226 HOTP, 截断为 DIGITS 位
+fn hotp(key: &[u8], counter: u64) -> u32 {
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, key);
+    let digest = hmac::sign(&key, &counter.to_be_bytes());
+    let digest = digest.as_ref();
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    binary % 10u32.pow(DIGITS)
+}
+
+pub fn code_at(secret: &str, step: i64) -> Option<String> {
+    let key = base32_decode(secret)?;
+    Some(format!(
+        "{:0width$}",
+        hotp(&key, step as u64),
+        width = DIGITS as usize
+    ))
+}
+
+// 在当前时间步前后 skew 个时间步内查找匹配的验证码, 返回匹配的时间步
+pub fn verify(secret: &str, code: &str, timestamp: i64, skew: i64) -> Option<i64> {
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let current = step_at(timestamp);
+    (current - skew..=current + skew).find(|step| {
+        code_at(secret, *step).is_some_and(|expected| {
+            ring::constant_time::verify_slices_are_equal(expected.as_bytes(), code.as_bytes())
+                .is_ok()
+        })
+    })
+}
+
+// 供验证器应用扫描的 otpauth:// 地址, 见 Google Authenticator 的 Key Uri Format
+pub fn otpauth_uri(issuer: &str, account: &str, secret: &str) -> String {
+    let mut url = Url::parse("otpauth://totp/").unwrap();
+    url.set_path(&format!("{issuer}:{account}"));
+    url.query_pairs_mut()
+        .append_pair("secret", secret)
+        .append_pair("issuer", issuer)
+        .append_pair("algorithm", "SHA1")
+        .append_pair("digits", &DIGITS.to_string())
+        .append_pair("period", &PERIOD_SECS.to_string());
+    url.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base32_roundtrip() {
+        // RFC 4648 第 10 节的测试向量
+        for (raw, encoded) in [
+            ("", ""),
+            ("f", "MY"),
+            ("fo", "MZXQ"),
+            ("foo", "MZXW6"),
+            ("foob", "MZXW6YQ"),
+            ("fooba", "MZXW6YTB"),
+            ("foobar", "MZXW6YTBOI"),
+        ] {
+            assert_eq!(base32_encode(raw.as_bytes()), encoded);
+            assert_eq!(base32_decode(encoded).unwrap(), raw.as_bytes());
+        }
+        assert_eq!(base32_decode("mzxw 6ytb oi==").unwrap(), b"foobar");
+        assert!(base32_decode("MZXW1").is_none());
+        assert_eq!(base32_decode(&generate_secret()).unwrap().len(), SECRET_LEN);
+    }
+
+    #[test]
+    fn test_rfc6238_vectors() {
+        // RFC 6238 附录 B 的 SHA1 测试向量, 取后 6 位
+        let secret = base32_encode(b"12345678901234567890");
+        for (timestamp, code) in [
+            (59, "287082"),
+            (1111111109, "081804"),
+            (1111111111, "050471"),
+            (1234567890, "005924"),
+            (2000000000, "279037"),
+        ] {
+            assert_eq!(code_at(&secret, step_at(timestamp)).unwrap(), code);
+        }
+    }
+
+    #[test]
+    fn test_verify_allows_clock_skew() {
+        let secret = generate_secret();
+        let now = 1_700_000_000;
+        let previous = code_at(&secret, step_at(now) - 1).unwrap();
+        assert_eq!(verify(&secret, &previous, now, 1), Some(step_at(now) - 1));
+        assert_eq!(verify(&secret, &previous, now, 0), None);
+        let stale = code_at(&secret, step_at(now) - 2).unwrap();
+        assert_eq!(verify(&secret, &stale, now, 1), None);
+        assert_eq!(verify(&secret, "12345", now, 1), None);
+        assert_eq!(verify(&secret, "abcdef", now, 1), None);
+    }
+
+    #[test]
+    fn test_otpauth_uri() {
+        assert_eq!(
+            otpauth_uri("Mithril", "user@example.com", "JBSWY3DPEHPK3PXP"),
+            "otpauth://totp/Mithril:user@example.com?secret=JBSWY3DPEHPK3PXP&issuer=Mithril&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+}